@@ -0,0 +1,149 @@
+//! Bearer profile commands used by the built-in HTTP and FTP applications
+mod responses;
+mod types;
+
+use atat::atat_derive::AtatCmd;
+pub use responses::*;
+pub use types::*;
+
+use super::NoResponse;
+
+/// 9.2.1 AT+SAPBR Bearer Settings for Applications Based on IP
+///
+/// Set a bearer parameter, e.g. "Contype" to "GPRS" or "APN" to the access point name.
+#[derive(AtatCmd)]
+#[at_cmd("+SAPBR=3,", NoResponse, value_sep = false, termination = "\r")]
+pub struct SetBearerParameter<'a> {
+    pub cid: u8,
+    #[at_arg(len = 8)]
+    pub tag: &'a str,
+    #[at_arg(len = 64)]
+    pub value: &'a str,
+}
+
+/// 9.2.1 AT+SAPBR Bearer Settings for Applications Based on IP
+///
+/// Open the GPRS bearer.
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+SAPBR=1,",
+    NoResponse,
+    timeout_ms = 85_000,
+    value_sep = false,
+    termination = "\r"
+)]
+pub struct OpenBearer {
+    pub cid: u8,
+}
+
+/// 9.2.1 AT+SAPBR Bearer Settings for Applications Based on IP
+///
+/// Close the GPRS bearer.
+#[derive(AtatCmd)]
+#[at_cmd(
+    "+SAPBR=0,",
+    NoResponse,
+    timeout_ms = 65_000,
+    value_sep = false,
+    termination = "\r"
+)]
+pub struct CloseBearer {
+    pub cid: u8,
+}
+
+/// 9.2.1 AT+SAPBR Bearer Settings for Applications Based on IP
+///
+/// Query the GPRS bearer status and IP address.
+#[derive(AtatCmd)]
+#[at_cmd("+SAPBR=2,", BearerStatus, value_sep = false, termination = "\r")]
+pub struct QueryBearer {
+    pub cid: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_hex::assert_eq_hex;
+    use atat::{AtatCmd, AtatIngress, Response};
+    use embedded_nal_async::Ipv4Addr;
+
+    use crate::{commands::AtatCmdEx, services::ingress_mock::setup_ingress};
+
+    use super::*;
+
+    #[test]
+    fn can_set_bearer_parameter() {
+        let cmd = SetBearerParameter {
+            cid: 1,
+            tag: "Contype",
+            value: "GPRS",
+        };
+        assert_eq_hex!(
+            b"AT+SAPBR=3,1,\"Contype\",\"GPRS\"\r",
+            cmd.to_vec().as_slice()
+        );
+
+        let cmd = SetBearerParameter {
+            cid: 1,
+            tag: "APN",
+            value: "internet",
+        };
+        assert_eq_hex!(
+            b"AT+SAPBR=3,1,\"APN\",\"internet\"\r",
+            cmd.to_vec().as_slice()
+        );
+    }
+
+    #[test]
+    fn can_open_bearer() {
+        let cmd = OpenBearer { cid: 1 };
+        assert_eq_hex!(b"AT+SAPBR=1,1\r", cmd.to_vec().as_slice());
+    }
+
+    #[test]
+    fn can_close_bearer() {
+        let cmd = CloseBearer { cid: 1 };
+        assert_eq_hex!(b"AT+SAPBR=0,1\r", cmd.to_vec().as_slice());
+    }
+
+    #[test]
+    fn can_query_bearer() {
+        let cmd = QueryBearer { cid: 1 };
+        assert_eq_hex!(b"AT+SAPBR=2,1\r", cmd.to_vec().as_slice());
+
+        let (mut ingress, res_sub) = setup_ingress!();
+        ingress
+            .try_write(b"\r\n+SAPBR: 1,1,\"10.89.193.1\"\r\n\r\nOK\r\n")
+            .unwrap();
+
+        let response = res_sub.try_get().unwrap();
+        let response: &Response<400> = &response.borrow();
+        if let Response::Ok(message) = response {
+            let response = cmd.parse(Ok(message)).unwrap();
+            assert_eq!(1, response.cid);
+            assert_eq!(BearerStatusValue::Connected, response.status);
+            assert_eq!(Some(Ipv4Addr::new(10, 89, 193, 1)), response.ip());
+        } else {
+            panic!("Invalid response");
+        }
+    }
+
+    #[test]
+    fn closed_bearer_has_no_ip() {
+        let cmd = QueryBearer { cid: 1 };
+
+        let (mut ingress, res_sub) = setup_ingress!();
+        ingress
+            .try_write(b"\r\n+SAPBR: 1,3,\"0.0.0.0\"\r\n\r\nOK\r\n")
+            .unwrap();
+
+        let response = res_sub.try_get().unwrap();
+        let response: &Response<400> = &response.borrow();
+        if let Response::Ok(message) = response {
+            let response = cmd.parse(Ok(message)).unwrap();
+            assert_eq!(BearerStatusValue::Closed, response.status);
+            assert_eq!(None, response.ip());
+        } else {
+            panic!("Invalid response");
+        }
+    }
+}
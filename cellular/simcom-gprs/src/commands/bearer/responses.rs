@@ -0,0 +1,27 @@
+use atat::atat_derive::AtatResp;
+use embedded_nal_async::Ipv4Addr;
+use heapless::String;
+
+use super::BearerStatusValue;
+
+/// 9.2.1 AT+SAPBR Bearer Settings for Applications Based on IP
+#[derive(AtatResp)]
+pub struct BearerStatus {
+    #[at_arg(position = 0)]
+    pub cid: u8,
+    #[at_arg(position = 1)]
+    pub status: BearerStatusValue,
+    #[at_arg(position = 2)]
+    pub ip: String<15>,
+}
+
+impl BearerStatus {
+    /// Get the bearer IP, or None if the bearer is not connected
+    pub fn ip(&self) -> Option<Ipv4Addr> {
+        if self.status == BearerStatusValue::Connected {
+            self.ip.parse().ok()
+        } else {
+            None
+        }
+    }
+}
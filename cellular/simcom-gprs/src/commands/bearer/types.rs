@@ -0,0 +1,10 @@
+use atat::atat_derive::AtatEnum;
+
+#[derive(AtatEnum, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BearerStatusValue {
+    Connecting = 0,
+    Connected = 1,
+    Closing = 2,
+    Closed = 3,
+}
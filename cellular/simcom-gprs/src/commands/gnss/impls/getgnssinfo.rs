@@ -0,0 +1,65 @@
+use core::str::FromStr;
+
+use atat::{atat_derive::AtatCmd, AtatCmd};
+
+use crate::commands::{
+    gnss::{GetGnssInfo, GnssInfo},
+    NoResponse,
+};
+
+impl AtatCmd for GetGnssInfo {
+    type Response = GnssInfo;
+
+    const MAX_LEN: usize = "AT+CGNSINF\r".len();
+
+    fn write(&self, buf: &mut [u8]) -> usize {
+        GetGnssInfoInner.write(buf)
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], atat::InternalError>,
+    ) -> Result<Self::Response, atat::Error> {
+        let resp = core::str::from_utf8(resp?).map_err(|_| atat::Error::Parse)?;
+        let mut fields = resp
+            .strip_prefix("+CGNSINF: ")
+            .ok_or(atat::Error::Parse)?
+            .split(',');
+        let mut next = || fields.next().ok_or(atat::Error::Parse);
+
+        let run_status = next()? == "1";
+        let fix_status = next()? == "1";
+        let _utc = next()?;
+        let latitude = optional(next()?)?;
+        let longitude = optional(next()?)?;
+        let altitude = optional(next()?)?;
+        let speed = optional(next()?)?;
+        // Course over ground, fix mode, reserved1, hdop, pdop, vdop, reserved2
+        for _ in 0..7 {
+            next()?;
+        }
+        let satellites_in_view = optional(next()?)?;
+
+        Ok(GnssInfo {
+            run_status,
+            fix_status,
+            latitude,
+            longitude,
+            altitude,
+            speed,
+            satellites_in_view,
+        })
+    }
+}
+
+fn optional<T: FromStr>(field: &str) -> Result<Option<T>, atat::Error> {
+    if field.is_empty() {
+        Ok(None)
+    } else {
+        field.parse().map(Some).map_err(|_| atat::Error::Parse)
+    }
+}
+
+#[derive(Clone, AtatCmd)]
+#[at_cmd("+CGNSINF", NoResponse, termination = "\r")]
+struct GetGnssInfoInner;
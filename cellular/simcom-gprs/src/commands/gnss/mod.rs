@@ -0,0 +1,91 @@
+//! GNSS commands for SIM808 class parts
+mod impls;
+mod responses;
+mod types;
+
+use super::NoResponse;
+use atat::atat_derive::AtatCmd;
+pub use responses::*;
+pub use types::*;
+
+/// AT+CGNSPWR GNSS Power Control
+#[derive(AtatCmd)]
+#[at_cmd("+CGNSPWR", NoResponse, termination = "\r")]
+pub struct SetGnssPower {
+    pub mode: GnssPowerMode,
+}
+
+/// AT+CGNSINF GNSS Navigation Information Parsed From NMEA Sentences
+///
+/// The information is returned as
+/// +CGNSINF: <run status>,<fix status>,<utc date & time>,<latitude>,<longitude>,<msl altitude>,
+/// <speed over ground>,<course over ground>,<fix mode>,<reserved1>,<hdop>,<pdop>,<vdop>,<reserved2>,
+/// <gnss satellites in view>,<gnss satellites used>,...
+pub struct GetGnssInfo;
+
+#[cfg(test)]
+mod tests {
+    use assert_hex::assert_eq_hex;
+    use atat::{AtatCmd, AtatIngress, Response};
+
+    use crate::{commands::AtatCmdEx, services::ingress_mock::setup_ingress};
+
+    use super::*;
+
+    #[test]
+    fn can_set_gnss_power() {
+        let cmd = SetGnssPower {
+            mode: GnssPowerMode::On,
+        };
+        assert_eq_hex!(b"AT+CGNSPWR=1\r", cmd.to_vec().as_slice());
+    }
+
+    #[test]
+    fn can_get_gnss_info() {
+        let cmd = GetGnssInfo;
+        assert_eq_hex!(b"AT+CGNSINF\r", cmd.to_vec().as_slice());
+
+        let (mut ingress, res_sub) = setup_ingress!();
+        ingress
+            .try_write(b"\r\n+CGNSINF: 1,1,20161019111009.000,31.221733,121.354517,-1.200,0.19,0.0,1,,1.4,1.6,0.8,,11,6,,,37,,\r\n\r\nOK\r\n")
+            .unwrap();
+
+        let response = res_sub.try_get().unwrap();
+        let response: &Response<400> = &response.borrow();
+        if let Response::Ok(message) = response {
+            let response = cmd.parse(Ok(message)).unwrap();
+            assert!(response.fix_status);
+            assert_eq!(Some(31.221733), response.latitude);
+            assert_eq!(Some(121.354517), response.longitude);
+            assert_eq!(Some(-1.2), response.altitude);
+            assert_eq!(Some(0.19), response.speed);
+            assert_eq!(Some(11), response.satellites_in_view);
+        } else {
+            panic!("Invalid response");
+        }
+    }
+
+    #[test]
+    fn can_get_gnss_info_without_fix() {
+        let cmd = GetGnssInfo;
+
+        let (mut ingress, res_sub) = setup_ingress!();
+        ingress
+            .try_write(b"\r\n+CGNSINF: 1,0,,,,,,,,,,,,,,,,,,,\r\n\r\nOK\r\n")
+            .unwrap();
+
+        let response = res_sub.try_get().unwrap();
+        let response: &Response<400> = &response.borrow();
+        if let Response::Ok(message) = response {
+            let response = cmd.parse(Ok(message)).unwrap();
+            assert!(!response.fix_status);
+            assert_eq!(None, response.latitude);
+            assert_eq!(None, response.longitude);
+            assert_eq!(None, response.altitude);
+            assert_eq!(None, response.speed);
+            assert_eq!(None, response.satellites_in_view);
+        } else {
+            panic!("Invalid response");
+        }
+    }
+}
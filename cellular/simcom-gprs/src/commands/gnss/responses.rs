@@ -0,0 +1,22 @@
+use atat::AtatResp;
+
+/// Response to AT+CGNSINF
+///
+/// The coordinate fields are `None` while there is no fix.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GnssInfo {
+    pub run_status: bool,
+    pub fix_status: bool,
+    /// Latitude in degrees
+    pub latitude: Option<f64>,
+    /// Longitude in degrees
+    pub longitude: Option<f64>,
+    /// MSL altitude in meters
+    pub altitude: Option<f32>,
+    /// Speed over ground in km/h
+    pub speed: Option<f32>,
+    pub satellites_in_view: Option<u8>,
+}
+
+impl AtatResp for GnssInfo {}
@@ -0,0 +1,8 @@
+use atat::atat_derive::AtatEnum;
+
+#[derive(AtatEnum, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GnssPowerMode {
+    Off = 0,
+    On = 1,
+}
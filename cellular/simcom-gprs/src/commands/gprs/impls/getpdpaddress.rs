@@ -0,0 +1,58 @@
+use atat::{
+    atat_derive::AtatCmd,
+    nom::{bytes, character, combinator, sequence},
+    AtatCmd,
+};
+use heapless::String;
+
+use crate::{
+    commands::{
+        gprs::{GetPdpAddress, PdpAddress},
+        parse::complete::prefix,
+        NoResponse,
+    },
+    ContextId,
+};
+
+impl AtatCmd for GetPdpAddress {
+    type Response = PdpAddress;
+
+    const MAX_LEN: usize = "AT+CGPADDR=X\r".len();
+
+    fn write(&self, buf: &mut [u8]) -> usize {
+        let inner = GetPdpAddressInner { cid: self.cid };
+        inner.write(buf)
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], atat::InternalError>,
+    ) -> Result<Self::Response, atat::Error> {
+        // The address is quoted by some firmware revisions and unquoted by others
+        if let Ok((reminder, (_, cid, _, _, address, _))) = sequence::tuple::<_, _, (), _>((
+            prefix("+CGPADDR:"),
+            character::complete::u8,
+            bytes::complete::tag(","),
+            combinator::opt(bytes::complete::tag("\"")),
+            bytes::complete::is_not("\""),
+            combinator::opt(bytes::complete::tag("\"")),
+        ))(resp?)
+        {
+            if reminder.is_empty() {
+                let address = core::str::from_utf8(address).map_err(|_| atat::Error::Parse)?;
+                return Ok(PdpAddress {
+                    cid: ContextId(cid),
+                    address: String::try_from(address).map_err(|_| atat::Error::Parse)?,
+                });
+            }
+        }
+
+        Err(atat::Error::Parse)
+    }
+}
+
+#[derive(Clone, AtatCmd)]
+#[at_cmd("+CGPADDR", NoResponse, termination = "\r")]
+struct GetPdpAddressInner {
+    pub cid: ContextId,
+}
@@ -0,0 +1,2 @@
+mod getpdpaddress;
+mod pdptype;
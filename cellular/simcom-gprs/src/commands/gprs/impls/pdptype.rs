@@ -0,0 +1,39 @@
+use atat::AtatLen;
+use serde::{Serialize, Serializer};
+
+use crate::commands::gprs::PdpType;
+
+impl AtatLen for PdpType {
+    const LEN: usize = 8;
+}
+
+impl Serialize for PdpType {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            PdpType::Ipv4 => serializer.serialize_bytes(b"\"IP\""),
+            PdpType::Ipv6 => serializer.serialize_bytes(b"\"IPV6\""),
+            PdpType::Ipv4v6 => serializer.serialize_bytes(b"\"IPV4V6\""),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use atat::serde_at::{to_slice, SerializeOptions};
+
+    use crate::commands::gprs::PdpType;
+
+    #[test]
+    fn can_serialize() {
+        let options = SerializeOptions {
+            value_sep: false,
+            ..SerializeOptions::default()
+        };
+        let mut buf = [0; 32];
+        let len = to_slice(&PdpType::Ipv4v6, "", &mut buf, options).unwrap();
+        assert_eq!(b"\"IPV4V6\"", &buf[..len]);
+    }
+}
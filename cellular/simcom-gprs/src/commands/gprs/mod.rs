@@ -1,3 +1,4 @@
+mod impls;
 mod responses;
 mod types;
 
@@ -39,8 +40,8 @@ pub struct SetGPRSAttached {
 pub struct SetPDPContextDefinition<'a> {
     #[at_arg(position = 0)]
     pub cid: ContextId,
-    #[at_arg(position = 1, len = 6)]
-    pub pdp_type: &'a str,
+    #[at_arg(position = 1)]
+    pub pdp_type: PdpType,
     #[at_arg(position = 2, len = 99)]
     pub apn: &'a str,
 }
@@ -57,6 +58,13 @@ pub struct ActivateOrDeactivatePDPContext {
 #[at_cmd("+CGACT?", NoResponse, timeout_ms = 150_000, termination = "\r")]
 pub struct GetPDPContextStates;
 
+/// 7.2.7 AT+CGPADDR Show PDP Address
+///
+/// Unlike AT+CIFSR, the address is replied in a well-formed response terminated by OK.
+pub struct GetPdpAddress {
+    pub cid: ContextId,
+}
+
 /// 7.2.10 AT+CGREG Network Registration Status
 #[derive(AtatCmd)]
 #[at_cmd("+CGREG?", GPRSNetworkRegistrationStatus, termination = "\r")]
@@ -103,7 +111,7 @@ mod tests {
     fn can_set_pdp_context_definition() {
         let cmd = SetPDPContextDefinition {
             cid: ContextId(1),
-            pdp_type: "IP",
+            pdp_type: PdpType::Ipv4,
             apn: "internet",
         };
 
@@ -113,6 +121,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_set_dual_stack_pdp_context_definition() {
+        let cmd = SetPDPContextDefinition {
+            cid: ContextId(1),
+            pdp_type: PdpType::Ipv4v6,
+            apn: "apn",
+        };
+
+        assert_eq_hex!(
+            b"AT+CGDCONT=1,\"IPV4V6\",\"apn\"\r",
+            cmd.to_vec().as_slice()
+        );
+    }
+
     #[test]
     fn can_deactivate_pdp_context() {
         let cmd = ActivateOrDeactivatePDPContext {
@@ -127,6 +149,20 @@ mod tests {
         assert_eq_hex!(b"AT+CGACT=0,1\r", cmd.to_vec().as_slice());
     }
 
+    #[test]
+    fn can_get_pdp_address() {
+        let cmd = GetPdpAddress { cid: ContextId(1) };
+        assert_eq_hex!(b"AT+CGPADDR=1\r", cmd.to_vec().as_slice());
+
+        let response = cmd.parse(Ok(b"+CGPADDR: 1,10.0.109.44")).unwrap();
+        assert_eq!(ContextId(1), response.cid);
+        assert_eq!("10.0.109.44", response.address.as_str());
+
+        let response = cmd.parse(Ok(b"+CGPADDR: 2,\"10.0.109.45\"")).unwrap();
+        assert_eq!(ContextId(2), response.cid);
+        assert_eq!("10.0.109.45", response.address.as_str());
+    }
+
     #[test]
     fn can_get_gprs_network_registration_status() {
         let cmd = GetGPRSNetworkRegistrationStatus;
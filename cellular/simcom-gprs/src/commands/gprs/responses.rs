@@ -1,6 +1,8 @@
 use atat::atat_derive::{AtatEnum, AtatResp};
 use heapless::String;
 
+use crate::ContextId;
+
 use super::GPRSAttachedState;
 
 /// 7.2.1 AT+CGATT Attach or Detach from GPRS Service
@@ -10,6 +12,15 @@ pub struct GPRSAttached {
     pub state: GPRSAttachedState,
 }
 
+/// 7.2.7 AT+CGPADDR Show PDP Address
+#[derive(AtatResp)]
+pub struct PdpAddress {
+    #[at_arg(position = 0)]
+    pub cid: ContextId,
+    #[at_arg(position = 1)]
+    pub address: String<39>,
+}
+
 /// 7.2.10 AT+CGREG Network Registration Status
 #[derive(AtatResp)]
 pub struct GPRSNetworkRegistrationStatus {
@@ -33,7 +44,7 @@ pub enum GPRSNetworkRegistrationUrcConfig {
     EnabledWithLocation = 2,
 }
 
-#[derive(AtatEnum, Debug, PartialEq)]
+#[derive(AtatEnum, Clone, Copy, Debug, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum GPRSNetworkRegistrationStat {
     /// Not registered, the MT is not currently searching a new operator to register to
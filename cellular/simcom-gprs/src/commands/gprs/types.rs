@@ -13,3 +13,16 @@ pub enum PdpState {
     Deactivated = 0,
     Activated = 1,
 }
+
+/// The packet data protocol type of a PDP context
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PdpType {
+    /// "IP"
+    #[default]
+    Ipv4,
+    /// "IPV6"
+    Ipv6,
+    /// "IPV4V6", i.e. dual-stack
+    Ipv4v6,
+}
@@ -0,0 +1,77 @@
+use atat::{
+    atat_derive::AtatCmd,
+    nom::{branch, bytes, character, combinator, sequence},
+    AtatCmd,
+};
+
+use crate::commands::{
+    gsm::{Clock, GetClock},
+    parse::complete::prefix,
+    NoResponse,
+};
+
+impl AtatCmd for GetClock {
+    type Response = Clock;
+
+    const MAX_LEN: usize = "AT+CCLK?\r".len();
+
+    fn write(&self, buf: &mut [u8]) -> usize {
+        GetClockInner.write(buf)
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], atat::InternalError>,
+    ) -> Result<Self::Response, atat::Error> {
+        // +CCLK: "yy/MM/dd,hh:mm:ss±zz"
+        let (
+            reminder,
+            (_, _, year, _, month, _, day, _, hour, _, minute, _, second, sign, zone, _),
+        ) = sequence::tuple::<_, _, (), _>((
+            prefix("+CCLK:"),
+            bytes::complete::tag("\""),
+            character::complete::u8,
+            bytes::complete::tag("/"),
+            character::complete::u8,
+            bytes::complete::tag("/"),
+            character::complete::u8,
+            bytes::complete::tag(","),
+            character::complete::u8,
+            bytes::complete::tag(":"),
+            character::complete::u8,
+            bytes::complete::tag(":"),
+            character::complete::u8,
+            combinator::opt(branch::alt((
+                bytes::complete::tag("+"),
+                bytes::complete::tag("-"),
+            ))),
+            character::complete::u8,
+            bytes::complete::tag("\""),
+        ))(resp?)
+        .map_err(|_| atat::Error::Parse)?;
+
+        if !reminder.is_empty() {
+            return Err(atat::Error::Parse);
+        }
+
+        let timezone = i8::try_from(zone).map_err(|_| atat::Error::Parse)?;
+        let timezone = match sign {
+            Some(b"-") => -timezone,
+            _ => timezone,
+        };
+
+        Ok(Clock {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            timezone,
+        })
+    }
+}
+
+#[derive(Clone, AtatCmd)]
+#[at_cmd("+CCLK?", NoResponse, termination = "\r")]
+struct GetClockInner;
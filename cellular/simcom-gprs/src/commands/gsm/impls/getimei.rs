@@ -0,0 +1,46 @@
+use atat::{
+    atat_derive::AtatCmd,
+    nom::{bytes, character},
+    AtatCmd,
+};
+use heapless::String;
+
+use crate::commands::{
+    gsm::{GetImei, ImeiResponse},
+    NoResponse,
+};
+
+impl AtatCmd for GetImei {
+    type Response = ImeiResponse;
+
+    const MAX_LEN: usize = "AT+CGSN\r".len();
+
+    fn write(&self, buf: &mut [u8]) -> usize {
+        GetImeiInner.write(buf)
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], atat::InternalError>,
+    ) -> Result<Self::Response, atat::Error> {
+        let (reminder, imei) =
+            bytes::complete::take_while_m_n::<_, _, ()>(15, 15, |c: u8| c.is_ascii_digit())(resp?)
+                .map_err(|_| atat::Error::Parse)?;
+        let (reminder, _) =
+            character::complete::multispace0::<_, ()>(reminder).map_err(|_| atat::Error::Parse)?;
+
+        if !reminder.is_empty() {
+            return Err(atat::Error::Parse);
+        }
+
+        // The bytes are all ascii digits
+        let imei = core::str::from_utf8(imei).unwrap();
+        Ok(ImeiResponse {
+            imei: String::try_from(imei).unwrap(),
+        })
+    }
+}
+
+#[derive(Clone, AtatCmd)]
+#[at_cmd("+CGSN", NoResponse, termination = "\r")]
+struct GetImeiInner;
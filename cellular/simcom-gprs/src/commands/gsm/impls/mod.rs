@@ -1,2 +1,4 @@
 mod facility;
+mod getclock;
+mod getimei;
 mod pinstatuscode;
@@ -24,6 +24,16 @@ pub struct GetModelId;
 #[at_cmd("+CGMR", SoftwareVersionResponse, termination = "\r")]
 pub struct GetSoftwareVersion;
 
+/// 3.2.11 AT+CGSN Request Product Serial Number Identification
+///
+/// The IMEI is replied as bare digits.
+pub struct GetImei;
+
+/// AT+CCLK Clock
+///
+/// +CCLK: "yy/MM/dd,hh:mm:ss±zz"
+pub struct GetClock;
+
 /// 3.2.17 AT+CLCK Facility Lock
 #[derive(AtatCmd)]
 #[at_cmd("+CLCK", NoResponse, timeout_ms = 15_000, termination = "\r")]
@@ -36,6 +46,13 @@ pub struct SetFacilityLock<'a> {
     pub password: Option<&'a str>,
 }
 
+/// 3.2.18 AT+CLIP Calling Line Identification Presentation
+#[derive(AtatCmd)]
+#[at_cmd("+CLIP", NoResponse, timeout_ms = 15_000, termination = "\r")]
+pub struct SetCallerIdPresentation {
+    pub mode: CallerIdPresentation,
+}
+
 /// 3.2.20 Report Mobile Equipment Error
 #[derive(AtatCmd)]
 #[at_cmd("+CMEE", NoResponse, termination = "\r")]
@@ -58,6 +75,9 @@ pub struct SetOperatorSelection<'a> {
 }
 
 /// 3.2.28 AT+CPIN Enter PIN
+///
+/// The "+CPIN: <code>" line is digested as [`crate::commands::urc::Urc::PinStatus`] as it is also sent unsolicited,
+/// so the status is read from the URC and not from the response.
 #[derive(AtatCmd)]
 #[at_cmd("+CPIN?", NoResponse, timeout_ms = 5_000, termination = "\r")]
 pub struct GetPinStatus;
@@ -78,6 +98,16 @@ pub struct ChangePin<'a> {
     pub new_pin: &'a str,
 }
 
+/// Enter the PUK and set a new PIN when the SIM is in the SIM PUK state
+#[derive(AtatCmd)]
+#[at_cmd("+CPIN", NoResponse, timeout_ms = 5_000, termination = "\r")]
+pub struct EnterPuk<'a> {
+    #[at_arg(len = 8)]
+    pub puk: &'a str,
+    #[at_arg(len = 4)]
+    pub new_pin: &'a str,
+}
+
 // 3.2.29 AT+CPWD Change Password
 #[derive(AtatCmd)]
 #[at_cmd("+CPWD", NoResponse, timeout_ms = 15_000, termination = "\r")]
@@ -164,6 +194,48 @@ mod tests {
         assert_eq!(b"Revision:1308B04SIM800M32", response.version.as_ref());
     }
 
+    #[test]
+    fn can_get_imei() {
+        let cmd = GetImei;
+        assert_eq_hex!(b"AT+CGSN\r", cmd.to_vec().as_bytes());
+
+        let mut digester = SimcomDigester::new();
+        assert_eq!(
+            (DigestResult::Response(Ok(b"123456789012345")), 33),
+            digester.digest(b"AT+CGSN\r\r\n123456789012345\r\n\r\nOK\r\n")
+        );
+
+        let response = cmd.parse(Ok(b"123456789012345")).unwrap();
+        assert_eq!("123456789012345", response.imei.as_str());
+        assert!(cmd.parse(Ok(b"12345678901234")).is_err());
+        assert_eq!(Some(123456789012345), response.as_u64());
+    }
+
+    #[test]
+    fn can_get_clock() {
+        let cmd = GetClock;
+        assert_eq_hex!(b"AT+CCLK?\r", cmd.to_vec().as_bytes());
+
+        let response = cmd.parse(Ok(b"+CCLK: \"24/03/15,13:45:30+04\"")).unwrap();
+        assert_eq!(24, response.year);
+        assert_eq!(3, response.month);
+        assert_eq!(15, response.day);
+        assert_eq!(13, response.hour);
+        assert_eq!(45, response.minute);
+        assert_eq!(30, response.second);
+        assert_eq!(4, response.timezone);
+        assert_eq!(1710506730, response.to_unix_timestamp());
+    }
+
+    #[test]
+    fn can_get_clock_with_negative_timezone() {
+        let cmd = GetClock;
+
+        let response = cmd.parse(Ok(b"+CCLK: \"00/01/01,00:00:00-20\"")).unwrap();
+        assert_eq!(-20, response.timezone);
+        assert_eq!(946702800, response.to_unix_timestamp());
+    }
+
     #[test]
     fn can_set_facility_lock_disable_pin() {
         let cmd = SetFacilityLock {
@@ -192,6 +264,14 @@ mod tests {
         assert_eq_hex!(b"AT+CMEE=1\r", cmd.to_vec().as_bytes());
     }
 
+    #[test]
+    fn can_set_caller_id_presentation() {
+        let cmd = SetCallerIdPresentation {
+            mode: CallerIdPresentation::Enable,
+        };
+        assert_eq_hex!(b"AT+CLIP=1\r", cmd.to_vec().as_bytes());
+    }
+
     #[test]
     fn can_get_operator_selection() {
         let cmd = GetOperatorSelection;
@@ -201,6 +281,16 @@ mod tests {
         assert_eq!(OperatorSelectionMode::Automatic, response.mode);
         assert_eq!(0, response.format.unwrap());
         assert_eq!("T-Mobile USA", response.operator.unwrap());
+        assert_eq!(None, response.act);
+    }
+
+    #[test]
+    fn can_get_operator_selection_with_access_technology() {
+        let cmd = GetOperatorSelection;
+        let response = cmd.parse(Ok(b"+COPS: 0,0,\"Operator\",3")).unwrap();
+        assert_eq!(OperatorSelectionMode::Automatic, response.mode);
+        assert_eq!("Operator", response.operator.unwrap());
+        assert_eq!(Some(AccessTechnology::Egprs), response.act);
     }
 
     #[test]
@@ -234,6 +324,15 @@ mod tests {
         assert_eq_hex!(b"AT+CPIN=\"11223344\",\"1234\"\r", cmd.to_vec().as_bytes());
     }
 
+    #[test]
+    fn can_enter_puk() {
+        let cmd = EnterPuk {
+            puk: "12345678",
+            new_pin: "4321",
+        };
+        assert_eq_hex!(b"AT+CPIN=\"12345678\",\"4321\"\r", cmd.to_vec().as_bytes());
+    }
+
     #[test]
     fn can_change_password() {
         let cmd = ChangePassword {
@@ -1,8 +1,12 @@
-use atat::atat_derive::AtatResp;
+use core::ops::Range;
+
+use atat::{atat_derive::AtatResp, AtatResp};
 use heapless::String;
 use heapless_bytes::Bytes;
 
-use super::{NetworkRegistrationStat, NetworkRegistrationUrcConfig, OperatorSelectionMode};
+use super::{
+    AccessTechnology, NetworkRegistrationStat, NetworkRegistrationUrcConfig, OperatorSelectionMode,
+};
 
 /// 3.2.8 Manufacturer Identification
 #[derive(AtatResp)]
@@ -22,6 +26,59 @@ pub struct SoftwareVersionResponse {
     pub version: Bytes<32>,
 }
 
+/// 3.2.11 Request Product Serial Number Identification
+pub struct ImeiResponse {
+    pub imei: String<15>,
+}
+
+impl AtatResp for ImeiResponse {}
+
+impl ImeiResponse {
+    /// Get the IMEI as a number
+    pub fn as_u64(&self) -> Option<u64> {
+        self.imei.parse().ok()
+    }
+}
+
+/// AT+CCLK Clock
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Clock {
+    /// The last two digits of the year
+    pub year: u8,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    /// The difference between local time and GMT in quarters of an hour
+    pub timezone: i8,
+}
+
+impl AtatResp for Clock {}
+
+impl Clock {
+    /// Get the number of seconds since the unix epoch, assuming that the year is after 2000.
+    pub fn to_unix_timestamp(&self) -> u32 {
+        let year = 2000 + self.year as u32;
+        let leap_days = |year: u32| year / 4 - year / 100 + year / 400;
+        let mut days = 365 * (year - 1970) + leap_days(year - 1) - leap_days(1969);
+
+        const DAYS_BEFORE_MONTH: [u32; 12] =
+            [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334];
+        days += DAYS_BEFORE_MONTH[(self.month.clamp(1, 12) - 1) as usize];
+        let is_leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        if is_leap_year && self.month > 2 {
+            days += 1;
+        }
+        days += (self.day as u32).saturating_sub(1);
+
+        let local =
+            days * 86400 + self.hour as u32 * 3600 + self.minute as u32 * 60 + self.second as u32;
+        local.wrapping_add_signed(-(self.timezone as i32) * 15 * 60)
+    }
+}
+
 // 3.2.22 AT+COPS Operator Selection
 #[derive(AtatResp)]
 pub struct OperatorSelection {
@@ -31,6 +88,8 @@ pub struct OperatorSelection {
     pub format: Option<u8>,
     #[at_arg(position = 2)]
     pub operator: Option<String<16>>,
+    #[at_arg(position = 3)]
+    pub act: Option<AccessTechnology>,
 }
 
 // 3.2.32 AT+CREG Network Registration
@@ -77,10 +136,27 @@ impl SignalQuality {
             _ => None,
         }
     }
+
+    /// Get the bit error rate range in percent as mapped by the RXQUAL table in 3GPP TS 45.008
+    pub fn bit_error_rate(&self) -> Option<Range<f32>> {
+        match self.ber {
+            0 => Some(0.0..0.2),
+            1 => Some(0.2..0.4),
+            2 => Some(0.4..0.8),
+            3 => Some(0.8..1.6),
+            4 => Some(1.6..3.2),
+            5 => Some(3.2..6.4),
+            6 => Some(6.4..12.8),
+            7 => Some(12.8..100.0),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use atat::serde_at::from_str;
+
     use super::SignalQuality;
 
     #[test]
@@ -93,4 +169,16 @@ mod tests {
         assert_eq!(Some(-52), SignalQuality { rssi: 31, ber: 0 }.rssi());
         assert_eq!(None, SignalQuality { rssi: 99, ber: 0 }.rssi());
     }
+
+    #[test]
+    fn test_ber() {
+        let response: SignalQuality = from_str("+CSQ: 20,3").unwrap();
+        assert_eq!(Some(-74), response.rssi());
+        assert_eq!(3, response.ber);
+        assert_eq!(Some(0.8..1.6), response.bit_error_rate());
+
+        let response: SignalQuality = from_str("+CSQ: 20,99").unwrap();
+        assert_eq!(Some(-74), response.rssi());
+        assert_eq!(None, response.bit_error_rate());
+    }
 }
@@ -50,6 +50,19 @@ pub enum OperatorSelectionMode {
     ManualAutomatic,
 }
 
+#[derive(AtatEnum, Debug, Clone, Copy, PartialEq)]
+#[at_enum(u8)]
+pub enum AccessTechnology {
+    #[at_arg(value = 0)]
+    Gsm,
+    #[at_arg(value = 1)]
+    GsmCompact,
+    #[at_arg(value = 2)]
+    Utran,
+    #[at_arg(value = 3)]
+    Egprs,
+}
+
 #[derive(AtatEnum, PartialEq)]
 #[at_enum(u8)]
 pub enum RestrictedSimAccessCommand {
@@ -67,6 +80,15 @@ pub enum RestrictedSimAccessCommand {
     Status,
 }
 
+#[derive(AtatEnum, PartialEq)]
+#[at_enum(u8)]
+pub enum CallerIdPresentation {
+    #[at_arg(value = 0)]
+    Disable,
+    #[at_arg(value = 1)]
+    Enable,
+}
+
 #[derive(AtatEnum, PartialEq)]
 #[at_enum(u8)]
 pub enum MobileEquipmentError {
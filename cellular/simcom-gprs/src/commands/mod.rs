@@ -1,10 +1,16 @@
 use atat::atat_derive::{AtatCmd, AtatResp};
 
+pub mod bearer;
+#[cfg(feature = "gnss")]
+pub mod gnss;
 pub mod gprs;
 pub mod gsm;
+pub(crate) mod parse;
 pub mod simcom;
+pub mod sms;
 pub mod tcpip;
 pub mod urc;
+pub mod ussd;
 pub mod v25ter;
 
 #[derive(AtatCmd)]
@@ -14,6 +20,45 @@ pub struct AT;
 #[derive(AtatResp)]
 pub struct NoResponse;
 
+pub const RAW_COMMAND_MAX_LEN: usize = 128;
+
+/// A command line, e.g. "AT+CFUN=1", sent verbatim where any information response is ignored
+pub struct RawCommand<'a> {
+    command: &'a str,
+}
+
+impl<'a> RawCommand<'a> {
+    /// Create a command, or fail with `atat::Error::Write` if it is longer than [`RAW_COMMAND_MAX_LEN`]
+    pub fn new(command: &'a str) -> Result<Self, atat::Error> {
+        if command.len() > RAW_COMMAND_MAX_LEN {
+            return Err(atat::Error::Write);
+        }
+        Ok(Self { command })
+    }
+}
+
+impl atat::AtatCmd for RawCommand<'_> {
+    const MAX_LEN: usize = RAW_COMMAND_MAX_LEN + 1;
+    const MAX_TIMEOUT_MS: u32 = 60_000;
+
+    type Response = NoResponse;
+
+    fn write(&self, buf: &mut [u8]) -> usize {
+        let len = self.command.len();
+        buf[..len].copy_from_slice(self.command.as_bytes());
+        buf[len] = b'\r';
+        len + 1
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], atat::InternalError>,
+    ) -> Result<Self::Response, atat::Error> {
+        resp?;
+        Ok(NoResponse)
+    }
+}
+
 #[cfg(test)]
 pub(crate) use cmd_ex::AtatCmdEx;
 
@@ -56,4 +101,19 @@ mod tests {
             digester.digest(b"AT\r\r\nOK\r\n")
         );
     }
+
+    #[test]
+    fn can_raw_command() {
+        let cmd = RawCommand::new("AT+CSCLK=0").unwrap();
+        assert_eq_hex!(b"AT+CSCLK=0\r", cmd.to_vec().as_slice());
+    }
+
+    #[test]
+    fn raw_command_rejects_too_long_command() {
+        let command = "A".repeat(RAW_COMMAND_MAX_LEN);
+        assert!(RawCommand::new(&command).is_ok());
+
+        let command = "A".repeat(RAW_COMMAND_MAX_LEN + 1);
+        assert!(matches!(RawCommand::new(&command), Err(atat::Error::Write)));
+    }
 }
@@ -0,0 +1,55 @@
+//! Parsers shared between command responses and URCs.
+//!
+//! Modules and firmwares differ in whether a space follows the colon of a response prefix,
+//! e.g. SIM800 emits `+CIPRXGET: 1,2` where SIM900 emits `+CIPRXGET:1,2`.
+//! Use `prefix()` to match both.
+
+pub mod complete {
+    use atat::nom::{bytes, combinator, error::ParseError, sequence, IResult};
+
+    /// Matches the equivalent of regex: <tag> ?
+    pub fn prefix<'a, Error: ParseError<&'a [u8]>>(
+        tag: &'static str,
+    ) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], &'a [u8], Error> {
+        combinator::recognize(sequence::pair(
+            bytes::complete::tag(tag),
+            combinator::opt(bytes::complete::tag(" ")),
+        ))
+    }
+}
+
+pub mod streaming {
+    use atat::nom::{bytes, combinator, error::ParseError, sequence, IResult};
+
+    /// Matches the equivalent of regex: <tag> ?
+    pub fn prefix<'a, Error: ParseError<&'a [u8]>>(
+        tag: &'static str,
+    ) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], &'a [u8], Error> {
+        combinator::recognize(sequence::pair(
+            bytes::streaming::tag(tag),
+            combinator::opt(bytes::streaming::tag(" ")),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_prefix_with_and_without_space() {
+        let (reminder, prefix) = complete::prefix::<()>("+CIPACK:")(b"+CIPACK: 3,2,1").unwrap();
+        assert_eq!(b"+CIPACK: ", prefix);
+        assert_eq!(b"3,2,1", reminder);
+
+        let (reminder, prefix) = complete::prefix::<()>("+CIPACK:")(b"+CIPACK:3,2,1").unwrap();
+        assert_eq!(b"+CIPACK:", prefix);
+        assert_eq!(b"3,2,1", reminder);
+    }
+
+    #[test]
+    fn streaming_prefix_is_incomplete_before_optional_space() {
+        assert!(streaming::prefix::<()>("+CIPACK:")(b"+CIPACK:").is_err());
+        assert!(streaming::prefix::<()>("+CIPACK:")(b"+CIPACK:3").is_ok());
+    }
+}
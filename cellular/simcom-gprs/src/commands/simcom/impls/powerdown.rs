@@ -0,0 +1,42 @@
+use atat::{
+    atat_derive::{AtatCmd, AtatEnum},
+    AtatCmd,
+};
+
+use crate::commands::{simcom::PowerDown, NoResponse};
+
+impl AtatCmd for PowerDown {
+    type Response = NoResponse;
+
+    const MAX_LEN: usize = "AT+CPOWD=1\r".len();
+
+    // The power down is acknowledged with a "NORMAL POWER DOWN" URC and not a response code
+    const EXPECTS_RESPONSE_CODE: bool = false;
+
+    fn write(&self, buf: &mut [u8]) -> usize {
+        let inner = PowerDownInner {
+            mode: PowerDownMode::Normal,
+        };
+        inner.write(buf)
+    }
+
+    fn parse(
+        &self,
+        _resp: Result<&[u8], atat::InternalError>,
+    ) -> Result<Self::Response, atat::Error> {
+        Ok(NoResponse)
+    }
+}
+
+#[derive(Clone, AtatCmd)]
+#[at_cmd("+CPOWD", NoResponse, termination = "\r")]
+struct PowerDownInner {
+    pub mode: PowerDownMode,
+}
+
+#[derive(Clone, PartialEq, AtatEnum)]
+#[at_enum(u8)]
+enum PowerDownMode {
+    #[at_arg(value = 1)]
+    Normal,
+}
@@ -1,13 +1,29 @@
+mod impls;
 mod responses;
 
 use atat::atat_derive::AtatCmd;
 pub use responses::*;
 
+/// 6.2.2 AT+CPOWD Power Off
+///
+/// The modem replies with a "NORMAL POWER DOWN" URC instead of OK.
+pub struct PowerDown;
+
 /// 6.2.23 AT+CCID Show ICCID
 #[derive(AtatCmd)]
 #[at_cmd("+CCID", GetCcidResponse, termination = "\r")]
 pub struct GetCcid;
 
+/// AT+CBC Battery Charge
+#[derive(AtatCmd)]
+#[at_cmd("+CBC", BatteryChargeResponse, termination = "\r")]
+pub struct GetBatteryCharge;
+
+/// AT+SPIC Times Remained to Input SIM PIN/PUK
+#[derive(AtatCmd)]
+#[at_cmd("+SPIC", PinRemainingAttemptsResponse, termination = "\r")]
+pub struct GetPinRemainingAttempts;
+
 /// 6.2.38 AT+CCALR Call Ready Query
 #[derive(AtatCmd)]
 #[at_cmd("+CCALR?", CallReadyResponse, termination = "\r")]
@@ -22,6 +38,12 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn can_power_down() {
+        let cmd = PowerDown;
+        assert_eq_hex!(b"AT+CPOWD=1\r", cmd.to_vec().as_slice());
+    }
+
     #[test]
     fn can_show_ccid() {
         let cmd = GetCcid {};
@@ -34,6 +56,34 @@ mod tests {
         assert_eq!(89457387300008689393u128, iccid);
     }
 
+    #[test]
+    fn can_get_battery_charge() {
+        let cmd = GetBatteryCharge;
+        assert_eq_hex!(b"AT+CBC\r", cmd.to_vec().as_slice());
+
+        let response = cmd.parse(Ok(b"+CBC: 0,82,4008\r\n")).unwrap();
+        assert_eq!(ChargeStatus::NotCharging, response.charge_status);
+        assert_eq!(82, response.capacity_percent);
+        assert_eq!(Some(4008), response.voltage_mv);
+
+        let response = cmd.parse(Ok(b"+CBC: 1,40\r\n")).unwrap();
+        assert_eq!(ChargeStatus::Charging, response.charge_status);
+        assert_eq!(40, response.capacity_percent);
+        assert_eq!(None, response.voltage_mv);
+    }
+
+    #[test]
+    fn can_get_pin_remaining_attempts() {
+        let cmd = GetPinRemainingAttempts;
+        assert_eq_hex!(b"AT+SPIC\r", cmd.to_vec().as_slice());
+
+        let response = cmd.parse(Ok(b"+SPIC: 3,3,10,10\r\n")).unwrap();
+        assert_eq!(3, response.pin1);
+        assert_eq!(3, response.pin2);
+        assert_eq!(10, response.puk1);
+        assert_eq!(10, response.puk2);
+    }
+
     #[test]
     fn can_get_call_ready() {
         let cmd = GetCallReady {};
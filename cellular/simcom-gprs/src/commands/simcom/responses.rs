@@ -7,6 +7,36 @@ pub struct GetCcidResponse {
     pub iccid: Bytes<20>,
 }
 
+/// Battery Charge
+#[derive(AtatResp)]
+pub struct BatteryChargeResponse {
+    pub charge_status: ChargeStatus,
+    /// Battery connection level 1..100
+    pub capacity_percent: u8,
+    /// Battery voltage in mV, not reported by all SIM900 firmwares
+    pub voltage_mv: Option<u16>,
+}
+
+#[derive(Debug, PartialEq, AtatEnum)]
+#[at_enum(u8)]
+pub enum ChargeStatus {
+    #[at_arg(value = 0)]
+    NotCharging,
+    #[at_arg(value = 1)]
+    Charging,
+    #[at_arg(value = 2)]
+    ChargingFinished,
+}
+
+/// Times Remained to Input SIM PIN/PUK
+#[derive(AtatResp)]
+pub struct PinRemainingAttemptsResponse {
+    pub pin1: u8,
+    pub pin2: u8,
+    pub puk1: u8,
+    pub puk2: u8,
+}
+
 /// 6.2.44 Call Ready Query
 #[derive(AtatResp)]
 pub struct CallReadyResponse {
@@ -0,0 +1,78 @@
+use atat::{
+    atat_derive::AtatCmd,
+    nom::{bytes, character, sequence},
+    AtatCmd,
+};
+use heapless::Vec;
+
+use crate::commands::{
+    sms::{ListMessages, ListedMessage, MessageList},
+    NoResponse,
+};
+
+use super::{parse_header, to_string};
+
+impl AtatCmd for ListMessages {
+    type Response = MessageList;
+
+    const MAX_LEN: usize = "AT+CMGL=\"REC UNREAD\"\r".len();
+
+    const MAX_TIMEOUT_MS: u32 = 20_000;
+
+    fn write(&self, buf: &mut [u8]) -> usize {
+        let inner = ListMessagesInner {
+            stat: self.stat.as_str(),
+        };
+        inner.write(buf)
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], atat::InternalError>,
+    ) -> Result<Self::Response, atat::Error> {
+        let mut reminder = resp?;
+        let mut messages = Vec::new();
+        let mut truncated = false;
+        while !reminder.is_empty() {
+            let (tail, (_, index, _, (stat, oa, scts), _)) = sequence::tuple((
+                bytes::complete::tag("+CMGL: "),
+                character::complete::u16,
+                bytes::complete::tag(","),
+                parse_header,
+                bytes::complete::tag("\r\n"),
+            ))(reminder)
+            .map_err(|_| atat::Error::Parse)?;
+
+            // The text spans until the next message, or the end of the response
+            let (text, tail) = match bytes::complete::take_until::<_, _, ()>("\r\n+CMGL: ")(tail) {
+                Ok((tail, text)) => (text, &tail[2..]),
+                Err(_) => (tail, &tail[tail.len()..]),
+            };
+            reminder = tail;
+
+            // Messages beyond the capacity are still parsed to validate the response, but not included
+            let message = ListedMessage {
+                index,
+                stat,
+                oa,
+                scts,
+                text: to_string(text).ok_or(atat::Error::Parse)?,
+            };
+            if messages.push(message).is_err() {
+                truncated = true;
+            }
+        }
+
+        Ok(MessageList {
+            messages,
+            truncated,
+        })
+    }
+}
+
+#[derive(Clone, AtatCmd)]
+#[at_cmd("+CMGL", NoResponse, termination = "\r")]
+struct ListMessagesInner<'a> {
+    #[at_arg(len = 10)]
+    pub stat: &'a str,
+}
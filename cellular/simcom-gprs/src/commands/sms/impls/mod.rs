@@ -0,0 +1,48 @@
+mod listmessages;
+mod readmessage;
+mod writemessagetext;
+
+use atat::nom::{bytes, sequence, IResult};
+use heapless::String;
+
+use super::MessageStatus;
+
+type Header = (MessageStatus, String<20>, String<20>);
+
+/// Parse the message header fields <stat>,<oa>,[<alpha>],[<scts>]
+fn parse_header(buf: &[u8]) -> IResult<&[u8], Header, atat::nom::error::Error<&[u8]>> {
+    let (reminder, (stat, _, oa, _, _alpha, _, scts)) = sequence::tuple((
+        quoted,
+        bytes::complete::tag(","),
+        quoted,
+        bytes::complete::tag(","),
+        quoted,
+        bytes::complete::tag(","),
+        quoted,
+    ))(buf)?;
+
+    let stat = MessageStatus::try_from(stat).map_err(|_| error(buf))?;
+    let oa = to_string(oa).ok_or_else(|| error(buf))?;
+    let scts = to_string(scts).ok_or_else(|| error(buf))?;
+    Ok((reminder, (stat, oa, scts)))
+}
+
+fn quoted(buf: &[u8]) -> IResult<&[u8], &[u8], atat::nom::error::Error<&[u8]>> {
+    sequence::delimited(
+        bytes::complete::tag("\""),
+        bytes::complete::take_until("\""),
+        bytes::complete::tag("\""),
+    )(buf)
+}
+
+fn error(buf: &[u8]) -> atat::nom::Err<atat::nom::error::Error<&[u8]>> {
+    atat::nom::Err::Error(atat::nom::error::Error::new(
+        buf,
+        atat::nom::error::ErrorKind::Verify,
+    ))
+}
+
+fn to_string<const N: usize>(value: &[u8]) -> Option<String<N>> {
+    let value = core::str::from_utf8(value).ok()?;
+    String::try_from(value).ok()
+}
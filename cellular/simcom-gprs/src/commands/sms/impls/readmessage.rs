@@ -0,0 +1,50 @@
+use atat::{
+    atat_derive::AtatCmd,
+    nom::{bytes, sequence},
+    AtatCmd,
+};
+
+use crate::commands::{
+    sms::{Message, ReadMessage},
+    NoResponse,
+};
+
+use super::{parse_header, to_string};
+
+impl AtatCmd for ReadMessage {
+    type Response = Message;
+
+    const MAX_LEN: usize = "AT+CMGR=XXXXX\r".len();
+
+    const MAX_TIMEOUT_MS: u32 = 5_000;
+
+    fn write(&self, buf: &mut [u8]) -> usize {
+        let inner = ReadMessageInner { index: self.index };
+        inner.write(buf)
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], atat::InternalError>,
+    ) -> Result<Self::Response, atat::Error> {
+        let (text, (_, (stat, oa, scts), _)) = sequence::tuple((
+            bytes::complete::tag("+CMGR: "),
+            parse_header,
+            bytes::complete::tag("\r\n"),
+        ))(resp?)
+        .map_err(|_| atat::Error::Parse)?;
+
+        Ok(Message {
+            stat,
+            oa,
+            scts,
+            text: to_string(text).ok_or(atat::Error::Parse)?,
+        })
+    }
+}
+
+#[derive(Clone, AtatCmd)]
+#[at_cmd("+CMGR", NoResponse, termination = "\r")]
+struct ReadMessageInner {
+    pub index: u16,
+}
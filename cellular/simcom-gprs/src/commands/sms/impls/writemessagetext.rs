@@ -0,0 +1,41 @@
+use atat::{
+    nom::{bytes, character, sequence},
+    AtatCmd,
+};
+
+use crate::commands::sms::{MessageReference, WriteMessageText, MAX_TEXT_LEN};
+
+/// Ctrl-Z terminates the message text
+const CTRL_Z: u8 = 0x1A;
+
+impl AtatCmd for WriteMessageText<'_> {
+    type Response = MessageReference;
+
+    const MAX_LEN: usize = MAX_TEXT_LEN + 1;
+
+    const MAX_TIMEOUT_MS: u32 = 60_000;
+
+    fn write(&self, buf: &mut [u8]) -> usize {
+        let len = self.text.len();
+        buf[..len].copy_from_slice(self.text.as_bytes());
+        buf[len] = CTRL_Z;
+        len + 1
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], atat::InternalError>,
+    ) -> Result<Self::Response, atat::Error> {
+        if let Ok((reminder, (_, mr))) = sequence::tuple::<_, _, (), _>((
+            bytes::complete::tag("+CMGS: "),
+            character::complete::u8,
+        ))(resp?)
+        {
+            if reminder.is_empty() {
+                return Ok(MessageReference { mr });
+            }
+        }
+
+        Err(atat::Error::Parse)
+    }
+}
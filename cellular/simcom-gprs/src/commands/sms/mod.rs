@@ -0,0 +1,236 @@
+//! SMS commands according to 3GPP TS27.005
+mod impls;
+mod responses;
+mod types;
+pub mod urcs;
+
+use super::NoResponse;
+use atat::atat_derive::AtatCmd;
+pub use responses::*;
+pub use types::*;
+
+/// The maximum length of a text mode message
+pub const MAX_TEXT_LEN: usize = 160;
+
+/// The maximum number of messages returned by AT+CMGL
+///
+/// The listing is truncated rather than failed if more messages are stored,
+/// see [`MessageList::truncated`]. The whole listing must fit in the ingress buffer in any case.
+pub const MAX_LISTED_MESSAGES: usize = 8;
+
+/// 4.1 AT+CMGD Delete SMS Message
+#[derive(AtatCmd)]
+#[at_cmd("+CMGD", NoResponse, timeout_ms = 5_000, termination = "\r")]
+pub struct DeleteMessage {
+    pub index: u16,
+    pub delflag: Option<DeleteFlag>,
+}
+
+/// 4.2 AT+CMGF Select SMS Message Format
+#[derive(AtatCmd)]
+#[at_cmd("+CMGF", NoResponse, termination = "\r")]
+pub struct SetMessageFormat {
+    pub mode: MessageFormat,
+}
+
+/// 4.3 AT+CMGL List SMS Messages from Preferred Store
+///
+/// The listed messages are returned in text mode as
+/// +CMGL: <index>,<stat>,<oa>,[<alpha>],[<scts>]
+/// <data>
+pub struct ListMessages {
+    pub stat: MessageStatus,
+}
+
+/// 4.4 AT+CMGR Read SMS Message
+///
+/// The message is returned in text mode as
+/// +CMGR: <stat>,<oa>,[<alpha>],[<scts>]
+/// <data>
+pub struct ReadMessage {
+    pub index: u16,
+}
+
+/// 4.5 AT+CMGS Send SMS Message
+///
+/// The modem replies with a prompt, after which the message text
+/// must be written using [`WriteMessageText`].
+#[derive(AtatCmd)]
+#[at_cmd("+CMGS", NoResponse, termination = "\r")]
+pub struct SendMessage<'a> {
+    #[at_arg(len = 20)]
+    pub da: &'a str,
+}
+
+/// The text written after the prompt from [`SendMessage`]
+///
+/// The text is terminated by Ctrl-Z, after which the modem replies with the message reference.
+pub struct WriteMessageText<'a> {
+    text: &'a str,
+}
+
+impl<'a> WriteMessageText<'a> {
+    /// Create the message text, or fail with `atat::Error::Write` if it is longer than [`MAX_TEXT_LEN`]
+    pub fn new(text: &'a str) -> Result<Self, atat::Error> {
+        if text.len() > MAX_TEXT_LEN {
+            return Err(atat::Error::Write);
+        }
+        Ok(Self { text })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_hex::assert_eq_hex;
+    use atat::{AtatCmd, AtatIngress, Response};
+
+    use crate::{commands::AtatCmdEx, services::ingress_mock::setup_ingress};
+
+    use super::*;
+
+    #[test]
+    fn can_set_message_format() {
+        let cmd = SetMessageFormat {
+            mode: MessageFormat::Text,
+        };
+        assert_eq_hex!(b"AT+CMGF=1\r", cmd.to_vec().as_slice());
+    }
+
+    #[test]
+    fn can_send_message() {
+        let cmd = SendMessage { da: "+4512345678" };
+        assert_eq_hex!(b"AT+CMGS=\"+4512345678\"\r", cmd.to_vec().as_slice());
+    }
+
+    #[test]
+    fn can_write_message_text() {
+        let cmd = WriteMessageText::new("Hello").unwrap();
+        assert_eq_hex!(b"Hello\x1A", cmd.to_vec().as_slice());
+
+        let (mut ingress, res_sub) = setup_ingress!();
+        ingress.try_write(b"\r\n+CMGS: 12\r\n\r\nOK\r\n").unwrap();
+
+        let response = res_sub.try_get().unwrap();
+        let response: &Response<400> = &response.borrow();
+        if let Response::Ok(message) = response {
+            let response = cmd.parse(Ok(message)).unwrap();
+            assert_eq!(12, response.mr);
+        } else {
+            panic!("Invalid response");
+        }
+    }
+
+    #[test]
+    fn write_message_text_rejects_too_long_text() {
+        let text = "A".repeat(MAX_TEXT_LEN);
+        assert!(WriteMessageText::new(&text).is_ok());
+
+        let text = "A".repeat(MAX_TEXT_LEN + 1);
+        assert!(matches!(
+            WriteMessageText::new(&text),
+            Err(atat::Error::Write)
+        ));
+    }
+
+    #[test]
+    fn can_read_message() {
+        let cmd = ReadMessage { index: 3 };
+        assert_eq_hex!(b"AT+CMGR=3\r", cmd.to_vec().as_slice());
+
+        let (mut ingress, res_sub) = setup_ingress!();
+        ingress
+            .try_write(b"\r\n+CMGR: \"REC UNREAD\",\"+4512345678\",\"\",\"24/01/30,20:40:31+04\"\r\nHello World\r\n\r\nOK\r\n")
+            .unwrap();
+
+        let response = res_sub.try_get().unwrap();
+        let response: &Response<400> = &response.borrow();
+        if let Response::Ok(message) = response {
+            let response = cmd.parse(Ok(message)).unwrap();
+            assert_eq!(MessageStatus::ReceivedUnread, response.stat);
+            assert_eq!("+4512345678", response.oa);
+            assert_eq!("24/01/30,20:40:31+04", response.scts);
+            assert_eq!("Hello World", response.text);
+        } else {
+            panic!("Invalid response");
+        }
+    }
+
+    #[test]
+    fn can_list_messages() {
+        let cmd = ListMessages {
+            stat: MessageStatus::All,
+        };
+        assert_eq_hex!(b"AT+CMGL=\"ALL\"\r", cmd.to_vec().as_slice());
+
+        let (mut ingress, res_sub) = setup_ingress!();
+        ingress
+            .try_write(b"\r\n+CMGL: 1,\"REC READ\",\"+4512345678\",\"\",\"24/01/30,20:40:31+04\"\r\nHello\r\n+CMGL: 4,\"REC UNREAD\",\"+4587654321\",\"\",\"24/01/31,08:00:00+04\"\r\nWorld\r\n\r\nOK\r\n")
+            .unwrap();
+
+        let response = res_sub.try_get().unwrap();
+        let response: &Response<400> = &response.borrow();
+        if let Response::Ok(message) = response {
+            let response = cmd.parse(Ok(message)).unwrap();
+            assert!(!response.truncated);
+            assert_eq!(2, response.messages.len());
+            assert_eq!(1, response.messages[0].index);
+            assert_eq!(MessageStatus::ReceivedRead, response.messages[0].stat);
+            assert_eq!("Hello", response.messages[0].text);
+            assert_eq!(4, response.messages[1].index);
+            assert_eq!("+4587654321", response.messages[1].oa);
+            assert_eq!("World", response.messages[1].text);
+        } else {
+            panic!("Invalid response");
+        }
+    }
+
+    #[test]
+    fn can_list_no_messages() {
+        let cmd = ListMessages {
+            stat: MessageStatus::ReceivedUnread,
+        };
+        assert_eq_hex!(b"AT+CMGL=\"REC UNREAD\"\r", cmd.to_vec().as_slice());
+
+        let response = cmd.parse(Ok(b"")).unwrap();
+        assert!(response.messages.is_empty());
+        assert!(!response.truncated);
+    }
+
+    #[test]
+    fn can_list_truncated_messages() {
+        let cmd = ListMessages {
+            stat: MessageStatus::All,
+        };
+
+        let mut response = std::vec::Vec::new();
+        for index in 0..MAX_LISTED_MESSAGES + 2 {
+            if index > 0 {
+                response.extend_from_slice(b"\r\n");
+            }
+            response.extend_from_slice(
+                format!("+CMGL: {index},\"REC READ\",\"+4512345678\",\"\",\"24/01/30,20:40:31+04\"\r\nHello {index}").as_bytes(),
+            );
+        }
+
+        let response = cmd.parse(Ok(&response)).unwrap();
+        assert!(response.truncated);
+        assert_eq!(MAX_LISTED_MESSAGES, response.messages.len());
+        assert_eq!(7, response.messages[7].index);
+        assert_eq!("Hello 7", response.messages[7].text);
+    }
+
+    #[test]
+    fn can_delete_message() {
+        let cmd = DeleteMessage {
+            index: 3,
+            delflag: None,
+        };
+        assert_eq_hex!(b"AT+CMGD=3\r", cmd.to_vec().as_slice());
+
+        let cmd = DeleteMessage {
+            index: 1,
+            delflag: Some(DeleteFlag::All),
+        };
+        assert_eq_hex!(b"AT+CMGD=1,4\r", cmd.to_vec().as_slice());
+    }
+}
@@ -0,0 +1,42 @@
+use atat::{atat_derive::AtatResp, AtatResp};
+use heapless::{String, Vec};
+
+use super::{MessageStatus, MAX_LISTED_MESSAGES, MAX_TEXT_LEN};
+
+/// 4.3 AT+CMGL List SMS Messages from Preferred Store
+pub struct MessageList {
+    pub messages: Vec<ListedMessage, MAX_LISTED_MESSAGES>,
+    /// More than [`MAX_LISTED_MESSAGES`] messages were listed, and only the first ones are included.
+    /// Note that the modem marks all listed unread messages as read, also those not included.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ListedMessage {
+    pub index: u16,
+    pub stat: MessageStatus,
+    pub oa: String<20>,
+    pub scts: String<20>,
+    pub text: String<MAX_TEXT_LEN>,
+}
+
+/// 4.4 AT+CMGR Read SMS Message
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Message {
+    pub stat: MessageStatus,
+    pub oa: String<20>,
+    pub scts: String<20>,
+    pub text: String<MAX_TEXT_LEN>,
+}
+
+impl AtatResp for MessageList {}
+
+impl AtatResp for Message {}
+
+/// 4.5 AT+CMGS Send SMS Message
+#[derive(AtatResp)]
+pub struct MessageReference {
+    pub mr: u8,
+}
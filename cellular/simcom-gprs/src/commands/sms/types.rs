@@ -0,0 +1,69 @@
+use atat::atat_derive::AtatEnum;
+
+#[derive(AtatEnum, Debug, PartialEq)]
+#[at_enum(u8)]
+pub enum MessageFormat {
+    #[at_arg(value = 0)]
+    Pdu,
+    #[at_arg(value = 1)]
+    Text,
+}
+
+#[derive(AtatEnum, Debug, PartialEq)]
+#[at_enum(u8)]
+pub enum DeleteFlag {
+    /// Delete the message at the given index
+    #[at_arg(value = 0)]
+    Index,
+    /// Delete all read messages
+    #[at_arg(value = 1)]
+    Read,
+    /// Delete all read and sent messages
+    #[at_arg(value = 2)]
+    ReadAndSent,
+    /// Delete all read, sent, and unsent messages
+    #[at_arg(value = 3)]
+    ReadSentAndUnsent,
+    /// Delete all messages
+    #[at_arg(value = 4)]
+    All,
+}
+
+/// The status of a message in text mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MessageStatus {
+    ReceivedUnread,
+    ReceivedRead,
+    StoredUnsent,
+    StoredSent,
+    /// All messages, only valid when listing messages
+    All,
+}
+
+impl MessageStatus {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            MessageStatus::ReceivedUnread => "REC UNREAD",
+            MessageStatus::ReceivedRead => "REC READ",
+            MessageStatus::StoredUnsent => "STO UNSENT",
+            MessageStatus::StoredSent => "STO SENT",
+            MessageStatus::All => "ALL",
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for MessageStatus {
+    type Error = ();
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        match value {
+            b"REC UNREAD" => Ok(MessageStatus::ReceivedUnread),
+            b"REC READ" => Ok(MessageStatus::ReceivedRead),
+            b"STO UNSENT" => Ok(MessageStatus::StoredUnsent),
+            b"STO SENT" => Ok(MessageStatus::StoredSent),
+            b"ALL" => Ok(MessageStatus::All),
+            _ => Err(()),
+        }
+    }
+}
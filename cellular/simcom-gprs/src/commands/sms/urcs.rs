@@ -0,0 +1,27 @@
+use atat::atat_derive::AtatResp;
+use heapless::String;
+
+use super::MAX_TEXT_LEN;
+
+/// 3.4.1 AT+CNMI New SMS Message Indications
+///
+/// The message is stored at `index` in the `mem` storage.
+#[derive(AtatResp, Clone, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NewMessageIndication {
+    #[at_arg(position = 0)]
+    pub mem: String<2>,
+    #[at_arg(position = 1)]
+    pub index: u16,
+}
+
+/// 3.4.1 AT+CNMI New SMS Message Indications
+///
+/// The message is routed directly to the host without being stored.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ReceivedMessage {
+    pub oa: String<20>,
+    pub scts: String<20>,
+    pub text: String<MAX_TEXT_LEN>,
+}
@@ -4,7 +4,10 @@ use atat::{
 };
 
 use crate::{
-    commands::tcpip::{QuerySendBufferSize, SendBufferSize},
+    commands::{
+        parse::complete::prefix,
+        tcpip::{QuerySendBufferSize, SendBufferSize},
+    },
     MAX_SOCKETS,
 };
 
@@ -33,7 +36,7 @@ impl AtatCmd for QuerySendBufferSize {
         for i in 0..MAX_SOCKETS {
             match sequence::tuple::<_, _, (), _>((
                 combinator::opt(bytes::complete::tag(b"\r\n")),
-                bytes::complete::tag("+CIPSEND: "),
+                prefix("+CIPSEND:"),
                 character::complete::u8,
                 bytes::complete::tag(","),
                 character::complete::u16,
@@ -106,6 +106,18 @@ pub struct GetConnectionStatus {
     pub id: usize,
 }
 
+/// 8.2.12 AT+CIPSTATUS Query Current Connection Status
+///
+/// AT+CIPSTATUS without an id replies with an OK before the status table.
+/// The `STATE: ...` header and the `C: <id>,...` row for each connection
+/// are therefore received as [`Urc::IpState`] and [`Urc::ClientStatus`].
+///
+/// [`Urc::IpState`]: crate::commands::urc::Urc::IpState
+/// [`Urc::ClientStatus`]: crate::commands::urc::Urc::ClientStatus
+#[derive(AtatCmd)]
+#[at_cmd("+CIPSTATUS", NoResponse, termination = "\r")]
+pub struct GetAllConnectionStatus;
+
 /// 8.2.13 AT+CDNSCFG Configure Domain Name Server
 #[derive(AtatCmd)]
 #[at_cmd("+CDNSCFG", NoResponse, termination = "\r")]
@@ -163,6 +175,25 @@ pub struct ReadData {
     pub max_len: usize,
 }
 
+/// AT+CIPSSL Set SSL Function
+///
+/// The setting applies to the connections started after it is set.
+#[cfg(feature = "tls")]
+#[derive(AtatCmd)]
+#[at_cmd("+CIPSSL", NoResponse, termination = "\r")]
+pub struct SetSsl {
+    pub mode: SslMode,
+}
+
+/// AT+SSLOPT Set SSL Option
+#[cfg(feature = "tls")]
+#[derive(AtatCmd)]
+#[at_cmd("+SSLOPT", NoResponse, termination = "\r")]
+pub struct SetSslOption {
+    pub option: SslOption,
+    pub enable: SslOptionState,
+}
+
 #[cfg(test)]
 mod tests {
     use assert_hex::assert_eq_hex;
@@ -201,6 +232,21 @@ mod tests {
         assert_eq_hex!(b"AT+CIPMUX=1\r", cmd.to_vec().as_slice());
     }
 
+    #[cfg(feature = "tls")]
+    #[test]
+    fn can_set_ssl() {
+        let cmd = SetSsl {
+            mode: SslMode::Enabled,
+        };
+        assert_eq_hex!(b"AT+CIPSSL=1\r", cmd.to_vec().as_slice());
+
+        let cmd = SetSslOption {
+            option: SslOption::IgnoreInvalidCertificate,
+            enable: SslOptionState::Enabled,
+        };
+        assert_eq_hex!(b"AT+SSLOPT=0,1\r", cmd.to_vec().as_slice());
+    }
+
     #[test]
     fn can_start_connection() {
         let cmd = StartConnection {
@@ -238,6 +284,26 @@ mod tests {
         }
     }
 
+    #[cfg(not(feature = "sim900"))]
+    #[test]
+    fn can_query_send_buffer_size_with_mixed_spacing() {
+        let cmd = QuerySendBufferSize;
+
+        let (mut ingress, res_sub, _) = setup_atat!();
+        ingress.try_write(b"\r\n+CIPSEND:0,1460\r\n+CIPSEND: 1,0\r\n+CIPSEND:2,0\r\n+CIPSEND: 3,0\r\n+CIPSEND:4,0\r\n+CIPSEND:5,512\r\n\r\nOK\r\n").unwrap();
+
+        let response = res_sub.try_get().unwrap();
+        let response: &Response<200> = &response.borrow();
+        if let Response::Ok(message) = response {
+            let response = cmd.parse(Ok(message)).unwrap();
+            assert_eq!(1460, response.size[0]);
+            assert_eq!(0, response.size[1]);
+            assert_eq!(512, response.size[5]);
+        } else {
+            panic!("Invalid response");
+        }
+    }
+
     #[cfg(feature = "sim900")]
     #[test]
     fn can_query_send_buffer_size_sim900() {
@@ -322,6 +388,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn can_query_connection_transmitting_state_without_space() {
+        let cmd = QueryPreviousConnectionDataTransmittingState { id: 2 };
+
+        let (mut ingress, res_sub, _) = setup_atat!();
+        ingress
+            .try_write(b"\r\n+CIPACK:3,2,1\r\n\r\nOK\r\n")
+            .unwrap();
+
+        let response = res_sub.try_get().unwrap();
+        let response: &Response<200> = &response.borrow();
+        if let Response::Ok(message) = response {
+            let response = cmd.parse(Ok(message)).unwrap();
+            assert_eq!(3, response.txlen);
+            assert_eq!(2, response.acklen);
+            assert_eq!(1, response.nacklen);
+        } else {
+            panic!("Invalid response");
+        }
+    }
+
     #[test]
     fn can_close_connection() {
         let cmd = CloseConnection { id: 2 };
@@ -613,4 +700,25 @@ mod tests {
 
         assert_eq!(0, urc_sub.available());
     }
+
+    #[test]
+    fn can_get_connection_status_without_space() {
+        let cmd = GetConnectionStatus { id: 2 };
+
+        let (mut ingress, res_sub, _) = setup_atat!();
+        ingress
+            .try_write(b"\r\n+CIPSTATUS:2,0,\"TCP\",\"1.2.3.4\",\"80\",\"CONNECTED\"\r\n\r\nOK\r\n")
+            .unwrap();
+
+        let response = res_sub.try_get().unwrap();
+        let response: &Response<200> = &response.borrow();
+        if let Response::Ok(message) = response {
+            let response = cmd.parse(Ok(message)).unwrap();
+            assert_eq!(2, response.id);
+            assert_eq!("1.2.3.4", response.ip);
+            assert_eq!(ClientState::Connected, response.state);
+        } else {
+            panic!("Invalid response");
+        }
+    }
 }
@@ -13,7 +13,29 @@ pub enum DataTransmittingMode {
     QuickSendMode = 1,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+#[cfg(feature = "tls")]
+#[derive(Debug, AtatEnum, PartialEq)]
+pub enum SslMode {
+    Disabled = 0,
+    Enabled = 1,
+}
+
+#[cfg(feature = "tls")]
+#[derive(Debug, AtatEnum, PartialEq)]
+pub enum SslOption {
+    IgnoreInvalidCertificate = 0,
+    ClientAuthentication = 1,
+}
+
+#[cfg(feature = "tls")]
+#[derive(Debug, AtatEnum, PartialEq)]
+pub enum SslOptionState {
+    Disabled = 0,
+    Enabled = 1,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ClientState {
     #[serde(rename = "INITIAL")]
     Initial,
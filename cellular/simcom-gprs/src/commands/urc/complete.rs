@@ -1,6 +1,11 @@
-use crate::{commands::gprs::PdpState, ContextId};
+use heapless::String;
 
-use super::{Data, ReadResult, Urc};
+use crate::{
+    commands::{gprs::PdpState, parse::complete::prefix, tcpip::ClientState},
+    ContextId,
+};
+
+use super::{ClientStatus, Data, IpState, ReadResult, ReceivedMessage, Urc};
 use atat::nom::{branch, bytes, character, combinator, sequence};
 
 pub(super) fn parse_pdp_state(resp: &[u8]) -> Option<Urc> {
@@ -53,11 +58,10 @@ pub(super) fn parse_connection_status(resp: &[u8]) -> Option<Urc> {
 
 pub(super) fn parse_data_available(resp: &[u8]) -> Option<Urc> {
     if let Ok((reminder, (_, id))) = sequence::tuple::<_, _, (), _>((
-        combinator::recognize(sequence::tuple((
-            bytes::complete::tag("+CIPRXGET:"),
-            combinator::opt(bytes::complete::tag(b" ")),
+        combinator::recognize(sequence::pair(
+            prefix("+CIPRXGET:"),
             bytes::complete::tag("1,"),
-        ))),
+        )),
         character::complete::u8,
     ))(resp)
     {
@@ -71,11 +75,10 @@ pub(super) fn parse_data_available(resp: &[u8]) -> Option<Urc> {
 
 pub(super) fn parse_read_data(resp: &[u8]) -> Option<Urc> {
     if let Ok((reminder, (_, id, _, (_, pending_len, _, data)))) = sequence::tuple::<_, _, (), _>((
-        combinator::recognize(sequence::tuple((
-            bytes::complete::tag("+CIPRXGET:"),
-            combinator::opt(bytes::complete::tag(b" ")),
+        combinator::recognize(sequence::pair(
+            prefix("+CIPRXGET:"),
             bytes::complete::tag("2,"),
-        ))),
+        )),
         character::complete::u8,
         bytes::complete::tag(","),
         combinator::flat_map(character::complete::u16, |data_len| {
@@ -114,3 +117,114 @@ pub(super) fn parse_dns_error(resp: &[u8]) -> Option<Urc> {
 
     None
 }
+
+pub(super) fn parse_message_received(resp: &[u8]) -> Option<Urc> {
+    if let Ok((text, (_, oa, _, _alpha, _, scts, _))) = sequence::tuple::<_, _, (), _>((
+        bytes::complete::tag("+CMT: "),
+        quoted,
+        bytes::complete::tag(","),
+        quoted,
+        bytes::complete::tag(","),
+        quoted,
+        bytes::complete::tag("\r\n"),
+    ))(resp)
+    {
+        return Some(Urc::MessageReceived(ReceivedMessage {
+            oa: to_string(oa)?,
+            scts: to_string(scts)?,
+            text: to_string(text)?,
+        }));
+    }
+
+    None
+}
+
+pub(super) fn parse_ip_state(resp: &[u8]) -> Option<Urc> {
+    if let Ok((state, _)) = prefix::<()>("STATE:")(resp) {
+        return Some(Urc::IpState(IpState {
+            state: to_string(state)?,
+        }));
+    }
+
+    None
+}
+
+pub(super) fn parse_client_status(resp: &[u8]) -> Option<Urc> {
+    if let Ok((reminder, (_, id, _, _bearer, _, mode, _, ip, _, port, _, state))) =
+        sequence::tuple::<_, _, (), _>((
+            prefix("C:"),
+            character::complete::u8,
+            bytes::complete::tag(","),
+            character::complete::digit0,
+            bytes::complete::tag(","),
+            quoted,
+            bytes::complete::tag(","),
+            quoted,
+            bytes::complete::tag(","),
+            quoted,
+            bytes::complete::tag(","),
+            quoted,
+        ))(resp)
+    {
+        if reminder.is_empty() {
+            let state = match state {
+                b"INITIAL" => ClientState::Initial,
+                b"CONNECTING" => ClientState::Connecting,
+                b"CONNECTED" => ClientState::Connected,
+                b"REMOTE CLOSING" => ClientState::RemoteClosing,
+                b"CLOSING" => ClientState::Closing,
+                b"CLOSED" => ClientState::Closed,
+                _ => return None,
+            };
+
+            return Some(Urc::ClientStatus(ClientStatus {
+                id: id as usize,
+                mode: to_string(mode)?,
+                ip: to_string(ip)?,
+                port: to_string(port)?,
+                state,
+            }));
+        }
+    }
+
+    None
+}
+
+pub(super) fn parse_caller_id(resp: &[u8]) -> Option<Urc> {
+    if let Ok((reminder, (_, number))) =
+        sequence::tuple::<_, _, (), _>((prefix("+CLIP:"), quoted))(resp)
+    {
+        if reminder.is_empty() || reminder.starts_with(b",") {
+            return Some(Urc::CallerId(to_string(number)?));
+        }
+    }
+
+    None
+}
+
+pub(super) fn parse_ussd_response(resp: &[u8]) -> Option<Urc> {
+    if let Ok((reminder, (_, _m, text))) = sequence::tuple::<_, _, (), _>((
+        prefix("+CUSD:"),
+        character::complete::u8,
+        combinator::opt(sequence::preceded(bytes::complete::tag(","), quoted)),
+    ))(resp)
+    {
+        if reminder.is_empty() || reminder.starts_with(b",") {
+            return Some(Urc::UssdResponse(to_string(text.unwrap_or_default())?));
+        }
+    }
+
+    None
+}
+
+fn quoted(buf: &[u8]) -> atat::nom::IResult<&[u8], &[u8], ()> {
+    sequence::delimited(
+        bytes::complete::tag("\""),
+        bytes::complete::take_until("\""),
+        bytes::complete::tag("\""),
+    )(buf)
+}
+
+fn to_string<const N: usize>(value: &[u8]) -> Option<String<N>> {
+    String::try_from(core::str::from_utf8(value).ok()?).ok()
+}
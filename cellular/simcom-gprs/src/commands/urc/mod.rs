@@ -14,15 +14,24 @@ use heapless::String;
 
 use crate::ContextId;
 
-use super::{gprs, gsm};
+use super::{gprs, gsm, sms, tcpip::ClientState, ussd::MAX_USSD_LEN};
 
 pub use gsm::urcs::*;
+pub use sms::urcs::*;
 
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Urc {
     CallReady,
+    Ring,
+
+    /// +CLIP: ...
+    ///
+    /// The number is empty if the caller withholds it.
+    CallerId(String<20>),
+
     SmsReady,
+    NormalPowerDown,
     PinStatus(PinStatus),
     ConnectOk(usize),
     ConnectFail(usize),
@@ -40,18 +49,41 @@ pub enum Urc {
 
     /// +CIPRXGET: 2,...
     ReadData(ReadResult),
+
+    /// STATE: ...
+    IpState(IpState),
+
+    /// C: ...
+    ClientStatus(ClientStatus),
+
+    /// +CMTI: ...
+    NewMessageIndication(NewMessageIndication),
+
+    /// +CMT: ...
+    MessageReceived(ReceivedMessage),
+
+    /// +CUSD: ...
+    ///
+    /// The string is empty if the network replies without one, e.g. when the operation is not supported.
+    UssdResponse(String<MAX_USSD_LEN>),
 }
 
 #[derive(Debug, Clone, AtatUrc)]
 enum UrcInner {
     #[at_urc("Call Ready")]
     CallReady,
+    #[at_urc("RING")]
+    Ring,
     #[at_urc("SMS Ready")]
     SmsReady,
+    #[at_urc("NORMAL POWER DOWN")]
+    NormalPowerDown,
     #[at_urc("+CPIN")]
     PinStatus(PinStatus),
     #[at_urc("+CDNSGIP")]
     DnsOk(DnsLookup),
+    #[at_urc("+CMTI")]
+    NewMessageIndication(NewMessageIndication),
 }
 
 /// 7.2.5 AT+CGACT PDP Context Activate or Deactivate
@@ -62,6 +94,24 @@ pub struct PdpContextState {
     pub state: gprs::PdpState,
 }
 
+/// 8.2.12 AT+CIPSTATUS Query Current Connection Status
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct IpState {
+    pub state: String<24>,
+}
+
+/// 8.2.12 AT+CIPSTATUS Query Current Connection Status
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClientStatus {
+    pub id: usize,
+    pub mode: String<3>,
+    pub ip: String<15>,
+    pub port: String<5>,
+    pub state: ClientState,
+}
+
 /// 8.2.14 AT+CDNSGIP Query the IP Address of Given Domain Name
 #[derive(Debug, Clone, AtatResp)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -89,9 +139,12 @@ impl From<UrcInner> for Urc {
     fn from(value: UrcInner) -> Self {
         match value {
             UrcInner::CallReady => Urc::CallReady,
+            UrcInner::Ring => Urc::Ring,
             UrcInner::SmsReady => Urc::SmsReady,
+            UrcInner::NormalPowerDown => Urc::NormalPowerDown,
             UrcInner::PinStatus(x) => Urc::PinStatus(x),
             UrcInner::DnsOk(x) => Urc::DnsResult(Ok(x)),
+            UrcInner::NewMessageIndication(x) => Urc::NewMessageIndication(x),
         }
     }
 }
@@ -110,6 +163,16 @@ impl AtatUrc for Urc {
             Some(urc)
         } else if let Some(urc) = complete::parse_dns_error(resp) {
             Some(urc)
+        } else if let Some(urc) = complete::parse_message_received(resp) {
+            Some(urc)
+        } else if let Some(urc) = complete::parse_ip_state(resp) {
+            Some(urc)
+        } else if let Some(urc) = complete::parse_client_status(resp) {
+            Some(urc)
+        } else if let Some(urc) = complete::parse_caller_id(resp) {
+            Some(urc)
+        } else if let Some(urc) = complete::parse_ussd_response(resp) {
+            Some(urc)
         } else if resp == b"+PDP: DEACT" {
             Some(Urc::PdpDeact)
         } else {
@@ -126,12 +189,20 @@ impl atat::Parser for Urc {
             streaming::parse_data_available,
             streaming::parse_read_data,
             streaming::parse_receive,
+            streaming::parse_message_received,
             urc_helper("Call Ready"),
+            urc_helper("RING"),
             urc_helper("SMS Ready"),
+            urc_helper("NORMAL POWER DOWN"),
             urc_helper("+PDP: DEACT"),
             urc_helper("+CPIN"),
             urc_helper("+CGACT"),
             urc_helper("+CDNSGIP"),
+            urc_helper("+CMTI"),
+            urc_helper("+CLIP"),
+            urc_helper("+CUSD"),
+            urc_helper("STATE"),
+            urc_helper("C"),
         ))(buf)?;
         Ok(r)
     }
@@ -171,6 +242,18 @@ mod tests {
         assert_matches!(urc, Urc::SmsReady);
     }
 
+    #[test]
+    fn can_parse_normal_power_down() {
+        let mut digester = SimcomDigester::new();
+
+        assert_eq!(
+            (DigestResult::Urc(b"NORMAL POWER DOWN"), 21),
+            digester.digest(b"\r\nNORMAL POWER DOWN\r\n")
+        );
+        let urc = Urc::parse(b"NORMAL POWER DOWN").unwrap();
+        assert_matches!(urc, Urc::NormalPowerDown);
+    }
+
     #[test]
     fn can_parse_pdp_deact() {
         let mut digester = SimcomDigester::new();
@@ -200,6 +283,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_parse_ring() {
+        let mut digester = SimcomDigester::new();
+
+        assert_eq!(
+            (DigestResult::Urc(b"RING"), 8),
+            digester.digest(b"\r\nRING\r\n")
+        );
+        let urc = Urc::parse(b"RING").unwrap();
+        assert_matches!(urc, Urc::Ring);
+    }
+
+    #[test]
+    fn can_parse_caller_id() {
+        let mut digester = SimcomDigester::new();
+
+        assert_eq!(
+            (
+                DigestResult::Urc(b"+CLIP: \"+4512345678\",145,\"\",0,\"\",0"),
+                38
+            ),
+            digester.digest(b"\r\n+CLIP: \"+4512345678\",145,\"\",0,\"\",0\r\n")
+        );
+        let urc = Urc::parse(b"+CLIP: \"+4512345678\",145,\"\",0,\"\",0").unwrap();
+        if let Urc::CallerId(number) = urc {
+            assert_eq!("+4512345678", number);
+        } else {
+            panic!("Invalid URC");
+        }
+    }
+
+    #[test]
+    fn can_parse_withheld_caller_id() {
+        let urc = Urc::parse(b"+CLIP: \"\",128,\"\",0,\"\",2").unwrap();
+        if let Urc::CallerId(number) = urc {
+            assert_eq!("", number);
+        } else {
+            panic!("Invalid URC");
+        }
+    }
+
+    #[test]
+    fn can_parse_ussd_response() {
+        let mut digester = SimcomDigester::new();
+
+        assert_eq!(
+            (
+                DigestResult::Urc(b"+CUSD: 0,\"Your balance is 12.50 DKK\",15"),
+                43
+            ),
+            digester.digest(b"\r\n+CUSD: 0,\"Your balance is 12.50 DKK\",15\r\n")
+        );
+        let urc = Urc::parse(b"+CUSD: 0,\"Your balance is 12.50 DKK\",15").unwrap();
+        if let Urc::UssdResponse(text) = urc {
+            assert_eq!("Your balance is 12.50 DKK", text);
+        } else {
+            panic!("Invalid URC");
+        }
+
+        let urc = Urc::parse(b"+CUSD: 4").unwrap();
+        if let Urc::UssdResponse(text) = urc {
+            assert_eq!("", text);
+        } else {
+            panic!("Invalid URC");
+        }
+    }
+
     #[test]
     fn can_parse_connect_ok() {
         let mut digester = SimcomDigester::new();
@@ -362,4 +512,128 @@ mod tests {
         let buf = &buf[4..];
         assert!(buf.is_empty());
     }
+
+    #[test]
+    fn can_parse_new_message_indication() {
+        let mut digester = SimcomDigester::new();
+
+        assert_eq!(
+            (DigestResult::Urc(b"+CMTI: \"SM\",3"), 17),
+            digester.digest(b"\r\n+CMTI: \"SM\",3\r\n")
+        );
+        let urc = Urc::parse(b"+CMTI: \"SM\",3").unwrap();
+
+        if let Urc::NewMessageIndication(urc) = urc {
+            assert_eq!("SM", urc.mem);
+            assert_eq!(3, urc.index);
+        } else {
+            panic!("Invalid URC");
+        }
+    }
+
+    #[test]
+    fn can_parse_message_received() {
+        let mut digester = SimcomDigester::new();
+
+        assert_eq!(
+            (
+                DigestResult::Urc(
+                    b"+CMT: \"+4512345678\",\"\",\"24/01/30,20:40:31+04\"\r\nHello World"
+                ),
+                62
+            ),
+            digester.digest(
+                b"\r\n+CMT: \"+4512345678\",\"\",\"24/01/30,20:40:31+04\"\r\nHello World\r\n"
+            )
+        );
+        let urc = Urc::parse(b"+CMT: \"+4512345678\",\"\",\"24/01/30,20:40:31+04\"\r\nHello World")
+            .unwrap();
+
+        if let Urc::MessageReceived(urc) = urc {
+            assert_eq!("+4512345678", urc.oa);
+            assert_eq!("24/01/30,20:40:31+04", urc.scts);
+            assert_eq!("Hello World", urc.text);
+        } else {
+            panic!("Invalid URC");
+        }
+    }
+
+    #[test]
+    fn can_parse_ip_state() {
+        let mut digester = SimcomDigester::new();
+
+        assert_eq!(
+            (DigestResult::Urc(b"STATE: IP STATUS"), 20),
+            digester.digest(b"\r\nSTATE: IP STATUS\r\n")
+        );
+        let urc = Urc::parse(b"STATE: IP STATUS").unwrap();
+
+        if let Urc::IpState(urc) = urc {
+            assert_eq!("IP STATUS", urc.state);
+        } else {
+            panic!("Invalid URC");
+        }
+    }
+
+    #[test]
+    fn can_parse_client_status() {
+        let mut digester = SimcomDigester::new();
+
+        assert_eq!(
+            (
+                DigestResult::Urc(b"C: 1,0,\"TCP\",\"1.2.3.4\",\"80\",\"CONNECTED\""),
+                43
+            ),
+            digester.digest(b"\r\nC: 1,0,\"TCP\",\"1.2.3.4\",\"80\",\"CONNECTED\"\r\n")
+        );
+        let urc = Urc::parse(b"C: 1,0,\"TCP\",\"1.2.3.4\",\"80\",\"CONNECTED\"").unwrap();
+
+        if let Urc::ClientStatus(urc) = urc {
+            assert_eq!(1, urc.id);
+            assert_eq!("TCP", urc.mode);
+            assert_eq!("1.2.3.4", urc.ip);
+            assert_eq!("80", urc.port);
+            assert_eq!(ClientState::Connected, urc.state);
+        } else {
+            panic!("Invalid URC");
+        }
+
+        let urc = Urc::parse(b"C: 0,,\"\",\"\",\"\",\"INITIAL\"").unwrap();
+
+        if let Urc::ClientStatus(urc) = urc {
+            assert_eq!(0, urc.id);
+            assert_eq!(ClientState::Initial, urc.state);
+        } else {
+            panic!("Invalid URC");
+        }
+    }
+
+    #[test]
+    fn can_parse_client_status_without_space() {
+        let mut digester = SimcomDigester::new();
+
+        assert_eq!(
+            (
+                DigestResult::Urc(b"C:1,0,\"TCP\",\"1.2.3.4\",\"80\",\"CONNECTED\""),
+                42
+            ),
+            digester.digest(b"\r\nC:1,0,\"TCP\",\"1.2.3.4\",\"80\",\"CONNECTED\"\r\n")
+        );
+        let urc = Urc::parse(b"C:1,0,\"TCP\",\"1.2.3.4\",\"80\",\"CONNECTED\"").unwrap();
+
+        if let Urc::ClientStatus(urc) = urc {
+            assert_eq!(1, urc.id);
+            assert_eq!(ClientState::Connected, urc.state);
+        } else {
+            panic!("Invalid URC");
+        }
+
+        let urc = Urc::parse(b"STATE:IP STATUS").unwrap();
+
+        if let Urc::IpState(urc) = urc {
+            assert_eq!("IP STATUS", urc.state);
+        } else {
+            panic!("Invalid URC");
+        }
+    }
 }
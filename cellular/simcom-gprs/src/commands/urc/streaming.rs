@@ -1,5 +1,7 @@
 use atat::nom::{branch, bytes, character, combinator, error::ParseError, sequence, IResult};
 
+use crate::commands::parse::streaming::prefix;
+
 /// Matches the equivalent of regex: \r\n+CGACT: [0-9],[0-9]
 pub fn parse_pdp_state<'a, Error: ParseError<&'a [u8]>>(
     buf: &'a [u8],
@@ -46,8 +48,7 @@ pub fn parse_data_available<'a, Error: ParseError<&'a [u8]>>(
     let (reminder, (_, frame, _)) = sequence::tuple((
         bytes::streaming::tag("\r\n"),
         combinator::recognize(sequence::tuple((
-            bytes::streaming::tag("+CIPRXGET:"),
-            combinator::opt(bytes::streaming::tag(b" ")),
+            prefix("+CIPRXGET:"),
             bytes::streaming::tag("1,"),
             character::streaming::u8,
         ))),
@@ -64,8 +65,7 @@ pub fn parse_read_data<'a, Error: ParseError<&'a [u8]>>(
     let (reminder, (_, frame)) = sequence::tuple((
         bytes::streaming::tag("\r\n"),
         combinator::recognize(sequence::tuple((
-            bytes::streaming::tag(b"+CIPRXGET:"),
-            combinator::opt(bytes::streaming::tag(b" ")),
+            prefix("+CIPRXGET:"),
             bytes::streaming::tag(b"2,"),
             character::streaming::u8,
             bytes::streaming::tag(","),
@@ -102,6 +102,24 @@ pub fn parse_receive<'a, Error: ParseError<&'a [u8]>>(
     Ok((reminder, (frame, 2 + frame.len() + 2)))
 }
 
+/// Matches the equivalent of regex: \r\n+CMT: .*\r\n.*\r\n
+pub fn parse_message_received<'a, Error: ParseError<&'a [u8]>>(
+    buf: &'a [u8],
+) -> IResult<&'a [u8], (&'a [u8], usize), Error> {
+    let (reminder, (_, frame, _)) = sequence::tuple((
+        bytes::streaming::tag("\r\n"),
+        combinator::recognize(sequence::tuple((
+            bytes::streaming::tag("+CMT: "),
+            bytes::streaming::take_until("\r\n"),
+            bytes::streaming::tag("\r\n"),
+            bytes::streaming::take_until("\r\n"),
+        ))),
+        bytes::streaming::tag("\r\n"),
+    ))(buf)?;
+
+    Ok((reminder, (frame, 2 + frame.len() + 2)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -164,4 +182,18 @@ mod tests {
         assert_eq!(b"+RECEIVE,2,1234:", result.0);
         assert_eq!(20, result.1);
     }
+
+    #[test]
+    fn can_parse_message_received() {
+        let (reminder, result) = parse_message_received::<()>(
+            b"\r\n+CMT: \"+4512345678\",\"\",\"24/01/30,20:40:31+04\"\r\nHello\r\nTAIL",
+        )
+        .unwrap();
+        assert_eq!(b"TAIL", reminder);
+        assert_eq!(
+            b"+CMT: \"+4512345678\",\"\",\"24/01/30,20:40:31+04\"\r\nHello",
+            result.0
+        );
+        assert_eq!(56, result.1);
+    }
 }
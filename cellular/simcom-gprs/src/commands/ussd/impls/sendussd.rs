@@ -0,0 +1,37 @@
+use atat::{atat_derive::AtatCmd, AtatCmd};
+
+use crate::commands::{ussd::SendUssd, NoResponse};
+
+impl AtatCmd for SendUssd<'_> {
+    type Response = NoResponse;
+
+    const MAX_LEN: usize = SendUssdInner::MAX_LEN;
+
+    fn write(&self, buf: &mut [u8]) -> usize {
+        let inner = SendUssdInner {
+            n: 1,
+            code: self.code,
+            dcs: self.dcs,
+        };
+        inner.write(buf)
+    }
+
+    fn parse(
+        &self,
+        resp: Result<&[u8], atat::InternalError>,
+    ) -> Result<Self::Response, atat::Error> {
+        resp?;
+        Ok(NoResponse)
+    }
+}
+
+#[derive(Clone, AtatCmd)]
+#[at_cmd("+CUSD", NoResponse, termination = "\r")]
+struct SendUssdInner<'a> {
+    #[at_arg(position = 0)]
+    n: u8,
+    #[at_arg(position = 1, len = 32)]
+    code: &'a str,
+    #[at_arg(position = 2)]
+    dcs: Option<u8>,
+}
@@ -0,0 +1,40 @@
+//! USSD commands according to 3GPP TS27.007
+mod impls;
+
+/// The maximum length of an USSD response string
+pub const MAX_USSD_LEN: usize = 182;
+
+/// 3.2.49 AT+CUSD Unstructured Supplementary Service Data
+///
+/// The result code presentation is enabled so that the network reply
+/// is reported in a "+CUSD" URC after OK.
+pub struct SendUssd<'a> {
+    /// The USSD code, e.g. "*100#"
+    pub code: &'a str,
+    /// The cell broadcast data coding scheme, 15 (GSM 7 bit default alphabet) if not specified
+    pub dcs: Option<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_hex::assert_eq_hex;
+
+    use crate::commands::AtatCmdEx;
+
+    use super::*;
+
+    #[test]
+    fn can_send_ussd() {
+        let cmd = SendUssd {
+            code: "*100#",
+            dcs: None,
+        };
+        assert_eq_hex!(b"AT+CUSD=1,\"*100#\"\r", cmd.to_vec().as_slice());
+
+        let cmd = SendUssd {
+            code: "*100#",
+            dcs: Some(15),
+        };
+        assert_eq_hex!(b"AT+CUSD=1,\"*100#\",15\r", cmd.to_vec().as_slice());
+    }
+}
@@ -1,4 +1,4 @@
-use atat::atat_derive::{AtatCmd, AtatEnum};
+use atat::atat_derive::{AtatCmd, AtatEnum, AtatResp};
 
 use super::NoResponse;
 
@@ -63,10 +63,45 @@ pub enum FlowControl {
     RtsCts = 2,
 }
 
+/// The rate selecting autobaud in [`SetBaudRate`]
+pub const AUTOBAUD: u32 = 0;
+
+/// 2.2.41 AT+IPR Set TE-TA Fixed Local Rate
+///
+/// The modem replies OK at the current rate and switches to the new rate immediately after,
+/// so the UART of the host must be reconfigured right after the response is received.
+/// The rate is stored in the non-volatile memory of the modem.
+#[derive(AtatCmd)]
+#[at_cmd("+IPR", NoResponse, termination = "\r")]
+pub struct SetBaudRate {
+    /// The baud rate, e.g. 115200, or [`AUTOBAUD`]
+    pub rate: u32,
+}
+
+/// 2.2.41 AT+IPR Set TE-TA Fixed Local Rate
+#[derive(AtatCmd)]
+#[at_cmd("+IPR?", BaudRate, termination = "\r")]
+pub struct GetBaudRate;
+
+/// 2.2.41 AT+IPR Set TE-TA Fixed Local Rate
+#[derive(AtatResp)]
+pub struct BaudRate {
+    /// The baud rate, or [`AUTOBAUD`]
+    pub rate: u32,
+}
+
+impl BaudRate {
+    pub fn is_autobaud(&self) -> bool {
+        self.rate == AUTOBAUD
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use assert_hex::assert_eq_hex;
 
+    use atat::AtatCmd;
+
     use crate::commands::AtatCmdEx;
 
     use super::*;
@@ -79,6 +114,14 @@ mod tests {
         assert_eq_hex!(b"ATE0\r", cmd.to_vec().as_slice());
     }
 
+    #[test]
+    fn can_set_response_format() {
+        let cmd = SetResponseFormat {
+            format: ResponseFormat::Text,
+        };
+        assert_eq_hex!(b"ATV1\r", cmd.to_vec().as_slice());
+    }
+
     #[test]
     fn can_reset() {
         let cmd = Reset {};
@@ -105,4 +148,26 @@ mod tests {
         };
         assert_eq_hex!(b"AT+IFC=0,0\r", cmd.to_vec().as_slice());
     }
+
+    #[test]
+    fn can_set_baud_rate() {
+        let cmd = SetBaudRate { rate: 460800 };
+        assert_eq_hex!(b"AT+IPR=460800\r", cmd.to_vec().as_slice());
+
+        let cmd = SetBaudRate { rate: AUTOBAUD };
+        assert_eq_hex!(b"AT+IPR=0\r", cmd.to_vec().as_slice());
+    }
+
+    #[test]
+    fn can_get_baud_rate() {
+        let cmd = GetBaudRate;
+        assert_eq_hex!(b"AT+IPR?\r", cmd.to_vec().as_slice());
+
+        let response = cmd.parse(Ok(b"+IPR: 115200")).unwrap();
+        assert_eq!(115200, response.rate);
+        assert!(!response.is_autobaud());
+
+        let response = cmd.parse(Ok(b"+IPR: 0")).unwrap();
+        assert!(response.is_autobaud());
+    }
 }
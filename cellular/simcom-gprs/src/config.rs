@@ -5,10 +5,37 @@ use embedded_hal::digital::OutputPin;
 pub trait SimcomConfig {
     type ResetPin: OutputPin;
 
+    #[deprecated(note = "Implement `flow_control()` instead")]
     const FLOW_CONTROL: FlowControl = FlowControl::None;
 
+    /// The number of times the local IP is queried (AT+CIFSR) after the wireless
+    /// connection is brought up before giving up.
+    /// The modem may reply ERROR to the first query(s) on slow networks.
+    const LOCAL_IP_ATTEMPTS: u8 = 3;
+
+    /// Whether the domain name servers are configured (AT+CDNSCFG) during data service setup.
+    /// Some carriers reject the command, in which case the network provided servers are used.
+    const CONFIGURE_DNS: bool = true;
+
+    /// Whether the local IP is read from the PDP context (AT+CGPADDR) instead of the AT+CIFSR reply
+    /// during data service setup.
+    /// AT+CIFSR replies the bare address without OK, which some firmware revisions garble.
+    /// It is still sent, as the modem does not allow connections to be started before it.
+    const LOCAL_IP_FROM_PDP_ADDRESS: bool = false;
+
+    /// Whether `Network::attach()` refuses to enter the pin when only one attempt remains (AT+SPIC),
+    /// so that a wrong pin cannot leave the SIM requiring the puk.
+    const PROTECT_LAST_PIN_ATTEMPT: bool = false;
+
     fn reset_pin(&mut self) -> &mut Self::ResetPin;
 
+    /// The flow control used on the serial connection to the modem.
+    /// Hardware flow control should be used for high throughput, e.g. when reading large data chunks.
+    fn flow_control(&self) -> FlowControl {
+        #[allow(deprecated)]
+        Self::FLOW_CONTROL
+    }
+
     fn atat_config(&self) -> Config {
         Config::new()
             // Not a value specified by simcom but it seems reasonable
@@ -21,6 +48,8 @@ pub trait SimcomConfig {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum FlowControl {
     /// No flow control is being used
     None,
@@ -1,22 +1,32 @@
 use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 
-use atat::{asynch::AtatClient, UrcSubscription};
+use atat::{asynch::AtatClient, AtatCmd, AtatIngress, UrcSubscription};
 use embassy_sync::{blocking_mutex::raw::NoopRawMutex, mutex::Mutex, pubsub::WaitResult};
-use embassy_time::{Duration, Timer};
+use embassy_time::{with_timeout, Duration, Instant, Timer};
 use embedded_hal::digital::OutputPin;
-use embedded_io_async::Write;
-use futures_intrusive::sync::LocalMutex;
+use embedded_io_async::{Read, Write};
+use futures::{
+    future::{self, Either},
+    pin_mut,
+};
+use futures_intrusive::sync::{LocalMutex, LocalMutexGuard};
 use heapless::Vec;
 
 use crate::{
-    commands::{gsm, simcom::GetCcid, urc::Urc, v25ter, AT},
-    services::data::SocketError,
-    DriverError, FlowControl, PartNumber, SimcomClient, SimcomConfig, SimcomResponseSlot,
+    commands::{
+        gsm,
+        simcom::{GetCcid, PowerDown},
+        tcpip::{ClientState, CloseConnection, GetConnectionStatus},
+        urc::Urc,
+        v25ter, RawCommand, AT,
+    },
+    services::{data::SocketError, network::NetworkError},
+    CmeError, DriverError, FlowControl, PartNumber, SimcomClient, SimcomConfig, SimcomResponseSlot,
     SimcomUrcChannel, MAX_SOCKETS,
 };
 
 pub(crate) const URC_CAPACITY: usize = 1 + 2 * (1 + MAX_SOCKETS); // A dns reply, and (RXGET + CLOSED) per socket + background subscription
-pub(crate) const URC_SUBSCRIBERS: usize = 2 + MAX_SOCKETS; // One for dns, one for background subscription, and one for each socket reply subscription
+pub(crate) const URC_SUBSCRIBERS: usize = 2 + MAX_SOCKETS; // One for background subscription, one for a request awaiting its urc (serialized by `Handle::urc_request`), and one for each socket reply subscription
 
 pub(crate) type SocketState = AtomicU8;
 pub(crate) const SOCKET_STATE_UNKNOWN: u8 = 0;
@@ -24,6 +34,9 @@ pub(crate) const SOCKET_STATE_UNUSED: u8 = 1;
 pub(crate) const SOCKET_STATE_USED: u8 = 2;
 pub(crate) const SOCKET_STATE_DROPPED: u8 = 3;
 
+/// The interval at which dropped sockets are closed by [`SimcomDevice::run()`] when no URCs arrive
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(5);
+
 pub struct SimcomDevice<'buf, 'sub, AtCl: AtatClient, Config: SimcomConfig> {
     pub handle: Handle<'sub, AtCl>,
     pub(crate) urc_channel: &'buf SimcomUrcChannel,
@@ -37,8 +50,10 @@ pub struct Handle<'sub, AtCl: AtatClient> {
     pub(crate) socket_state: Vec<SocketState, MAX_SOCKETS>,
     pub(crate) data_available: [AtomicBool; MAX_SOCKETS],
     pub(crate) max_urc_len: usize,
-    background_subscription:
+    powered_down: AtomicBool,
+    pub(crate) background_subscription:
         Mutex<NoopRawMutex, UrcSubscription<'sub, Urc, URC_CAPACITY, URC_SUBSCRIBERS>>,
+    pub(crate) urc_request: Mutex<NoopRawMutex, ()>,
 }
 
 impl<'buf, 'sub, W: Write, Config: SimcomConfig, const INGRESS_BUF_SIZE: usize>
@@ -77,7 +92,9 @@ where
                 socket_state: Vec::new(),
                 data_available: Default::default(),
                 max_urc_len,
+                powered_down: AtomicBool::new(false),
                 background_subscription: Mutex::new(urc_channel.subscribe().unwrap()),
+                urc_request: Mutex::new(()),
             },
             urc_channel,
             part_number: None,
@@ -104,14 +121,27 @@ where
     }
 
     /// Setup the fundamentals for communicating with the modem
-    pub async fn setup(&mut self) -> Result<(), DriverError> {
+    ///
+    /// This must also be called when the modem is powered on again after `power_down()`.
+    /// The modem is set up even if `DriverError::PinRequired` or `DriverError::PukRequired` is returned,
+    /// so the application can continue by entering the code.
+    /// Setup does not fail if the pin status cannot be read, e.g. because no SIM is inserted.
+    pub async fn setup(&mut self) -> Result<(), DriverError>
+    where
+        AtCl: 'static,
+    {
         self.is_alive(20).await?;
+        self.handle.powered_down.store(false, Ordering::Release);
 
         let mut client = self.handle.client.lock().await;
         client.send(&v25ter::SetFactoryDefinedConfiguration).await?;
 
         client.send(&v25ter::Reset).await?;
 
+        // The digester expects no echo and verbose text result codes.
+        // If the stored profile says otherwise, echoed commands and numeric
+        // result codes are fed to the parser and urcs are misparsed.
+        // This must be done after the reset, as that loads the stored profile.
         client
             .send(&v25ter::SetCommandEchoMode {
                 mode: v25ter::CommandEchoMode::Disable,
@@ -119,23 +149,27 @@ where
             .await?;
 
         client
-            .send(&gsm::SetMobileEquipmentError {
-                value: gsm::MobileEquipmentError::EnableNumeric,
+            .send(&v25ter::SetResponseFormat {
+                format: v25ter::ResponseFormat::Text,
             })
             .await?;
 
-        let (from_modem, to_modem) = match Config::FLOW_CONTROL {
-            FlowControl::None => (v25ter::FlowControl::Disabled, v25ter::FlowControl::Disabled),
-            FlowControl::RtsCts => (v25ter::FlowControl::RtsCts, v25ter::FlowControl::RtsCts),
-        };
-
         client
-            .send(&v25ter::SetFlowControl {
-                from_modem,
-                to_modem: Some(to_modem),
+            .send(&gsm::SetMobileEquipmentError {
+                value: gsm::MobileEquipmentError::EnableNumeric,
             })
             .await?;
 
+        // Flow control is disabled by the factory defaults
+        if self.config.flow_control() == FlowControl::RtsCts {
+            client
+                .send(&v25ter::SetFlowControl {
+                    from_modem: v25ter::FlowControl::RtsCts,
+                    to_modem: Some(v25ter::FlowControl::RtsCts),
+                })
+                .await?;
+        }
+
         let response = client.send(&gsm::GetManufacturerId).await?;
         if response.manufacturer != b"SIMCOM_Ltd" {
             return Err(DriverError::UnsupportedManufacturer);
@@ -166,8 +200,18 @@ where
                 .push(SocketState::new(SOCKET_STATE_UNKNOWN))
                 .unwrap();
         }
-
-        Ok(())
+        drop(client);
+
+        // AT+CPIN?
+        match self.handle.get_pin_status(self.urc_channel).await {
+            Ok(gsm::PinStatusCode::SimPin) => Err(DriverError::PinRequired),
+            Ok(gsm::PinStatusCode::SimPuk) => Err(DriverError::PukRequired),
+            Ok(_) => Ok(()),
+            Err(e) => {
+                warn!("Unable to get the pin status: {:?}", e);
+                Ok(())
+            }
+        }
     }
 
     /// Check that the cellular module is alive.
@@ -190,7 +234,7 @@ where
 
     /// Get the sim card iccid
     pub async fn iccid(&self) -> Result<u128, DriverError> {
-        let mut client = self.handle.client.lock().await;
+        let mut client = self.handle.lock_client().await?;
         for _ in 0..10 {
             match client.send(&GetCcid).await {
                 Ok(response) => {
@@ -210,6 +254,107 @@ where
             atat::CmeError::SimNotInserted,
         )))
     }
+
+    /// Send a list of commands, e.g. "AT+CSCLK=0", stopping on the first that fails
+    ///
+    /// Each command must respond within `timeout`.
+    /// The error is `DriverError::BatchCommand` with the index of the command that failed.
+    pub async fn send_batch(
+        &mut self,
+        commands: &[&str],
+        timeout: Duration,
+    ) -> Result<(), DriverError> {
+        let mut client = self.handle.lock_client().await?;
+        for (index, &command) in commands.iter().enumerate() {
+            let command =
+                RawCommand::new(command).map_err(|e| DriverError::BatchCommand(index, e))?;
+
+            let result = with_timeout(timeout, client.send(&command))
+                .await
+                .unwrap_or(Err(atat::Error::Timeout));
+            result.map_err(|e| DriverError::BatchCommand(index, e))?;
+        }
+        Ok(())
+    }
+}
+
+impl<AtCl: AtatClient + 'static, Config: SimcomConfig> SimcomDevice<'_, '_, AtCl, Config> {
+    /// Gracefully power down the modem
+    ///
+    /// The modem deregisters from the network before it turns off,
+    /// which is preferred over simply cutting the power.
+    /// Commands sent after the modem is powered down fail with `DriverError::PoweredDown`.
+    pub async fn power_down(&mut self) -> Result<(), DriverError> {
+        self.handle
+            .send_and_await_urc(
+                self.urc_channel,
+                &PowerDown,
+                Duration::from_secs(5),
+                DriverError::PowerDownTimeout,
+                |urc| matches!(urc, Urc::NormalPowerDown).then_some(()),
+            )
+            .await?;
+
+        self.handle.powered_down.store(true, Ordering::Release);
+        Ok(())
+    }
+}
+
+impl<AtCl: AtatClient + 'static, Config: SimcomConfig> SimcomDevice<'_, '_, AtCl, Config> {
+    /// Run the background work of the driver
+    ///
+    /// The future never completes and should be spawned once by the application,
+    /// after which the services can be used from other tasks.
+    /// It feeds everything received on `serial` to `ingress`,
+    /// handles URCs as they arrive, and closes sockets that have been dropped.
+    pub async fn run(&self, mut ingress: impl AtatIngress, mut serial: impl Read) -> ! {
+        let ingress = ingress.read_from(&mut serial);
+        let background = self.handle.run_background();
+        pin_mut!(ingress, background);
+
+        match future::select(ingress, background).await {
+            Either::Left((never, _)) => never,
+            Either::Right((never, _)) => never,
+        }
+    }
+}
+
+/// The modem is powered down and does not respond to commands
+pub(crate) struct PoweredDown;
+
+impl From<PoweredDown> for DriverError {
+    fn from(_: PoweredDown) -> Self {
+        DriverError::PoweredDown
+    }
+}
+
+impl From<PoweredDown> for NetworkError {
+    fn from(_: PoweredDown) -> Self {
+        NetworkError::PoweredDown
+    }
+}
+
+impl From<PoweredDown> for SocketError {
+    fn from(_: PoweredDown) -> Self {
+        SocketError::PoweredDown
+    }
+}
+
+impl<AtCl: AtatClient> Handle<'_, AtCl> {
+    /// Fail if the modem is powered down
+    pub(crate) fn ensure_powered(&self) -> Result<(), PoweredDown> {
+        if self.powered_down.load(Ordering::Acquire) {
+            Err(PoweredDown)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Lock the AT client, or fail immediately if the modem is powered down
+    pub(crate) async fn lock_client(&self) -> Result<LocalMutexGuard<'_, AtCl>, PoweredDown> {
+        self.ensure_powered()?;
+        Ok(self.client.lock().await)
+    }
 }
 
 impl<AtCl: AtatClient + 'static> Handle<'_, AtCl> {
@@ -239,6 +384,69 @@ impl<AtCl: AtatClient + 'static> Handle<'_, AtCl> {
         }
     }
 
+    /// Send a command and wait for the first URC accepted by `matcher`
+    ///
+    /// The subscription is created while the client is locked, before the command is sent,
+    /// so a URC that arrives right after the response is not missed.
+    /// Concurrent requests are serialized such that only one of them holds a subscription.
+    /// `timeout_error` is returned if no URC was matched within `timeout`.
+    pub(crate) async fn send_and_await_urc<Cmd: AtatCmd, T, E>(
+        &self,
+        urc_channel: &SimcomUrcChannel,
+        cmd: &Cmd,
+        timeout: Duration,
+        timeout_error: E,
+        mut matcher: impl FnMut(Urc) -> Option<T>,
+    ) -> Result<T, E>
+    where
+        E: From<PoweredDown> + From<atat::Error>,
+    {
+        let _request = self.urc_request.lock().await;
+        let mut urc_subscription = {
+            let mut client = self.lock_client().await?;
+            let subscription = urc_channel.subscribe().unwrap();
+
+            client.send(cmd).await?;
+
+            subscription
+        };
+
+        let timeout_instant = Instant::now() + timeout;
+        while let Some(remaining) = timeout_instant.checked_duration_since(Instant::now()) {
+            let Ok(urc) = with_timeout(remaining, urc_subscription.next_message_pure()).await
+            else {
+                break;
+            };
+            self.drain_background_urcs();
+
+            if let Some(value) = matcher(urc) {
+                return Ok(value);
+            }
+        }
+
+        Err(timeout_error)
+    }
+
+    /// Query the pin status
+    ///
+    /// The status is reported in a "+CPIN" URC and not in the response to `AT+CPIN?`.
+    pub(crate) async fn get_pin_status(
+        &self,
+        urc_channel: &SimcomUrcChannel,
+    ) -> Result<gsm::PinStatusCode, NetworkError> {
+        self.send_and_await_urc(
+            urc_channel,
+            &gsm::GetPinStatus,
+            Duration::from_secs(5),
+            NetworkError::PinTimeout,
+            |urc| match urc {
+                Urc::PinStatus(status) => Some(status.code),
+                _ => None,
+            },
+        )
+        .await
+    }
+
     pub(crate) fn drain_background_urcs(&self) {
         if let Ok(mut subscription) = self.background_subscription.try_lock() {
             while let Some(urc) = subscription.try_next_message() {
@@ -250,9 +458,68 @@ impl<AtCl: AtatClient + 'static> Handle<'_, AtCl> {
         }
     }
 
+    /// Handle URCs as they arrive and close dropped sockets
+    async fn run_background(&self) -> ! {
+        loop {
+            {
+                let mut subscription = self.background_subscription.lock().await;
+                if let Ok(urc) =
+                    with_timeout(CLEANUP_INTERVAL, subscription.next_message_pure()).await
+                {
+                    self.handle_urc(urc);
+                    while let Some(urc) = subscription.try_next_message_pure() {
+                        self.handle_urc(urc);
+                    }
+                }
+            }
+
+            self.close_dropped_sockets().await;
+        }
+    }
+
+    /// Close sockets that have been dropped by the application
+    pub(crate) async fn close_dropped_sockets(&self) {
+        for (id, state) in self.socket_state.iter().enumerate() {
+            if state.load(Ordering::Relaxed) == SOCKET_STATE_DROPPED {
+                let Ok(mut client) = self.lock_client().await else {
+                    // Nothing can be closed while the modem is powered down
+                    return;
+                };
+
+                // The close connection command does not return anything.
+                // The actual transition from USED to UNUSED happens in URC handling,
+                // as a "<id>, CLOSE OK" URC is sent when the connection is closed.
+                match client.send(&CloseConnection { id }).await {
+                    Ok(_) => {}
+                    Err(atat::Error::CmeError(e))
+                        if matches!(
+                            CmeError::from(e),
+                            CmeError::OperationNotAllowed | CmeError::Unspecified
+                        ) =>
+                    {
+                        // CME Error seems to be returned if the connection is already closed
+                        // Verify that it is actually the case
+                        if let Ok(status) = client.send(&GetConnectionStatus { id }).await {
+                            if status.state == ClientState::Closed {
+                                warn!("[{}] Socket already closed", id);
+                                state.store(SOCKET_STATE_UNUSED, Ordering::Release);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        // If the close is not sent, we will simply retry later when `close_dropped_sockets()` is called again.
+                        error!("[{}] Close request failed with error {}", id, e);
+                    }
+                }
+            }
+        }
+    }
+
     fn handle_urc(&self, urc: Urc) {
         match urc {
             Urc::CallReady => {}
+            Urc::Ring => debug!("Incoming call"),
+            Urc::CallerId(number) => debug!("Incoming call from {}", number),
             Urc::SmsReady => {}
             Urc::PinStatus(_) => {}
             Urc::ConnectOk(_id) => {}
@@ -265,6 +532,7 @@ impl<AtCl: AtatClient + 'static> Handle<'_, AtCl> {
                 self.socket_state[id].store(SOCKET_STATE_UNUSED, Ordering::Release);
             }
             Urc::PdpDeact => info!("GPRS is disconnected by network"),
+            Urc::NormalPowerDown => info!("Modem is powered down"),
             Urc::PdbState(state) => {
                 debug!("PDP state for context {} is {:?}", state.cid, state.state);
             }
@@ -279,6 +547,13 @@ impl<AtCl: AtatClient + 'static> Handle<'_, AtCl> {
                 debug!("[{}] Data available to be read", id);
                 self.data_available[id].store(true, Ordering::Release);
             }
+            Urc::IpState(_) => {}
+            Urc::ClientStatus(_) => {}
+            Urc::NewMessageIndication(indication) => {
+                debug!("New SMS message stored at index {}", indication.index);
+            }
+            Urc::MessageReceived(_) => debug!("New SMS message received"),
+            Urc::UssdResponse(_) => debug!("USSD response received"),
             Urc::ReadData(result) => {
                 debug!(
                     "[{}] Received {} bytes, there are {} pending bytes available",
@@ -289,3 +564,327 @@ impl<AtCl: AtatClient + 'static> Handle<'_, AtCl> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use core::assert_matches::assert_matches;
+
+    use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, channel::Channel};
+
+    use crate::services::{
+        device_mock::{respond, setup_atat, ResetPin},
+        serial_mock::ChannelSerial,
+    };
+
+    use super::*;
+
+    struct RtsCtsConfig(ResetPin);
+
+    impl SimcomConfig for RtsCtsConfig {
+        type ResetPin = ResetPin;
+
+        fn reset_pin(&mut self) -> &mut Self::ResetPin {
+            &mut self.0
+        }
+
+        fn flow_control(&self) -> FlowControl {
+            FlowControl::RtsCts
+        }
+    }
+
+    const SETUP_PREFIX: &[(&[u8], &[u8])] = &[
+        (b"AT\r", b"\r\nOK\r\n"),
+        (b"AT&F0\r", b"\r\nOK\r\n"),
+        (b"ATZ\r", b"\r\nOK\r\n"),
+        (b"ATE0\r", b"\r\nOK\r\n"),
+        (b"ATV1\r", b"\r\nOK\r\n"),
+        (b"AT+CMEE=1\r", b"\r\nOK\r\n"),
+    ];
+
+    #[cfg(feature = "sim800")]
+    const MODEL: (PartNumber, &[u8]) = (PartNumber::Sim800, b"\r\nSIMCOM_SIM800\r\n\r\nOK\r\n");
+    #[cfg(not(feature = "sim800"))]
+    const MODEL: (PartNumber, &[u8]) = (PartNumber::Sim900, b"\r\nSIMCOM_SIM900\r\n\r\nOK\r\n");
+
+    const SETUP_SUFFIX: &[(&[u8], &[u8])] = &[
+        (b"AT+CGMI\r", b"\r\nSIMCOM_Ltd\r\n\r\nOK\r\n"),
+        (b"AT+CGMM\r", MODEL.1),
+        (b"AT+CGMR\r", b"\r\nRevision:1418B04SIM800C24\r\n\r\nOK\r\n"),
+    ];
+
+    const PIN_READY: &[(&[u8], &[u8])] = &[(b"AT+CPIN?\r", b"\r\n+CPIN: READY\r\n\r\nOK\r\n")];
+
+    #[tokio::test]
+    async fn setup_does_not_set_flow_control_by_default() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+
+        let exchange = [SETUP_PREFIX, SETUP_SUFFIX, PIN_READY].concat();
+        let (result, _) = tokio::join!(
+            device.setup(),
+            respond(&mut ingress, &mut serial, &exchange)
+        );
+
+        result.unwrap();
+        assert!(serial.try_next_message_pure().is_none());
+    }
+
+    #[tokio::test]
+    async fn setup_reports_pin_required() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+
+        let exchange = [
+            SETUP_PREFIX,
+            SETUP_SUFFIX,
+            &[(b"AT+CPIN?\r", b"\r\n+CPIN: SIM PIN\r\n\r\nOK\r\n")],
+        ]
+        .concat();
+        let (result, _) = tokio::join!(
+            device.setup(),
+            respond(&mut ingress, &mut serial, &exchange)
+        );
+
+        assert_matches!(result, Err(DriverError::PinRequired));
+        assert_eq!(Some(MODEL.0), device.part_number);
+    }
+
+    #[tokio::test]
+    async fn setup_succeeds_when_pin_status_is_unavailable() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+
+        let exchange = [
+            SETUP_PREFIX,
+            SETUP_SUFFIX,
+            &[(b"AT+CPIN?\r", b"\r\n+CME ERROR: 10\r\n")],
+        ]
+        .concat();
+        let (result, _) = tokio::join!(
+            device.setup(),
+            respond(&mut ingress, &mut serial, &exchange)
+        );
+
+        result.unwrap();
+        assert_eq!(Some(MODEL.0), device.part_number);
+    }
+
+    #[tokio::test]
+    async fn setup_sets_hardware_flow_control() {
+        let (mut ingress, mut device, mut serial) = setup_atat!(RtsCtsConfig(ResetPin(true)));
+
+        let exchange = [
+            SETUP_PREFIX,
+            &[(b"AT+IFC=2,2\r", b"\r\nOK\r\n")],
+            SETUP_SUFFIX,
+            PIN_READY,
+        ]
+        .concat();
+        let (result, _) = tokio::join!(
+            device.setup(),
+            respond(&mut ingress, &mut serial, &exchange)
+        );
+
+        result.unwrap();
+        assert!(serial.try_next_message_pure().is_none());
+    }
+
+    #[tokio::test]
+    async fn setup_sets_hardware_flow_control_from_deprecated_const() {
+        struct ConstRtsCtsConfig(ResetPin);
+
+        impl SimcomConfig for ConstRtsCtsConfig {
+            type ResetPin = ResetPin;
+
+            const FLOW_CONTROL: FlowControl = FlowControl::RtsCts;
+
+            fn reset_pin(&mut self) -> &mut Self::ResetPin {
+                &mut self.0
+            }
+        }
+
+        let (mut ingress, mut device, mut serial) = setup_atat!(ConstRtsCtsConfig(ResetPin(true)));
+
+        let exchange = [
+            SETUP_PREFIX,
+            &[(b"AT+IFC=2,2\r", b"\r\nOK\r\n")],
+            SETUP_SUFFIX,
+            PIN_READY,
+        ]
+        .concat();
+        let (result, _) = tokio::join!(
+            device.setup(),
+            respond(&mut ingress, &mut serial, &exchange)
+        );
+
+        result.unwrap();
+        assert!(serial.try_next_message_pure().is_none());
+    }
+
+    #[tokio::test]
+    async fn can_power_down() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+
+        let (result, _) = tokio::join!(
+            device.power_down(),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[(b"AT+CPOWD=1\r", b"\r\nNORMAL POWER DOWN\r\n")]
+            )
+        );
+
+        result.unwrap();
+    }
+
+    #[tokio::test]
+    async fn commands_fail_after_power_down() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+
+        let (result, _) = tokio::join!(
+            device.power_down(),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[(b"AT+CPOWD=1\r", b"\r\nNORMAL POWER DOWN\r\n")]
+            )
+        );
+        result.unwrap();
+
+        assert_matches!(device.iccid().await, Err(DriverError::PoweredDown));
+        assert!(serial.try_next_message_pure().is_none());
+    }
+
+    #[tokio::test]
+    async fn send_batch_stops_on_first_error() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+
+        let (result, _) = tokio::join!(
+            device.send_batch(
+                &["AT+CSCLK=0", "AT+CNETLIGHT=0", "AT+CMGF=1"],
+                Duration::from_secs(1)
+            ),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[
+                    (b"AT+CSCLK=0\r", b"\r\nOK\r\n"),
+                    (b"AT+CNETLIGHT=0\r", b"\r\nERROR\r\n"),
+                ]
+            )
+        );
+
+        assert_matches!(
+            result,
+            Err(DriverError::BatchCommand(1, atat::Error::Error))
+        );
+        assert!(serial.try_next_message_pure().is_none());
+    }
+
+    #[tokio::test]
+    async fn send_and_await_urc_resolves_on_matching_urc() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+
+        let (result, _) = tokio::join!(
+            device.handle.send_and_await_urc(
+                device.urc_channel,
+                &AT,
+                Duration::from_secs(1),
+                DriverError::PowerDownTimeout,
+                |urc| matches!(urc, Urc::SmsReady).then_some(()),
+            ),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[(b"AT\r", b"\r\nOK\r\n\r\nCall Ready\r\n\r\nSMS Ready\r\n")]
+            )
+        );
+
+        result.unwrap();
+    }
+
+    #[tokio::test]
+    async fn send_and_await_urc_is_serialized_when_all_sockets_are_subscribed() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let _socket_subscriptions: Vec<_, MAX_SOCKETS> = (0..MAX_SOCKETS)
+            .map(|_| device.urc_channel.subscribe().unwrap())
+            .collect();
+
+        let request = || {
+            device.handle.send_and_await_urc(
+                device.urc_channel,
+                &AT,
+                Duration::from_secs(1),
+                DriverError::PowerDownTimeout,
+                |urc| matches!(urc, Urc::SmsReady).then_some(()),
+            )
+        };
+
+        let (first, second, _) = tokio::join!(request(), request(), async {
+            respond(&mut ingress, &mut serial, &[(b"AT\r", b"\r\nOK\r\n")]).await;
+            Timer::after_millis(100).await;
+            ingress.write(b"\r\nSMS Ready\r\n").await;
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[(b"AT\r", b"\r\nOK\r\n\r\nSMS Ready\r\n")],
+            )
+            .await;
+        });
+
+        first.unwrap();
+        second.unwrap();
+    }
+
+    #[tokio::test]
+    async fn send_and_await_urc_times_out_without_matching_urc() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+
+        let (result, _) = tokio::join!(
+            device.handle.send_and_await_urc(
+                device.urc_channel,
+                &AT,
+                Duration::from_millis(100),
+                DriverError::PowerDownTimeout,
+                |urc| matches!(urc, Urc::SmsReady).then_some(()),
+            ),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[(b"AT\r", b"\r\nOK\r\n\r\nCall Ready\r\n")]
+            )
+        );
+
+        assert_matches!(result, Err(DriverError::PowerDownTimeout));
+    }
+
+    #[tokio::test]
+    async fn run_handles_urcs_and_closes_dropped_sockets() {
+        static RX: Channel<CriticalSectionRawMutex, &'static [u8], 4> = Channel::new();
+        let (ingress, mut device, mut serial) = setup_atat!();
+        for state in [SOCKET_STATE_USED, SOCKET_STATE_DROPPED] {
+            device
+                .handle
+                .socket_state
+                .push(SocketState::new(state))
+                .unwrap();
+        }
+
+        RX.try_send(b"\r\n0, CLOSED\r\n").unwrap();
+
+        let run = device.run(ingress, ChannelSerial(&RX));
+        let modem = async {
+            let sent = with_timeout(Duration::from_secs(1), serial.next_message_pure())
+                .await
+                .unwrap();
+            assert_eq!(b"AT+CIPCLOSE=1\r", sent.as_slice());
+            assert_eq!(
+                SOCKET_STATE_UNUSED,
+                device.handle.socket_state[0].load(Ordering::Relaxed)
+            );
+        };
+        pin_mut!(run, modem);
+
+        match future::select(run, modem).await {
+            Either::Left((never, _)) => never,
+            Either::Right(_) => {}
+        }
+    }
+}
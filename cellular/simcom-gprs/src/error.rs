@@ -8,6 +8,14 @@ pub enum DriverError {
     UnsupportedModel,
     Atat(atat::Error),
     AlreadyTaken,
+    PowerDownTimeout,
+    PoweredDown,
+    /// The modem is set up but the SIM is locked, so the pin must be entered e.g. with `Network::attach()`
+    PinRequired,
+    /// The modem is set up but the SIM is blocked, so the puk must be entered with `Network::enter_puk()`
+    PukRequired,
+    /// A command in a batch failed, where the index of the command is the first value
+    BatchCommand(usize, atat::Error),
     Network(NetworkError),
     Socket(SocketError),
 }
@@ -22,6 +30,7 @@ impl From<NetworkError> for DriverError {
     fn from(value: NetworkError) -> Self {
         match value {
             NetworkError::Atat(atat) => DriverError::Atat(atat),
+            NetworkError::PoweredDown => DriverError::PoweredDown,
             other => DriverError::Network(other),
         }
     }
@@ -31,7 +40,102 @@ impl From<SocketError> for DriverError {
     fn from(value: SocketError) -> Self {
         match value {
             SocketError::Atat(atat) => DriverError::Atat(atat),
+            SocketError::PoweredDown => DriverError::PoweredDown,
             other => DriverError::Socket(other),
         }
     }
 }
+
+/// The common "+CME ERROR" codes reported by the SIM800 and SIM900 modems
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CmeError {
+    OperationNotAllowed,
+    OperationNotSupported,
+    SimNotInserted,
+    SimPinRequired,
+    SimPukRequired,
+    SimFailure,
+    SimBusy,
+    SimWrong,
+    IncorrectPassword,
+    SimPin2Required,
+    SimPuk2Required,
+    MemoryFull,
+    NoNetworkService,
+    NetworkTimeout,
+    /// Code 100, which the modem reports for a number of failures,
+    /// e.g. when closing a connection that is already closed or when GPRS attach fails
+    Unspecified,
+    /// A code that is not covered by the other variants
+    Unknown(u16),
+}
+
+impl From<u16> for CmeError {
+    fn from(value: u16) -> Self {
+        match value {
+            3 => CmeError::OperationNotAllowed,
+            4 => CmeError::OperationNotSupported,
+            10 => CmeError::SimNotInserted,
+            11 => CmeError::SimPinRequired,
+            12 => CmeError::SimPukRequired,
+            13 => CmeError::SimFailure,
+            14 => CmeError::SimBusy,
+            15 => CmeError::SimWrong,
+            16 => CmeError::IncorrectPassword,
+            17 => CmeError::SimPin2Required,
+            18 => CmeError::SimPuk2Required,
+            20 => CmeError::MemoryFull,
+            30 => CmeError::NoNetworkService,
+            31 => CmeError::NetworkTimeout,
+            100 => CmeError::Unspecified,
+            other => CmeError::Unknown(other),
+        }
+    }
+}
+
+impl From<CmeError> for u16 {
+    fn from(value: CmeError) -> Self {
+        match value {
+            CmeError::OperationNotAllowed => 3,
+            CmeError::OperationNotSupported => 4,
+            CmeError::SimNotInserted => 10,
+            CmeError::SimPinRequired => 11,
+            CmeError::SimPukRequired => 12,
+            CmeError::SimFailure => 13,
+            CmeError::SimBusy => 14,
+            CmeError::SimWrong => 15,
+            CmeError::IncorrectPassword => 16,
+            CmeError::SimPin2Required => 17,
+            CmeError::SimPuk2Required => 18,
+            CmeError::MemoryFull => 20,
+            CmeError::NoNetworkService => 30,
+            CmeError::NetworkTimeout => 31,
+            CmeError::Unspecified => 100,
+            CmeError::Unknown(code) => code,
+        }
+    }
+}
+
+impl From<atat::CmeError> for CmeError {
+    fn from(value: atat::CmeError) -> Self {
+        (value as u16).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_convert_cme_error() {
+        assert_eq!(CmeError::OperationNotAllowed, CmeError::from(3));
+        assert_eq!(CmeError::Unspecified, CmeError::from(100));
+        assert_eq!(CmeError::Unknown(99), CmeError::from(99));
+        assert_eq!(
+            CmeError::SimNotInserted,
+            CmeError::from(atat::CmeError::SimNotInserted)
+        );
+        assert_eq!(16, u16::from(CmeError::IncorrectPassword));
+    }
+}
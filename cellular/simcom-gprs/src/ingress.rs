@@ -6,9 +6,22 @@ use crate::{
     SimcomDigester, SimcomResponseSlot, SimcomUrcChannel,
 };
 
-pub struct SimcomIngress<'a, const INGRESS_BUF_SIZE: usize>(
-    Ingress<'a, SimcomDigester, Urc, INGRESS_BUF_SIZE, URC_CAPACITY, URC_SUBSCRIBERS>,
-);
+/// Fault detected by the ingress while processing received bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum IngressFault {
+    /// The number of buffered bytes that could not be parsed exceeded the configured threshold
+    Desynchronized,
+    /// The ingress buffer is full without containing a complete response or URC
+    BufferOverflow,
+}
+
+pub struct SimcomIngress<'a, const INGRESS_BUF_SIZE: usize> {
+    inner: Ingress<'a, SimcomDigester, Urc, INGRESS_BUF_SIZE, URC_CAPACITY, URC_SUBSCRIBERS>,
+    buf_len: usize,
+    fault_threshold: usize,
+    fault_callback: Option<fn(IngressFault)>,
+}
 
 impl<'a, const INGRESS_BUF_SIZE: usize> SimcomIngress<'a, INGRESS_BUF_SIZE> {
     pub fn new(
@@ -16,29 +29,97 @@ impl<'a, const INGRESS_BUF_SIZE: usize> SimcomIngress<'a, INGRESS_BUF_SIZE> {
         res_slot: &'a SimcomResponseSlot<INGRESS_BUF_SIZE>,
         urc_channel: &'a SimcomUrcChannel,
     ) -> Self {
-        Self(Ingress::new(
-            SimcomDigester::new(),
-            buf,
-            res_slot,
-            urc_channel,
-        ))
+        let buf_len = buf.len();
+        Self {
+            inner: Ingress::new(SimcomDigester::new(), buf, res_slot, urc_channel),
+            buf_len,
+            fault_threshold: buf_len,
+            fault_callback: None,
+        }
+    }
+
+    /// Invoke `callback` when the ingress loses sync with the modem.
+    ///
+    /// The callback is invoked when `threshold` or more bytes are buffered without
+    /// being recognized as a response or URC, or if the buffer overflows.
+    /// The ingress buffer is cleared before the callback is invoked.
+    /// The threshold should be larger than the largest expected response.
+    pub fn with_fault_callback(mut self, threshold: usize, callback: fn(IngressFault)) -> Self {
+        self.fault_threshold = threshold;
+        self.fault_callback = Some(callback);
+        self
+    }
+
+    fn check_fault(&mut self) {
+        let Some(callback) = self.fault_callback else {
+            return;
+        };
+
+        let pending = self.buf_len - self.inner.write_buf().len();
+        let fault = if pending == self.buf_len {
+            IngressFault::BufferOverflow
+        } else if pending >= self.fault_threshold {
+            IngressFault::Desynchronized
+        } else {
+            return;
+        };
+
+        warn!("Ingress fault {:?} with {} pending bytes", fault, pending);
+        self.inner.clear();
+        callback(fault);
     }
 }
 
 impl<const INGRESS_BUF_SIZE: usize> AtatIngress for SimcomIngress<'_, INGRESS_BUF_SIZE> {
     fn write_buf(&mut self) -> &mut [u8] {
-        self.0.write_buf()
+        self.inner.write_buf()
     }
 
     fn try_advance(&mut self, commit: usize) -> Result<(), IngressError> {
-        self.0.try_advance(commit)
+        self.inner.try_advance(commit)?;
+        self.check_fault();
+        Ok(())
     }
 
     async fn advance(&mut self, commit: usize) {
-        self.0.advance(commit).await
+        self.inner.advance(commit).await;
+        self.check_fault();
     }
 
     fn clear(&mut self) {
-        self.0.clear()
+        self.inner.clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn garbage_beyond_threshold_triggers_fault_callback() {
+        static FAULTS: AtomicUsize = AtomicUsize::new(0);
+        let buf = static_cell::make_static!([0; 64]);
+        static RES_SLOT: SimcomResponseSlot<64> = SimcomResponseSlot::new();
+        static URC_CHANNEL: SimcomUrcChannel = SimcomUrcChannel::new();
+        let mut ingress =
+            SimcomIngress::new(buf, &RES_SLOT, &URC_CHANNEL).with_fault_callback(16, |fault| {
+                assert_eq!(IngressFault::Desynchronized, fault);
+                FAULTS.fetch_add(1, Ordering::Relaxed);
+            });
+
+        ingress.try_write(b"\r\nOK\r\n").unwrap();
+        assert_eq!(0, FAULTS.load(Ordering::Relaxed));
+
+        ingress.try_write(b"\xFF\x13garbage\x00").unwrap();
+        assert_eq!(0, FAULTS.load(Ordering::Relaxed));
+
+        ingress.try_write(b"\x7Fmore garbage").unwrap();
+        assert_eq!(1, FAULTS.load(Ordering::Relaxed));
+
+        // The ingress is cleared and can process responses again
+        ingress.try_write(b"\r\nOK\r\n").unwrap();
+        assert_eq!(64, ingress.write_buf().len());
     }
 }
@@ -36,8 +36,8 @@ pub use config::{FlowControl, SimcomConfig};
 pub use device::SimcomDevice;
 use device::{URC_CAPACITY, URC_SUBSCRIBERS};
 pub use digester::SimcomDigester;
-pub use error::DriverError;
-pub use ingress::SimcomIngress;
+pub use error::{CmeError, DriverError};
+pub use ingress::{IngressFault, SimcomIngress};
 use serde::{Deserialize, Serialize};
 
 pub use atat;
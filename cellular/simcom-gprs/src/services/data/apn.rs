@@ -1,8 +1,11 @@
+use crate::commands::gprs::PdpType;
+
 #[derive(Clone)]
 pub struct Apn<'a> {
     pub apn: &'a str,
     pub username: &'a str,
     pub password: &'a str,
+    pub(super) pdp_type: PdpType,
 }
 
 impl<'a> Apn<'a> {
@@ -11,8 +14,17 @@ impl<'a> Apn<'a> {
             apn,
             username: "",
             password: "",
+            pdp_type: PdpType::Ipv4,
         }
     }
+
+    /// Request a PDP context of a specific type, which is IPv4 by default
+    ///
+    /// Only the context is of the requested type, sockets still only support IPv4 remote addresses.
+    pub const fn with_pdp_type(mut self, pdp_type: PdpType) -> Self {
+        self.pdp_type = pdp_type;
+        self
+    }
 }
 
 impl<'a> From<&'a str> for Apn<'a> {
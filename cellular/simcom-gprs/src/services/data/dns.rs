@@ -1,11 +1,85 @@
 use atat::asynch::AtatClient;
-use embassy_time::{with_timeout, Duration, Instant};
-use embedded_nal_async::{AddrType, Dns};
+use embassy_time::{Duration, Instant};
+use embedded_nal_async::{AddrType, Dns, IpAddr, Ipv4Addr};
+use heapless::{String, Vec};
 
 use crate::commands::{tcpip::ResolveHostIp, urc::Urc};
 
 use super::{DataService, SocketError};
 
+/// The number of resolved hosts kept in the dns cache
+const DNS_CACHE_SIZE: usize = 4;
+
+struct DnsCacheEntry {
+    host: String<128>,
+    ip: Ipv4Addr,
+    expires_at: Instant,
+}
+
+/// Cache of resolved hosts where the least recently used entry is evicted when full
+pub(super) struct DnsCache {
+    ttl: Option<Duration>,
+    /// The entries ordered from the least to the most recently used
+    entries: Vec<DnsCacheEntry, DNS_CACHE_SIZE>,
+}
+
+impl DnsCache {
+    pub(super) const fn new() -> Self {
+        Self {
+            ttl: None,
+            entries: Vec::new(),
+        }
+    }
+
+    fn get(&mut self, host: &str) -> Option<Ipv4Addr> {
+        let now = Instant::now();
+        self.entries.retain(|entry| entry.expires_at > now);
+
+        let index = self.entries.iter().position(|entry| entry.host == host)?;
+        let entry = self.entries.remove(index);
+        let ip = entry.ip;
+        self.entries.push(entry).ok();
+        Some(ip)
+    }
+
+    fn insert(&mut self, host: &str, ip: Ipv4Addr) {
+        let Some(ttl) = self.ttl else {
+            return;
+        };
+        let Ok(host) = String::try_from(host) else {
+            return;
+        };
+
+        self.entries.retain(|entry| entry.host != host);
+        if self.entries.is_full() {
+            self.entries.remove(0);
+        }
+        self.entries
+            .push(DnsCacheEntry {
+                host,
+                ip,
+                expires_at: Instant::now() + ttl,
+            })
+            .ok();
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl<AtCl: AtatClient> DataService<'_, '_, '_, AtCl> {
+    /// Set how long resolved hosts are cached, or `None` to disable the dns cache.
+    ///
+    /// The cache is disabled by default, and is cleared when the data service is setup,
+    /// as the resolved addresses may change between PDP sessions.
+    pub fn set_dns_cache_ttl(&mut self, ttl: Option<Duration>) {
+        let cache = self.dns_lock.get_mut();
+        cache.ttl = ttl;
+        cache.clear();
+    }
+}
+
 impl<AtCl: AtatClient + 'static> Dns for DataService<'_, '_, '_, AtCl> {
     type Error = SocketError;
 
@@ -13,55 +87,142 @@ impl<AtCl: AtatClient + 'static> Dns for DataService<'_, '_, '_, AtCl> {
         &self,
         host: &str,
         addr_type: AddrType,
-    ) -> Result<embedded_nal_async::IpAddr, Self::Error> {
+    ) -> Result<IpAddr, Self::Error> {
         if addr_type == AddrType::IPv6 {
             return Err(SocketError::UnsupportedIpVersion);
         }
         assert!(addr_type == AddrType::IPv4 || addr_type == AddrType::Either);
 
         // The modem can only handle one dns lookup at a time
-        // TODO: Maybe let the mutex protect the handle instead of having a Mutex<()>
-        let _guard = self.dns_lock.lock().await;
+        // TODO: Maybe let the mutex protect the handle instead of only the dns cache
+        let mut cache = self.dns_lock.lock().await;
+        if let Some(ip) = cache.get(host) {
+            return Ok(IpAddr::V4(ip));
+        }
 
         self.handle.drain_background_urcs();
 
-        let mut urc_subscription = {
-            let mut client = self.handle.client.lock().await;
-            let subscription = self.urc_channel.subscribe().unwrap();
-
-            // Start resolving the host ip
-            client.send(&ResolveHostIp { host }).await?;
-
-            subscription
-        };
-
-        // Wait for the URC reporting the resolved ip
-        let timeout_instant = Instant::now() + Duration::from_secs(20);
-        while let Some(remaining) = timeout_instant.checked_duration_since(Instant::now()) {
-            let urc = with_timeout(remaining, urc_subscription.next_message_pure())
-                .await
-                .map_err(|_| SocketError::DnsTimeout)?;
-            self.handle.drain_background_urcs();
-
-            if let Urc::DnsResult(result) = urc {
-                if let Ok(result) = result {
-                    if result.host == host {
-                        return Ok(result.ip.parse().unwrap());
+        // Start resolving the host ip and wait for the URC reporting the resolved ip
+        let ip = self
+            .handle
+            .send_and_await_urc(
+                self.urc_channel,
+                &ResolveHostIp { host },
+                Duration::from_secs(20),
+                SocketError::DnsTimeout,
+                |urc| match urc {
+                    Urc::DnsResult(Ok(result)) if result.host == host => {
+                        Some(Ok(result.ip.parse::<Ipv4Addr>().unwrap()))
                     }
-                } else {
-                    return Err(SocketError::DnsError);
-                }
-            }
-        }
+                    Urc::DnsResult(Err(_)) => Some(Err(SocketError::DnsError)),
+                    _ => None,
+                },
+            )
+            .await??;
 
-        Err(SocketError::DnsTimeout)
+        cache.insert(host, ip);
+        Ok(IpAddr::V4(ip))
     }
 
     async fn get_host_by_address(
         &self,
-        _addr: embedded_nal_async::IpAddr,
+        _addr: IpAddr,
         _result: &mut [u8],
     ) -> Result<usize, Self::Error> {
         unimplemented!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use embassy_time::Timer;
+
+    use crate::services::device_mock::{respond, setup_atat};
+
+    use super::*;
+
+    fn lookup(host: &str, ip: &str) -> (std::vec::Vec<u8>, std::vec::Vec<u8>) {
+        (
+            format!("AT+CDNSGIP=\"{host}\"\r").into_bytes(),
+            format!("\r\nOK\r\n\r\n+CDNSGIP: 1,\"{host}\",\"{ip}\"\r\n").into_bytes(),
+        )
+    }
+
+    #[tokio::test]
+    async fn resolved_host_is_cached_until_expired() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut data = DataService::new(&device.handle, device.urc_channel);
+        data.set_dns_cache_ttl(Some(Duration::from_millis(200)));
+
+        let (cmd, response) = lookup("utiliread.dk", "1.2.3.4");
+
+        let exchange = [(cmd.as_slice(), response.as_slice())];
+        let (ip, _) = tokio::join!(
+            data.get_host_by_name("utiliread.dk", AddrType::IPv4),
+            respond(&mut ingress, &mut serial, &exchange)
+        );
+        assert_eq!(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)), ip.unwrap());
+
+        // Served from the cache without sending any command
+        let ip = data.get_host_by_name("utiliread.dk", AddrType::IPv4).await;
+        assert_eq!(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)), ip.unwrap());
+        assert!(serial.try_next_message_pure().is_none());
+
+        Timer::after_millis(250).await;
+
+        let (cmd, response) = lookup("utiliread.dk", "5.6.7.8");
+
+        let exchange = [(cmd.as_slice(), response.as_slice())];
+        let (ip, _) = tokio::join!(
+            data.get_host_by_name("utiliread.dk", AddrType::IPv4),
+            respond(&mut ingress, &mut serial, &exchange)
+        );
+        assert_eq!(IpAddr::V4(Ipv4Addr::new(5, 6, 7, 8)), ip.unwrap());
+    }
+
+    #[tokio::test]
+    async fn least_recently_used_host_is_evicted() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut data = DataService::new(&device.handle, device.urc_channel);
+        data.set_dns_cache_ttl(Some(Duration::from_secs(60)));
+
+        for i in 0..DNS_CACHE_SIZE as u8 {
+            let host = format!("host{i}");
+            let (cmd, response) = lookup(&host, &format!("10.0.0.{i}"));
+            let exchange = [(cmd.as_slice(), response.as_slice())];
+            let (ip, _) = tokio::join!(
+                data.get_host_by_name(&host, AddrType::IPv4),
+                respond(&mut ingress, &mut serial, &exchange)
+            );
+            ip.unwrap();
+        }
+
+        // Use host0 such that host1 becomes the least recently used
+        data.get_host_by_name("host0", AddrType::IPv4)
+            .await
+            .unwrap();
+
+        let (cmd, response) = lookup("other", "10.0.0.99");
+
+        let exchange = [(cmd.as_slice(), response.as_slice())];
+        let (ip, _) = tokio::join!(
+            data.get_host_by_name("other", AddrType::IPv4),
+            respond(&mut ingress, &mut serial, &exchange)
+        );
+        ip.unwrap();
+
+        data.get_host_by_name("host0", AddrType::IPv4)
+            .await
+            .unwrap();
+        assert!(serial.try_next_message_pure().is_none());
+
+        let (cmd, response) = lookup("host1", "10.0.0.1");
+
+        let exchange = [(cmd.as_slice(), response.as_slice())];
+        let (ip, _) = tokio::join!(
+            data.get_host_by_name("host1", AddrType::IPv4),
+            respond(&mut ingress, &mut serial, &exchange)
+        );
+        assert_eq!(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), ip.unwrap());
+    }
+}
@@ -1,30 +1,45 @@
 mod apn;
 mod dns;
 mod tcp;
+#[cfg(feature = "tls")]
+mod tls;
+mod udp;
 
 use atat::{asynch::AtatClient, AtatCmd};
 use core::{str::from_utf8, sync::atomic::Ordering};
 use embassy_sync::{blocking_mutex::raw::NoopRawMutex, mutex::Mutex};
+use embassy_time::{with_timeout, Duration, Instant, Timer};
 use embedded_io::ErrorKind;
 use embedded_nal_async::Ipv4Addr;
+use heapless_bytes::Bytes;
 
 use crate::{
     commands::{
+        gprs::{GetPdpAddress, PdpType, SetPDPContextDefinition},
         gsm::SetMobileEquipmentError,
         tcpip::{
-            BringUpWireless, ClientState, CloseConnection, ConfigureDomainNameServer,
-            DeactivateGprsPdpContext, GetConnectionStatus, GetLocalIP, MultiIpValue,
-            SelectDataTransmittingMode, SetManualRxGetMode, StartMultiIpConnection,
-            StartTaskAndSetApn,
+            BringUpWireless, ClientState, ConfigureDomainNameServer, DeactivateGprsPdpContext,
+            GetAllConnectionStatus, GetLocalIP, MultiIpValue, SelectDataTransmittingMode,
+            SetManualRxGetMode, StartMultiIpConnection, StartTaskAndSetApn,
         },
+        urc::Urc,
     },
     device::{Handle, SOCKET_STATE_DROPPED, SOCKET_STATE_UNUSED, SOCKET_STATE_USED},
-    DriverError, SimcomConfig, SimcomDevice, SimcomUrcChannel,
+    ContextId, DriverError, SimcomConfig, SimcomDevice, SimcomUrcChannel, MAX_SOCKETS,
 };
 
+use dns::DnsCache;
+
 pub use apn::Apn;
+pub use tcp::{Peer, ReconnectEvent, ReconnectingSocket, RetryPolicy, TcpSocket};
+pub use udp::UdpSocket;
+
+use super::network::{NetworkError, SetupStep};
 
-use super::network::NetworkError;
+/// The PDP context activated by AT+CSTT and AT+CIICR, which is the only one used by the TCP/IP stack
+///
+/// Selecting another context is deliberately not supported, see [`SimcomDevice::data()`].
+const CONTEXT_ID: ContextId = ContextId(1);
 
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -39,18 +54,31 @@ pub enum SocketError {
     Closed,
     UnableToRead,
     ReadTimeout,
+    BufferFull,
+    DatagramTooLarge,
     UnableToWrite,
     WriteTimeout,
+    PoweredDown,
+    /// The modem rejected the request to use TLS
+    #[cfg(feature = "tls")]
+    TlsNotSupported,
 }
 
 impl embedded_io::Error for SocketError {
     fn kind(&self) -> ErrorKind {
         match &self {
             SocketError::UnsupportedIpVersion => ErrorKind::Unsupported,
+            #[cfg(feature = "tls")]
+            SocketError::TlsNotSupported => ErrorKind::Unsupported,
             SocketError::DnsTimeout => ErrorKind::TimedOut,
             SocketError::UnableToConnect => ErrorKind::ConnectionRefused,
             SocketError::ConnectTimeout => ErrorKind::TimedOut,
             SocketError::Closed => ErrorKind::ConnectionAborted,
+            SocketError::ReadTimeout => ErrorKind::TimedOut,
+            SocketError::WriteTimeout => ErrorKind::TimedOut,
+            SocketError::NoAvailableSockets => ErrorKind::OutOfMemory,
+            SocketError::BufferFull => ErrorKind::OutOfMemory,
+            SocketError::DatagramTooLarge => ErrorKind::InvalidInput,
             _ => ErrorKind::Other,
         }
     }
@@ -65,13 +93,23 @@ impl From<atat::Error> for SocketError {
 pub struct DataService<'buf, 'dev, 'sub, AtCl: AtatClient> {
     handle: &'dev Handle<'sub, AtCl>,
     urc_channel: &'buf SimcomUrcChannel,
-    dns_lock: Mutex<NoopRawMutex, ()>,
+    dns_lock: Mutex<NoopRawMutex, DnsCache>,
     pub local_ip: Option<Ipv4Addr>,
 }
 
 impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static, Config: SimcomConfig>
     SimcomDevice<'buf, 'sub, AtCl, Config>
 {
+    /// Bring up the GPRS connection and take the data service
+    ///
+    /// The TCP/IP stack of the modem (AT+CSTT, AT+CIICR and AT+CIFSR) only supports a single PDP context,
+    /// and AT+CIPSHUT during setup deactivates it regardless of the APN.
+    /// Only one data service can therefore exist at a time, and `DriverError::AlreadyTaken` is returned otherwise.
+    ///
+    /// Multiple PDP contexts, e.g. a private APN next to the public internet, are not supported by this service.
+    /// The context is not selectable, as sockets would keep using the context of the TCP/IP stack anyway.
+    /// The bearer profiles in [`crate::commands::bearer`] (AT+SAPBR) have their own APN,
+    /// but they are only used by the built-in HTTP and FTP applications.
     pub async fn data(
         &'dev self,
         apn: Apn<'_>,
@@ -82,7 +120,7 @@ impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static, Config: SimcomConfig>
             .is_ok()
         {
             let mut service = DataService::new(&self.handle, self.urc_channel);
-            match service.setup(apn).await {
+            match service.setup::<Config>(apn).await {
                 Ok(_) => Ok(service),
                 Err(e) => {
                     self.data_service_taken.store(false, Ordering::Relaxed);
@@ -100,57 +138,116 @@ impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> DataService<'buf, 'dev, 'sub,
         Self {
             handle,
             urc_channel,
-            dns_lock: Mutex::new(()),
+            dns_lock: Mutex::new(DnsCache::new()),
             local_ip: None,
         }
     }
 
-    async fn setup(&mut self, apn: Apn<'_>) -> Result<(), NetworkError> {
+    async fn setup<Config: SimcomConfig>(&mut self, apn: Apn<'_>) -> Result<(), NetworkError> {
         // According to the sim800 tcpip application note one should use the command group:
         // AT+CSTT, AT+CIICR and AT+CIFSR to start the task and activate the wireless connection.
         // See §2.1.1 in https://www.waveshare.com/w/upload/6/65/SIM800_Series_TCPIP_Application_Note_V1.02.pdf
+        self.handle.ensure_powered()?;
 
         // AT+CIPSHUT
-        self.send(&DeactivateGprsPdpContext).await?;
+        self.send_step(
+            SetupStep::DeactivateGprsPdpContext,
+            &DeactivateGprsPdpContext,
+        )
+        .await?;
+
+        // The resolved addresses may change when a new PDP context is activated
+        self.dns_lock.get_mut().clear();
 
         // AT+CIPRXGET
-        self.send(&SetManualRxGetMode).await?;
+        self.send_step(SetupStep::SetManualRxGetMode, &SetManualRxGetMode)
+            .await?;
 
         // AT+CIPMUX
-        self.send(&StartMultiIpConnection {
-            n: MultiIpValue::MultiIpConnection,
-        })
+        self.send_step(
+            SetupStep::StartMultiIpConnection,
+            &StartMultiIpConnection {
+                n: MultiIpValue::MultiIpConnection,
+            },
+        )
         .await?;
 
+        // AT+CGDCONT
+        // The context defaults to IPv4 and is only defined explicitly if another type is requested
+        if apn.pdp_type != PdpType::Ipv4 {
+            self.send_step(
+                SetupStep::DefinePdpContext,
+                &SetPDPContextDefinition {
+                    cid: CONTEXT_ID,
+                    pdp_type: apn.pdp_type,
+                    apn: apn.apn,
+                },
+            )
+            .await?;
+        }
+
         // AT+CSTT
         // This implicitly activates the pdp context
         // so we should not manually call AT+CGACT
         // See git rev 3aa2787 for a version that connected using both.
         // It worked with tdc and telia, but not with onomondo.
-        self.send(&StartTaskAndSetApn {
-            apn: apn.apn,
-            username: apn.username,
-            password: apn.password,
-        })
+        self.send_step(
+            SetupStep::StartTaskAndSetApn,
+            &StartTaskAndSetApn {
+                apn: apn.apn,
+                username: apn.username,
+                password: apn.password,
+            },
+        )
         .await?;
 
         // AT+CIICR
-        self.send(&BringUpWireless).await?;
+        self.send_step(SetupStep::BringUpWireless, &BringUpWireless)
+            .await?;
 
         // AT+CMEE
-        self.send(&SetMobileEquipmentError {
-            value: crate::commands::gsm::MobileEquipmentError::EnableVerbose,
-        })
+        self.send_step(
+            SetupStep::SetMobileEquipmentError,
+            &SetMobileEquipmentError {
+                value: crate::commands::gsm::MobileEquipmentError::EnableVerbose,
+            },
+        )
         .await?;
 
         // AT+CIFSR
-        let ip = self.send(&GetLocalIP).await?.ip;
-        self.local_ip = Some(from_utf8(ip.as_slice()).unwrap().parse().unwrap());
+        // This is also required when the local IP is read from the PDP context,
+        // as it moves the modem from "IP GPRSACT" to "IP STATUS" where connections can be started
+        let ip = self
+            .get_local_ip(Config::LOCAL_IP_ATTEMPTS)
+            .await
+            .map_err(|e| step_failed(SetupStep::GetLocalIP, e))?;
+
+        if Config::LOCAL_IP_FROM_PDP_ADDRESS {
+            // AT+CGPADDR
+            let response = self
+                .send(&GetPdpAddress { cid: CONTEXT_ID })
+                .await
+                .map_err(|e| step_failed(SetupStep::GetPdpAddress, e))?;
+            let ip = response
+                .address
+                .as_str()
+                .parse()
+                .map_err(|_| step_failed(SetupStep::GetPdpAddress, atat::Error::Parse))?;
+            self.local_ip = Some(ip);
+        } else {
+            self.local_ip = Some(from_utf8(ip.as_slice()).unwrap().parse().unwrap());
+        }
 
         // AT+CIPSTATUS
-        for (id, state) in self.handle.socket_state.iter().enumerate() {
-            let response = self.send(&GetConnectionStatus { id }).await?;
-            let new_state = match response.state {
+        let client_states = if self.handle.socket_state.is_empty() {
+            [ClientState::Initial; MAX_SOCKETS]
+        } else {
+            self.get_all_connection_status()
+                .await
+                .map_err(|e| step_failed(SetupStep::GetConnectionStatus, e))?
+        };
+        for (state, client_state) in self.handle.socket_state.iter().zip(client_states) {
+            let new_state = match client_state {
                 ClientState::Initial => SOCKET_STATE_UNUSED,
                 ClientState::Closed => SOCKET_STATE_UNUSED,
                 ClientState::Connecting => SOCKET_STATE_USED,
@@ -165,53 +262,391 @@ impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> DataService<'buf, 'dev, 'sub,
         // Enter quick send mode so that we get an URC when written data is buffered
         // instead of when it is received by the server
         // This changes the default "SEND OK" response into "DATA ACCEPT"
-        self.send(&SelectDataTransmittingMode {
-            mode: crate::commands::tcpip::DataTransmittingMode::QuickSendMode,
-        })
+        self.send_step(
+            SetupStep::SelectDataTransmittingMode,
+            &SelectDataTransmittingMode {
+                mode: crate::commands::tcpip::DataTransmittingMode::QuickSendMode,
+            },
+        )
         .await?;
 
         // AT+CDNSCFG
-        self.send(&ConfigureDomainNameServer {
-            pri_dns: "1.1.1.1",
-            sec_dns: Some("1.0.0.1"),
-        })
-        .await?;
+        if Config::CONFIGURE_DNS {
+            self.send_step(
+                SetupStep::ConfigureDomainNameServer,
+                &ConfigureDomainNameServer {
+                    pri_dns: "1.1.1.1",
+                    sec_dns: Some("1.0.0.1"),
+                },
+            )
+            .await?;
+        }
 
         Ok(())
     }
 
+    /// Get the local IP, retrying if the modem replies ERROR
+    ///
+    /// AT+CIFSR may fail right after AT+CIICR if the network is slow to assign an address.
+    async fn get_local_ip(&mut self, attempts: u8) -> Result<Bytes<15>, atat::Error> {
+        let mut attempt = 1;
+        loop {
+            match self.send(&GetLocalIP).await {
+                Ok(response) => return Ok(response.ip),
+                Err(atat::Error::Error) if attempt < attempts => {
+                    warn!("Unable to get local IP, retrying");
+                }
+                Err(e) => return Err(e),
+            }
+
+            attempt += 1;
+            Timer::after_millis(500).await;
+        }
+    }
+
+    /// Get the state of all connections using a single AT+CIPSTATUS
+    ///
+    /// The modem replies OK before the status table, so the rows are received as URCs.
+    async fn get_all_connection_status(&self) -> Result<[ClientState; MAX_SOCKETS], atat::Error> {
+        let _request = self.handle.urc_request.lock().await;
+        let mut urc_subscription = {
+            let mut client = self.handle.client.lock().await;
+            let subscription = self.urc_channel.subscribe().unwrap();
+
+            client.send(&GetAllConnectionStatus).await?;
+
+            subscription
+        };
+
+        let mut states = [ClientState::Initial; MAX_SOCKETS];
+        let mut received = 0;
+        let timeout_instant =
+            Instant::now() + Duration::from_millis(GetAllConnectionStatus::MAX_TIMEOUT_MS as u64);
+        while received < self.handle.socket_state.len() {
+            let remaining = timeout_instant
+                .checked_duration_since(Instant::now())
+                .ok_or(atat::Error::Timeout)?;
+            let urc = with_timeout(remaining, urc_subscription.next_message_pure())
+                .await
+                .map_err(|_| atat::Error::Timeout)?;
+            self.handle.drain_background_urcs();
+
+            if let Urc::ClientStatus(status) = urc {
+                if let Some(state) = states.get_mut(status.id) {
+                    *state = status.state;
+                    received += 1;
+                }
+            }
+        }
+
+        Ok(states)
+    }
+
+    async fn send_step<CMD: AtatCmd>(
+        &mut self,
+        step: SetupStep,
+        cmd: &CMD,
+    ) -> Result<CMD::Response, NetworkError> {
+        self.send(cmd).await.map_err(|e| step_failed(step, e))
+    }
+
     async fn send<CMD: AtatCmd>(&mut self, cmd: &CMD) -> Result<CMD::Response, atat::Error> {
         let mut client = self.handle.client.lock().await;
 
         client.send(cmd).await
     }
+}
 
-    async fn close_dropped_sockets(&self) {
-        for (id, state) in self.handle.socket_state.iter().enumerate() {
-            if state.load(Ordering::Relaxed) == SOCKET_STATE_DROPPED {
-                let mut client = self.handle.client.lock().await;
-
-                // The close connection command does not return anything.
-                // The actual transition from USED to UNUSED happens in URC handling,
-                // as a "<id>, CLOSE OK" URC is sent when the connection is closed.
-                match client.send(&CloseConnection { id }).await {
-                    Ok(_) => {}
-                    Err(atat::Error::CmeError(e)) if e == 3.into() || e == 100.into() => {
-                        // CME Error seems to be returned if the connection is already closed
-                        // Verify that it is actually the case
-                        if let Ok(status) = client.send(&GetConnectionStatus { id }).await {
-                            if status.state == ClientState::Closed {
-                                warn!("[{}] Socket already closed", id);
-                                state.store(SOCKET_STATE_UNUSED, Ordering::Release);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        // If the close is not sent, we will simply retry later when `close_dropped_sockets()` is called again.
-                        error!("[{}] Close request failed with error {}", id, e);
-                    }
-                }
+fn step_failed(step: SetupStep, error: atat::Error) -> NetworkError {
+    error!("{} failed: {:?}", step.command(), error);
+    NetworkError::Step(step, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::assert_matches::assert_matches;
+
+    use crate::services::device_mock::{respond, setup_atat, Config, ResetPin};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn setup_failure_reports_step() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut data = DataService::new(&device.handle, device.urc_channel);
+
+        let setup = data.setup::<Config>(Apn::new("internet"));
+        let responses = respond(
+            &mut ingress,
+            &mut serial,
+            &[
+                (b"AT+CIPSHUT\r", b"\r\nSHUT OK\r\n"),
+                (b"AT+CIPRXGET=1\r", b"\r\nOK\r\n"),
+                (b"AT+CIPMUX=1\r", b"\r\nOK\r\n"),
+                (b"AT+CSTT=\"internet\",\"\",\"\"\r", b"\r\nOK\r\n"),
+                (b"AT+CIICR\r", b"\r\nERROR\r\n"),
+            ],
+        );
+
+        let (setup, _) = tokio::join!(setup, responses);
+
+        assert_matches!(
+            setup,
+            Err(NetworkError::Step(
+                SetupStep::BringUpWireless,
+                atat::Error::Error
+            ))
+        );
+        assert_eq!("CIICR", SetupStep::BringUpWireless.command());
+    }
+
+    #[tokio::test]
+    async fn setup_defines_dual_stack_pdp_context() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut data = DataService::new(&device.handle, device.urc_channel);
+
+        let setup = data.setup::<Config>(Apn::new("apn").with_pdp_type(PdpType::Ipv4v6));
+        let responses = respond(
+            &mut ingress,
+            &mut serial,
+            &[
+                (b"AT+CIPSHUT\r", b"\r\nSHUT OK\r\n"),
+                (b"AT+CIPRXGET=1\r", b"\r\nOK\r\n"),
+                (b"AT+CIPMUX=1\r", b"\r\nOK\r\n"),
+                (b"AT+CGDCONT=1,\"IPV4V6\",\"apn\"\r", b"\r\nOK\r\n"),
+                (b"AT+CSTT=\"apn\",\"\",\"\"\r", b"\r\nERROR\r\n"),
+            ],
+        );
+
+        let (setup, _) = tokio::join!(setup, responses);
+
+        assert_matches!(
+            setup,
+            Err(NetworkError::Step(
+                SetupStep::StartTaskAndSetApn,
+                atat::Error::Error
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn can_setup_with_local_ip_retry() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut data = DataService::new(&device.handle, device.urc_channel);
+
+        let setup = data.setup::<Config>(Apn::new("internet"));
+        let responses = respond(
+            &mut ingress,
+            &mut serial,
+            &[
+                (b"AT+CIPSHUT\r", b"\r\nSHUT OK\r\n"),
+                (b"AT+CIPRXGET=1\r", b"\r\nOK\r\n"),
+                (b"AT+CIPMUX=1\r", b"\r\nOK\r\n"),
+                (b"AT+CSTT=\"internet\",\"\",\"\"\r", b"\r\nOK\r\n"),
+                (b"AT+CIICR\r", b"\r\nOK\r\n"),
+                (b"AT+CMEE=2\r", b"\r\nOK\r\n"),
+                (b"AT+CIFSR\r", b"\r\nERROR\r\n"),
+                (b"AT+CIFSR\r", b"\r\n10.0.109.44\r\n"),
+                (b"AT+CIPQSEND=1\r", b"\r\nOK\r\n"),
+                (b"AT+CDNSCFG=\"1.1.1.1\",\"1.0.0.1\"\r", b"\r\nOK\r\n"),
+            ],
+        );
+
+        let (setup, _) = tokio::join!(setup, responses);
+
+        setup.unwrap();
+        assert_eq!(Some(Ipv4Addr::new(10, 0, 109, 44)), data.local_ip);
+    }
+
+    #[tokio::test]
+    async fn can_setup_without_dns_config() {
+        struct NoDnsConfig;
+
+        impl SimcomConfig for NoDnsConfig {
+            type ResetPin = ResetPin;
+
+            const CONFIGURE_DNS: bool = false;
+
+            fn reset_pin(&mut self) -> &mut Self::ResetPin {
+                unreachable!()
+            }
+        }
+
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut data = DataService::new(&device.handle, device.urc_channel);
+
+        let setup = data.setup::<NoDnsConfig>(Apn::new("internet"));
+        let responses = respond(
+            &mut ingress,
+            &mut serial,
+            &[
+                (b"AT+CIPSHUT\r", b"\r\nSHUT OK\r\n"),
+                (b"AT+CIPRXGET=1\r", b"\r\nOK\r\n"),
+                (b"AT+CIPMUX=1\r", b"\r\nOK\r\n"),
+                (b"AT+CSTT=\"internet\",\"\",\"\"\r", b"\r\nOK\r\n"),
+                (b"AT+CIICR\r", b"\r\nOK\r\n"),
+                (b"AT+CMEE=2\r", b"\r\nOK\r\n"),
+                (b"AT+CIFSR\r", b"\r\n10.0.109.44\r\n"),
+                (b"AT+CIPQSEND=1\r", b"\r\nOK\r\n"),
+            ],
+        );
+
+        let (setup, _) = tokio::join!(setup, responses);
+
+        setup.unwrap();
+        assert!(serial.try_next_message_pure().is_none());
+    }
+
+    #[tokio::test]
+    async fn can_setup_with_local_ip_from_pdp_address() {
+        struct PdpAddressConfig;
+
+        impl SimcomConfig for PdpAddressConfig {
+            type ResetPin = ResetPin;
+
+            const LOCAL_IP_FROM_PDP_ADDRESS: bool = true;
+
+            fn reset_pin(&mut self) -> &mut Self::ResetPin {
+                unreachable!()
             }
         }
+
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut data = DataService::new(&device.handle, device.urc_channel);
+
+        let setup = data.setup::<PdpAddressConfig>(Apn::new("internet"));
+        let responses = respond(
+            &mut ingress,
+            &mut serial,
+            &[
+                (b"AT+CIPSHUT\r", b"\r\nSHUT OK\r\n"),
+                (b"AT+CIPRXGET=1\r", b"\r\nOK\r\n"),
+                (b"AT+CIPMUX=1\r", b"\r\nOK\r\n"),
+                (b"AT+CSTT=\"internet\",\"\",\"\"\r", b"\r\nOK\r\n"),
+                (b"AT+CIICR\r", b"\r\nOK\r\n"),
+                (b"AT+CMEE=2\r", b"\r\nOK\r\n"),
+                (b"AT+CIFSR\r", b"\r\n10.0.109.44\r\n"),
+                (
+                    b"AT+CGPADDR=1\r",
+                    b"\r\n+CGPADDR: 1,\"10.0.109.45\"\r\n\r\nOK\r\n",
+                ),
+                (b"AT+CIPQSEND=1\r", b"\r\nOK\r\n"),
+                (b"AT+CDNSCFG=\"1.1.1.1\",\"1.0.0.1\"\r", b"\r\nOK\r\n"),
+            ],
+        );
+
+        let (setup, _) = tokio::join!(setup, responses);
+
+        setup.unwrap();
+        assert_eq!(Some(Ipv4Addr::new(10, 0, 109, 45)), data.local_ip);
+    }
+
+    #[tokio::test]
+    async fn setup_fails_when_pdp_address_is_invalid() {
+        struct PdpAddressConfig;
+
+        impl SimcomConfig for PdpAddressConfig {
+            type ResetPin = ResetPin;
+
+            const LOCAL_IP_FROM_PDP_ADDRESS: bool = true;
+
+            fn reset_pin(&mut self) -> &mut Self::ResetPin {
+                unreachable!()
+            }
+        }
+
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut data = DataService::new(&device.handle, device.urc_channel);
+
+        let setup = data.setup::<PdpAddressConfig>(Apn::new("internet"));
+        let responses = respond(
+            &mut ingress,
+            &mut serial,
+            &[
+                (b"AT+CIPSHUT\r", b"\r\nSHUT OK\r\n"),
+                (b"AT+CIPRXGET=1\r", b"\r\nOK\r\n"),
+                (b"AT+CIPMUX=1\r", b"\r\nOK\r\n"),
+                (b"AT+CSTT=\"internet\",\"\",\"\"\r", b"\r\nOK\r\n"),
+                (b"AT+CIICR\r", b"\r\nOK\r\n"),
+                (b"AT+CMEE=2\r", b"\r\nOK\r\n"),
+                (b"AT+CIFSR\r", b"\r\n10.0.109.44\r\n"),
+                (b"AT+CGPADDR=1\r", b"\r\n+CGPADDR: 1,\"\"\r\n\r\nOK\r\n"),
+            ],
+        );
+
+        let (setup, _) = tokio::join!(setup, responses);
+
+        assert_matches!(
+            setup,
+            Err(NetworkError::Step(
+                SetupStep::GetPdpAddress,
+                atat::Error::Parse
+            ))
+        );
+        assert_eq!(None, data.local_ip);
+    }
+
+    #[cfg(not(feature = "sim900"))]
+    #[tokio::test]
+    async fn can_setup_with_connection_status_table() {
+        use crate::device::{SocketState, SOCKET_STATE_UNKNOWN};
+
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        for _ in 0..MAX_SOCKETS {
+            device
+                .handle
+                .socket_state
+                .push(SocketState::new(SOCKET_STATE_UNKNOWN))
+                .unwrap();
+        }
+        let mut data = DataService::new(&device.handle, device.urc_channel);
+
+        let setup = data.setup::<Config>(Apn::new("internet"));
+        let responses = respond(
+            &mut ingress,
+            &mut serial,
+            &[
+                (b"AT+CIPSHUT\r", b"\r\nSHUT OK\r\n"),
+                (b"AT+CIPRXGET=1\r", b"\r\nOK\r\n"),
+                (b"AT+CIPMUX=1\r", b"\r\nOK\r\n"),
+                (b"AT+CSTT=\"internet\",\"\",\"\"\r", b"\r\nOK\r\n"),
+                (b"AT+CIICR\r", b"\r\nOK\r\n"),
+                (b"AT+CMEE=2\r", b"\r\nOK\r\n"),
+                (b"AT+CIFSR\r", b"\r\n10.0.109.44\r\n"),
+                (
+                    b"AT+CIPSTATUS\r",
+                    b"\r\nOK\r\n\r\nSTATE: IP STATUS\r\n\
+                    \r\nC: 0,,\"\",\"\",\"\",\"INITIAL\"\r\n\
+                    \r\nC: 1,0,\"TCP\",\"1.2.3.4\",\"80\",\"CONNECTED\"\r\n\
+                    \r\nC: 2,,\"\",\"\",\"\",\"INITIAL\"\r\n\
+                    \r\nC: 3,0,\"TCP\",\"1.2.3.4\",\"80\",\"CLOSED\"\r\n\
+                    \r\nC: 4,,\"\",\"\",\"\",\"INITIAL\"\r\n\
+                    \r\nC: 5,,\"\",\"\",\"\",\"INITIAL\"\r\n",
+                ),
+                (b"AT+CIPQSEND=1\r", b"\r\nOK\r\n"),
+                (b"AT+CDNSCFG=\"1.1.1.1\",\"1.0.0.1\"\r", b"\r\nOK\r\n"),
+            ],
+        );
+
+        let (setup, _) = tokio::join!(setup, responses);
+
+        setup.unwrap();
+        let states: Vec<u8> = device
+            .handle
+            .socket_state
+            .iter()
+            .map(|state| state.load(Ordering::Relaxed))
+            .collect();
+        assert_eq!(
+            vec![
+                SOCKET_STATE_UNUSED,
+                SOCKET_STATE_USED,
+                SOCKET_STATE_UNUSED,
+                SOCKET_STATE_UNUSED,
+                SOCKET_STATE_UNUSED,
+                SOCKET_STATE_UNUSED
+            ],
+            states
+        );
     }
 }
@@ -1,25 +1,33 @@
+use alloc::vec::Vec;
 use core::sync::atomic::Ordering;
 
 use atat::{asynch::AtatClient, AtatCmd};
 use core::fmt::Write as _;
 use embassy_time::{with_timeout, Duration, Instant, Timer};
 use embedded_io_async::{Read, Write};
-use embedded_nal_async::{SocketAddr, TcpConnect};
+use embedded_nal_async::{AddrType, Dns, SocketAddr, TcpConnect};
 use heapless::String;
 
 use crate::{
     commands::{
         tcpip::{
-            QueryPreviousConnectionDataTransmittingState, ReadData, SendData, StartConnection,
-            WriteData, MAX_WRITE,
+            QueryPreviousConnectionDataTransmittingState, QuerySendBufferSize, ReadData, SendData,
+            StartConnection, WriteData, MAX_WRITE,
         },
         urc::Urc,
     },
     device::Handle,
-    SimcomUrcChannel,
+    SimcomUrcChannel, SimcomUrcSubscription,
 };
 
-use super::{DataService, SocketError, SOCKET_STATE_DROPPED, SOCKET_STATE_USED};
+use super::{
+    DataService, SocketError, SOCKET_STATE_DROPPED, SOCKET_STATE_UNUSED, SOCKET_STATE_USED,
+};
+
+const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+const SEND_BUFFER_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const ACK_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
 impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> TcpConnect
     for DataService<'buf, 'dev, 'sub, AtCl>
@@ -32,10 +40,14 @@ impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> TcpConnect
         &'a self,
         remote: SocketAddr,
     ) -> Result<Self::Connection<'a>, Self::Error> {
+        let SocketAddr::V4(remote) = remote else {
+            return Err(SocketError::UnsupportedIpVersion);
+        };
+
         self.handle.drain_background_urcs();
 
         // Close any sockets that have been dropped
-        self.close_dropped_sockets().await;
+        self.handle.close_dropped_sockets().await;
 
         let mut socket = TcpSocket::try_new(self.handle, self.urc_channel)?;
         info!("[{}] Socket created", socket.id);
@@ -51,12 +63,54 @@ impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> TcpConnect
     }
 }
 
+impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> DataService<'buf, 'dev, 'sub, AtCl> {
+    /// Resolve and connect to a host, retrying up to `retries` times if the connection fails
+    ///
+    /// Dropped sockets are closed and `backoff` is awaited between attempts.
+    /// The error from the last attempt is returned if all attempts fail.
+    pub async fn connect_with_retry(
+        &self,
+        host: &str,
+        port: u16,
+        retries: u8,
+        backoff: Duration,
+    ) -> Result<TcpSocket<'buf, 'dev, 'sub, AtCl>, SocketError> {
+        let mut attempt = 0;
+        loop {
+            let result = match self.get_host_by_name(host, AddrType::IPv4).await {
+                Ok(ip) => self.connect(SocketAddr::new(ip, port)).await,
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(socket) => return Ok(socket),
+                Err(e) if attempt < retries => {
+                    warn!("Unable to connect, retrying");
+                    debug!("Connect attempt {} failed with error {:?}", attempt + 1, e);
+                }
+                Err(e) => return Err(e),
+            }
+
+            attempt += 1;
+
+            // The client is not locked while waiting for the next attempt
+            self.handle.close_dropped_sockets().await;
+            Timer::after(backoff).await;
+        }
+    }
+}
+
 pub struct TcpSocket<'buf, 'dev, 'sub, AtCl: AtatClient> {
-    id: usize,
-    handle: &'dev Handle<'sub, AtCl>,
-    urc_channel: &'buf SimcomUrcChannel,
+    pub(super) id: usize,
+    pub(super) handle: &'dev Handle<'sub, AtCl>,
+    pub(super) urc_channel: &'buf SimcomUrcChannel,
     write_cooldown_timer: Option<Timer>,
     last_nacklen_before_write: usize,
+    pending_len: usize,
+    read_timeout: Option<Duration>,
+    write_timeout: Option<Duration>,
+    last_probe_acklen: Option<usize>,
+    received_since_probe: bool,
 }
 
 impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> TcpSocket<'buf, 'dev, 'sub, AtCl> {
@@ -71,29 +125,42 @@ impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> TcpSocket<'buf, 'dev, 'sub, A
             urc_channel,
             write_cooldown_timer: None,
             last_nacklen_before_write: 0,
+            pending_len: 0,
+            read_timeout: Some(DEFAULT_READ_TIMEOUT),
+            write_timeout: Some(DEFAULT_WRITE_TIMEOUT),
+            last_probe_acklen: None,
+            received_since_probe: false,
         })
     }
 
-    async fn connect(&mut self, ip: &str, port: &str) -> Result<(), SocketError> {
-        self.handle.drain_background_urcs();
-
-        let mut urc_subscription = {
-            let mut client = self.handle.client.lock().await;
-            let urc_subscription = self.urc_channel.subscribe().unwrap();
+    /// Set the timeout for the modem to respond to a read request, which is 10 seconds by default
+    ///
+    /// The in-flight request is abandoned and `SocketError::ReadTimeout` is returned when the timeout expires.
+    /// With no timeout, only the timeout of the `AT+CIPRXGET` command applies.
+    pub fn with_read_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
 
-            client
-                .send(&StartConnection {
-                    id: self.id,
-                    mode: "TCP",
-                    ip,
-                    port,
-                })
-                .await
-                .map_err(|_| SocketError::UnableToConnect)?;
+    /// Set the timeout for the modem to make progress on a write, which is 30 seconds by default
+    ///
+    /// `SocketError::WriteTimeout` is returned if the send buffer stays full or no bytes are accepted
+    /// before the timeout expires. With no timeout, a write waits until the whole buffer is accepted.
+    pub fn with_write_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.write_timeout = timeout;
+        self
+    }
 
-            urc_subscription
-        };
+    async fn connect(&mut self, ip: &str, port: &str) -> Result<(), SocketError> {
+        let urc_subscription = self.start_connection("TCP", ip, port).await?;
+        self.wait_for_connect(urc_subscription).await
+    }
 
+    /// Wait for the URC reporting the outcome of a connection request
+    pub(super) async fn wait_for_connect(
+        &mut self,
+        mut urc_subscription: SimcomUrcSubscription<'buf>,
+    ) -> Result<(), SocketError> {
         let timeout_instant =
             Instant::now() + Duration::from_millis(StartConnection::MAX_TIMEOUT_MS as u64);
         while let Some(timeout) = timeout_instant.checked_duration_since(Instant::now()) {
@@ -114,7 +181,32 @@ impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> TcpSocket<'buf, 'dev, 'sub, A
         Err(SocketError::ConnectTimeout)
     }
 
-    fn drain_background_urcs_and_ensure_in_use(&self) -> Result<(), SocketError> {
+    /// Send the connection request, returning a subscription for the URCs that follow
+    pub(super) async fn start_connection(
+        &mut self,
+        mode: &str,
+        ip: &str,
+        port: &str,
+    ) -> Result<SimcomUrcSubscription<'buf>, SocketError> {
+        self.handle.drain_background_urcs();
+
+        let mut client = self.handle.lock_client().await?;
+        let urc_subscription = self.urc_channel.subscribe().unwrap();
+
+        client
+            .send(&StartConnection {
+                id: self.id,
+                mode,
+                ip,
+                port,
+            })
+            .await
+            .map_err(|_| SocketError::UnableToConnect)?;
+
+        Ok(urc_subscription)
+    }
+
+    pub(super) fn drain_background_urcs_and_ensure_in_use(&self) -> Result<(), SocketError> {
         self.handle.drain_background_urcs();
 
         if self.handle.socket_state[self.id].load(Ordering::Acquire) == SOCKET_STATE_USED {
@@ -124,40 +216,230 @@ impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> TcpSocket<'buf, 'dev, 'sub, A
         }
     }
 
+    /// Mark the socket as unused after the `<id>, CLOSED` urc was received while waiting for it
+    ///
+    /// The background subscription cannot be drained while `SimcomDevice::run()` is waiting for urcs,
+    /// so a blocked read must handle the urc itself.
+    fn closed_by_peer(&self) -> SocketError {
+        warn!("[{}] Socket closed", self.id);
+        self.handle.socket_state[self.id].store(SOCKET_STATE_UNUSED, Ordering::Release);
+        SocketError::Closed
+    }
+
+    /// Get the number of bytes that are known to be pending in the modem
+    ///
+    /// This is the pending length reported by the last read,
+    /// or at least one if the modem has since reported that more data is available.
+    pub fn bytes_available(&self) -> usize {
+        self.handle.drain_background_urcs();
+
+        if self.pending_len == 0 && self.handle.data_available[self.id].load(Ordering::Acquire) {
+            1
+        } else {
+            self.pending_len
+        }
+    }
+
+    /// Probe if the connection is still alive using `AT+CIPACK`
+    ///
+    /// This is a best-effort heuristic for detecting a connection that was silently dropped,
+    /// for example by a NAT gateway, and it should be called periodically while the socket is idle.
+    /// The socket is reported dead when the acknowledged length has not advanced since the previous call
+    /// while sent data is still unacknowledged, and no data was received from the server in the meantime.
+    /// The client is only locked while the command is sent.
+    pub async fn is_alive(&mut self) -> Result<bool, SocketError> {
+        self.drain_background_urcs_and_ensure_in_use()?;
+
+        let state = {
+            let mut client = self.handle.lock_client().await?;
+            client
+                .send(&QueryPreviousConnectionDataTransmittingState { id: self.id })
+                .await?
+        };
+
+        let received = self.received_since_probe
+            || self.pending_len > 0
+            || self.handle.data_available[self.id].load(Ordering::Acquire);
+        let stalled = self.last_probe_acklen == Some(state.acklen) && state.txlen > state.acklen;
+
+        self.last_probe_acklen = Some(state.acklen);
+        self.received_since_probe = false;
+
+        if stalled && !received {
+            warn!("[{}] Acknowledged length is not advancing", self.id);
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Wait until all written data is acknowledged by the peer using `AT+CIPACK`
+    ///
+    /// A write returns when the modem has accepted the data in quick send mode,
+    /// so use this to know that the peer has actually received it.
+    /// `SocketError::WriteTimeout` is returned if the data is not acknowledged before the timeout expires.
+    /// The client is only locked while the command is sent.
+    pub async fn flush_acked(&mut self, timeout: Duration) -> Result<(), SocketError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            self.drain_background_urcs_and_ensure_in_use()?;
+
+            let state = {
+                let mut client = self.handle.lock_client().await?;
+                client
+                    .send(&QueryPreviousConnectionDataTransmittingState { id: self.id })
+                    .await?
+            };
+            if state.acklen >= state.txlen {
+                return Ok(());
+            }
+
+            if Instant::now() >= deadline {
+                warn!("[{}] Written data was not acknowledged in time", self.id);
+                return Err(SocketError::WriteTimeout);
+            }
+
+            Timer::after(ACK_POLL_INTERVAL).await;
+        }
+    }
+
     async fn read(&mut self, buf: &mut [u8]) -> Result<usize, SocketError> {
         self.drain_background_urcs_and_ensure_in_use()?;
         if buf.is_empty() {
             return Ok(0);
         }
 
+        // Keep reading while the modem reports pending data and there is room in the buffer
+        let mut len = 0;
+        loop {
+            let read = match self.read_data_into(&mut buf[len..]).await {
+                Ok(read) => read,
+                // Return what is already read and let the next read report the error
+                Err(_) if len > 0 => break,
+                Err(e) => return Err(e),
+            };
+            len += read;
+
+            if len == buf.len() || self.pending_len == 0 {
+                break;
+            }
+        }
+
+        Ok(len)
+    }
+
+    /// Read the next received data into a caller provided buffer
+    ///
+    /// The data is appended to `buf`. At most the remaining capacity is requested from the modem,
+    /// so any data that does not fit stays pending in the modem for the next read.
+    /// [`SocketError::BufferFull`] is returned if `buf` has no remaining capacity.
+    pub async fn read_into<const N: usize>(
+        &mut self,
+        buf: &mut heapless::Vec<u8, N>,
+    ) -> Result<usize, SocketError> {
+        self.drain_background_urcs_and_ensure_in_use()?;
+
+        let len = buf.len();
+        if len == N {
+            return Err(SocketError::BufferFull);
+        }
+
+        // Resizing to the capacity cannot fail
+        buf.resize_default(N).unwrap();
+        let result = self.read_data_into(&mut buf[len..]).await;
+        buf.truncate(len + *result.as_ref().unwrap_or(&0));
+
+        match result {
+            Err(SocketError::ReadTimeout) => Ok(0),
+            result => result,
+        }
+    }
+
+    pub(super) fn max_read_len(&self) -> usize {
         const MAX_READ: usize = 1460;
         const MAX_HEADER_LEN: usize = "\r\n+CIPRXGET: 1,1,4444,4444\r\n".len();
         const TAIL_LEN: usize = "\r\nOK\r\n".len();
-        let max_len = usize::min(
-            usize::min(buf.len(), MAX_READ),
+        usize::min(
+            MAX_READ,
             self.handle.max_urc_len - MAX_HEADER_LEN - TAIL_LEN,
-        );
+        )
+    }
+
+    /// Read the next received data into `buf`, requesting at most `buf.len()` bytes
+    async fn read_data_into(&mut self, buf: &mut [u8]) -> Result<usize, SocketError> {
+        let max_len = usize::min(buf.len(), self.max_read_len());
+        let data = self.read_data(max_len).await?;
+
+        // The modem never replies with more than the requested length
+        let len = usize::min(buf.len(), data.len());
+        buf[..len].copy_from_slice(&data[..len]);
+        Ok(len)
+    }
+
+    async fn read_data(&mut self, max_len: usize) -> Result<Vec<u8>, SocketError> {
+        // An idle socket is not an error, so do not drop the socket if this times out
+        self.wait_for_data_available().await?;
+
+        let result = self.try_read_data(max_len).await;
+        if let Err(SocketError::ReadTimeout) = result {
+            error!("[{}] Timeout while reading data", self.id);
+            self.handle.socket_state[self.id].store(SOCKET_STATE_DROPPED, Ordering::Release);
+        }
+        result
+    }
+
+    /// Wait for the modem to report that data is available using the `+CIPRXGET: 1,<id>` urc
+    ///
+    /// This avoids sending `AT+CIPRXGET=2` while there is nothing to read.
+    /// `SocketError::ReadTimeout` is returned if no data is reported before the read timeout expires.
+    async fn wait_for_data_available(&mut self) -> Result<(), SocketError> {
+        let mut urc_subscription = self.urc_channel.subscribe().unwrap();
+
+        // The urc may already have been handled in the background before we subscribed
+        self.drain_background_urcs_and_ensure_in_use()?;
+        if self.pending_len > 0 || self.handle.data_available[self.id].load(Ordering::Acquire) {
+            return Ok(());
+        }
+
+        trace!("[{}] Waiting for data to become available", self.id);
+
+        let wait = async {
+            loop {
+                let urc = urc_subscription.next_message_pure().await;
+                self.drain_background_urcs_and_ensure_in_use()?;
+
+                // Urcs for other sockets are left for their owners
+                match urc {
+                    Urc::DataAvailable(id) if id == self.id => return Ok(()),
+                    Urc::Closed(id) if id == self.id => return Err(self.closed_by_peer()),
+                    _ => {}
+                }
+            }
+        };
+
+        match self.read_timeout {
+            Some(timeout) => with_timeout(timeout, wait)
+                .await
+                .map_err(|_| SocketError::ReadTimeout)?,
+            None => wait.await,
+        }
+    }
 
+    /// Read the next received data without dropping the socket if nothing is received before the timeout
+    pub(super) async fn try_read_data(&mut self, max_len: usize) -> Result<Vec<u8>, SocketError> {
         let mut urc_subscription = {
-            let mut client = self.handle.client.lock().await;
+            let mut client = self.handle.lock_client().await?;
             let urc_subscription = self.urc_channel.subscribe().unwrap();
 
             trace!("[{}] Sending ReadData", self.id);
 
-            client
-                .send(&ReadData {
-                    id: self.id,
-                    max_len,
-                })
-                .await
-                .map_err(|_| SocketError::UnableToRead)?;
+            self.send_read_data(&mut client, max_len).await?;
 
             urc_subscription
         };
 
         let mut no_data_response_received = false;
 
-        let mut timeout_instant = Instant::now() + Duration::from_secs(60);
+        let mut timeout_instant = Instant::now() + self.read_data_urc_timeout();
         'wait_for_data: while let Some(timeout) =
             timeout_instant.checked_duration_since(Instant::now())
         {
@@ -173,21 +455,23 @@ impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> TcpSocket<'buf, 'dev, 'sub, A
 
             match urc {
                 Urc::ReadData(r) if r.id == self.id => {
+                    self.pending_len = r.pending_len;
                     if r.data_len > 0 {
-                        buf[..r.data_len].copy_from_slice(r.data.take().unwrap().as_slice());
-                        return Ok(r.data_len);
+                        self.received_since_probe = true;
+                        return Ok(r.data.take().unwrap());
                     }
 
                     // There was no data - start waiting for the DataAvailable urc
                     no_data_response_received = true;
                 }
+                Urc::Closed(id) if id == self.id => return Err(self.closed_by_peer()),
                 Urc::DataAvailable(id) if id == self.id => {
                     // Re-request data now when we know that it is available
                     // Only do so if we have not yet processed the ReadData urc
                     if no_data_response_received {
                         debug!("[{}] Re-sending data read request", id);
 
-                        let mut client = self.handle.client.lock().await;
+                        let mut client = self.handle.lock_client().await?;
 
                         // Drain all messages in subscription before re-sending ReadData
                         let mut cnt = 0;
@@ -202,16 +486,10 @@ impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> TcpSocket<'buf, 'dev, 'sub, A
 
                         trace!("[{}] Sending ReadData", id);
 
-                        client
-                            .send(&ReadData {
-                                id: self.id,
-                                max_len,
-                            })
-                            .await
-                            .map_err(|_| SocketError::UnableToRead)?;
+                        self.send_read_data(&mut client, max_len).await?;
 
                         // Reset timeout to ensure that we in fact read the response
-                        timeout_instant = Instant::now() + Duration::from_secs(10);
+                        timeout_instant = Instant::now() + self.read_data_urc_timeout();
                     } else {
                         debug!(
                             "[{}] Data available urc received before read data response urc",
@@ -223,11 +501,34 @@ impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> TcpSocket<'buf, 'dev, 'sub, A
             }
         }
 
-        error!("[{}] Timeout while reading data", self.id);
-        self.handle.socket_state[self.id].store(SOCKET_STATE_DROPPED, Ordering::Release);
         Err(SocketError::ReadTimeout)
     }
 
+    /// The time to wait for the modem to report the data after a read request
+    fn read_data_urc_timeout(&self) -> Duration {
+        self.read_timeout
+            .unwrap_or(Duration::from_millis(ReadData::MAX_TIMEOUT_MS as u64))
+    }
+
+    async fn send_read_data(&self, client: &mut AtCl, max_len: usize) -> Result<(), SocketError> {
+        let cmd = ReadData {
+            id: self.id,
+            max_len,
+        };
+        let request = client.send(&cmd);
+
+        let result = match self.read_timeout {
+            // The request is abandoned if it is not completed within the timeout
+            Some(timeout) => with_timeout(timeout, request)
+                .await
+                .map_err(|_| SocketError::ReadTimeout)?,
+            None => request.await,
+        };
+
+        result.map_err(|_| SocketError::UnableToRead)?;
+        Ok(())
+    }
+
     async fn write(&mut self, buf: &[u8]) -> Result<usize, SocketError> {
         if buf.is_empty() {
             self.drain_background_urcs_and_ensure_in_use()?;
@@ -249,7 +550,7 @@ impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> TcpSocket<'buf, 'dev, 'sub, A
                 self.drain_background_urcs_and_ensure_in_use()?;
 
                 {
-                    let mut client = self.handle.client.lock().await;
+                    let mut client = self.handle.lock_client().await?;
                     let response = client
                         .send(&QueryPreviousConnectionDataTransmittingState { id: self.id })
                         .await?;
@@ -266,23 +567,91 @@ impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> TcpSocket<'buf, 'dev, 'sub, A
             }
         }
 
-        // let max_len = loop {
-        //     self.drain_background_urcs_and_ensure_in_use()?;
-        //     let mut client = self.handle.client.lock().await;
-        //     let buf_size = client.send(&QuerySendBufferSize).await?;
-        //     let max_len = buf_size.size[self.id];
-        //     if max_len > 0 {
-        //         break max_len;
-        //     }
-        // };
-        // let max_len = usize::min(max_len, MAX_WRITE);
+        // Write the buffer in chunks that fit in the free send buffer reported by the modem,
+        // as the modem may otherwise accept less than what is sent.
+        let mut written = 0;
+        let mut deadline = self.write_timeout.map(|timeout| Instant::now() + timeout);
+        while written < buf.len() {
+            match self.write_next_chunk(&buf[written..], &mut deadline).await {
+                Ok(accepted) => written += accepted,
+                // Return what is already written and let the next write report the error
+                Err(_) if written > 0 => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        // Start write cooldown timer.
+        // 900ms seems to be a good number such that the first DataTransmittingState.nacklen
+        // is likely zero (see above)
+        // A value of 1000ms lets nacklen on the first query be nonzero too much
+        // which causes us to retry the DataTransmittingState query
+        self.write_cooldown_timer = Some(Timer::after_millis(1000));
+        Ok(written)
+    }
+
+    /// Wait for the modem to report free space in the send buffer of the socket using `AT+CIPSEND?`
+    async fn wait_for_send_buffer(
+        &mut self,
+        deadline: Option<Instant>,
+    ) -> Result<usize, SocketError> {
+        loop {
+            self.drain_background_urcs_and_ensure_in_use()?;
+
+            let size = {
+                let mut client = self.handle.lock_client().await?;
+                client.send(&QuerySendBufferSize).await?.size[self.id]
+            };
+            if size > 0 {
+                return Ok(usize::min(size, MAX_WRITE));
+            }
+
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                warn!(
+                    "[{}] Send buffer did not become available before the write timeout",
+                    self.id
+                );
+                return Err(SocketError::WriteTimeout);
+            }
+
+            Timer::after(SEND_BUFFER_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Write the start of `buf` once there is room for it in the send buffer
+    ///
+    /// The deadline is restarted whenever the modem accepts any bytes.
+    async fn write_next_chunk(
+        &mut self,
+        buf: &[u8],
+        deadline: &mut Option<Instant>,
+    ) -> Result<usize, SocketError> {
+        loop {
+            let max_len = self.wait_for_send_buffer(*deadline).await?;
+            let len = usize::min(buf.len(), max_len);
+            let accepted = self.write_chunk(&buf[..len]).await?;
+
+            if accepted > 0 {
+                *deadline = self.write_timeout.map(|timeout| Instant::now() + timeout);
+                return Ok(accepted);
+            }
+
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                warn!(
+                    "[{}] No bytes were accepted before the write timeout",
+                    self.id
+                );
+                return Err(SocketError::WriteTimeout);
+            }
+        }
+    }
 
-        let len = usize::min(buf.len(), MAX_WRITE);
+    async fn write_chunk(&mut self, buf: &[u8]) -> Result<usize, SocketError> {
+        let len = buf.len();
         debug!("[{}] Writing {} bytes", self.id, len);
 
         self.drain_background_urcs_and_ensure_in_use()?;
 
-        let mut client = self.handle.client.lock().await;
+        let mut client = self.handle.lock_client().await?;
         // Hold client all the way from request prompt until DATA ACCEPT is received
 
         // Obtain a prompt
@@ -297,18 +666,12 @@ impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> TcpSocket<'buf, 'dev, 'sub, A
         // We have received prompt and are ready to write data
 
         // Write the data buffer
-        match client.send(&WriteData { buf: &buf[..len] }).await {
+        match client.send(&WriteData { buf }).await {
             Ok(response) => {
                 debug!(
                     "[{}] Accepted {} out of {} written bytes",
                     self.id, response.accepted, len
                 );
-                // Start write cooldown timer.
-                // 900ms seems to be a good number such that the first DataTransmittingState.nacklen
-                // is likely zero (see above)
-                // A value of 1000ms lets nacklen on the first query be nonzero too much
-                // which causes us to retry the DataTransmittingState query
-                self.write_cooldown_timer = Some(Timer::after_millis(1000));
                 Ok(response.accepted)
             }
             Err(e) => {
@@ -362,64 +725,187 @@ impl<AtCl: AtatClient> Drop for TcpSocket<'_, '_, '_, AtCl> {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use core::convert::Infallible;
+/// The peer of a [`ReconnectingSocket`]
+#[derive(Clone, Copy)]
+pub enum Peer<'a> {
+    /// A fixed socket address
+    Addr(SocketAddr),
+    /// A host name that is resolved every time the socket is dialed, and a port
+    Host(&'a str, u16),
+}
 
-    use atat::AtatIngress;
-    use embedded_hal::digital::{ErrorType, OutputPin};
-    use embedded_nal_async::{IpAddr, Ipv4Addr, SocketAddr};
-    use static_cell::make_static;
+/// The policy for how a [`ReconnectingSocket`] re-dials its peer
+#[derive(Clone, Copy)]
+pub struct RetryPolicy {
+    /// The number of times the peer is dialed before giving up
+    pub max_attempts: u8,
+    /// The delay between attempts
+    pub delay: Duration,
+}
 
-    use crate::{
-        device::{SocketState, SOCKET_STATE_UNKNOWN, SOCKET_STATE_UNUSED},
-        services::serial_mock::{RxMock, SerialMock},
-        SimcomConfig, SimcomDevice, SimcomIngress, SimcomResponseSlot, MAX_SOCKETS,
-    };
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            delay: Duration::from_secs(5),
+        }
+    }
+}
 
-    use super::*;
+/// The event reported when a [`ReconnectingSocket`] has re-dialed its peer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReconnectEvent {
+    /// The address that the socket is now connected to
+    pub remote: SocketAddr,
+    /// The number of attempts it took to reconnect
+    pub attempts: u8,
+}
+
+/// A TCP socket that re-dials the same peer when the underlying connection is closed
+///
+/// Any data that was in flight when the connection closed is lost,
+/// so callers should check [`ReconnectingSocket::take_reconnect_event()`]
+/// to learn when the stream has been restarted.
+pub struct ReconnectingSocket<'a, 'buf, 'dev, 'sub, AtCl: AtatClient> {
+    data: &'a DataService<'buf, 'dev, 'sub, AtCl>,
+    peer: Peer<'a>,
+    policy: RetryPolicy,
+    socket: Option<TcpSocket<'buf, 'dev, 'sub, AtCl>>,
+    reconnect_event: Option<ReconnectEvent>,
+}
+
+impl<'a, 'buf, 'dev, 'sub, AtCl: AtatClient + 'static>
+    ReconnectingSocket<'a, 'buf, 'dev, 'sub, AtCl>
+{
+    /// Dial the peer and create the socket
+    pub async fn connect(
+        data: &'a DataService<'buf, 'dev, 'sub, AtCl>,
+        peer: Peer<'a>,
+        policy: RetryPolicy,
+    ) -> Result<Self, SocketError> {
+        let mut socket = Self {
+            data,
+            peer,
+            policy,
+            socket: None,
+            reconnect_event: None,
+        };
+        socket.dial().await?;
+        Ok(socket)
+    }
+
+    /// Take the event reported by the most recent reconnect, if any
+    pub fn take_reconnect_event(&mut self) -> Option<ReconnectEvent> {
+        self.reconnect_event.take()
+    }
 
-    struct Config(ResetPin);
-    struct ResetPin(bool);
+    async fn dial(&mut self) -> Result<(SocketAddr, u8), SocketError> {
+        // The previous socket must release its id before a new one is taken
+        self.socket = None;
+
+        let mut attempt = 1;
+        loop {
+            let result = match self.remote().await {
+                Ok(remote) => self
+                    .data
+                    .connect(remote)
+                    .await
+                    .map(|socket| (remote, socket)),
+                Err(e) => Err(e),
+            };
 
-    impl SimcomConfig for Config {
-        type ResetPin = ResetPin;
+            match result {
+                Ok((remote, socket)) => {
+                    self.socket = Some(socket);
+                    return Ok((remote, attempt));
+                }
+                Err(e) if attempt < self.policy.max_attempts => {
+                    warn!("Unable to dial peer, retrying");
+                    debug!("Dial attempt {} failed with error {:?}", attempt, e);
+                }
+                Err(e) => return Err(e),
+            }
 
-        fn reset_pin(&mut self) -> &mut Self::ResetPin {
-            &mut self.0
+            attempt += 1;
+            Timer::after(self.policy.delay).await;
         }
     }
 
-    impl OutputPin for ResetPin {
-        fn set_low(&mut self) -> Result<(), Self::Error> {
-            self.0 = false;
-            Ok(())
+    async fn reconnect(&mut self) -> Result<&mut TcpSocket<'buf, 'dev, 'sub, AtCl>, SocketError> {
+        let (remote, attempts) = self.dial().await?;
+        info!("Reconnected to peer after {} attempt(s)", attempts);
+        self.reconnect_event = Some(ReconnectEvent { remote, attempts });
+        Ok(self.socket.as_mut().unwrap())
+    }
+
+    async fn remote(&self) -> Result<SocketAddr, SocketError> {
+        match self.peer {
+            Peer::Addr(remote) => Ok(remote),
+            Peer::Host(host, port) => {
+                let ip = self.data.get_host_by_name(host, AddrType::IPv4).await?;
+                Ok(SocketAddr::new(ip, port))
+            }
         }
+    }
+}
+impl<AtCl: AtatClient> embedded_io::ErrorType for ReconnectingSocket<'_, '_, '_, '_, AtCl> {
+    type Error = SocketError;
+}
 
-        fn set_high(&mut self) -> Result<(), Self::Error> {
-            self.0 = true;
-            Ok(())
+impl<AtCl: AtatClient + 'static> Read for ReconnectingSocket<'_, '_, '_, '_, AtCl> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, SocketError> {
+        if let Some(socket) = self.socket.as_mut() {
+            match Read::read(socket, buf).await {
+                Err(SocketError::Closed) => warn!("Socket closed, reconnecting"),
+                result => return result,
+            }
         }
+
+        let socket = self.reconnect().await?;
+        Read::read(socket, buf).await
     }
+}
+
+impl<AtCl: AtatClient + 'static> Write for ReconnectingSocket<'_, '_, '_, '_, AtCl> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, SocketError> {
+        if let Some(socket) = self.socket.as_mut() {
+            match Write::write(socket, buf).await {
+                Err(SocketError::Closed) => warn!("Socket closed, reconnecting"),
+                result => return result,
+            }
+        }
 
-    impl ErrorType for ResetPin {
-        type Error = Infallible;
+        let socket = self.reconnect().await?;
+        Write::write(socket, buf).await
     }
 
-    macro_rules! setup_atat {
-        () => {{
-            let ingress_buf = make_static!([0; 128]);
-            static RES_SLOT: SimcomResponseSlot<128> = SimcomResponseSlot::new();
-            let device_buf = make_static!([0; 128]);
-            static URC_CHANNEL: SimcomUrcChannel = SimcomUrcChannel::new();
-            static SERIAL: SerialMock = SerialMock::new();
-            let (tx, rx) = SERIAL.split();
-            let ingress = SimcomIngress::new(ingress_buf, &RES_SLOT, &URC_CHANNEL);
-            let config = Config(ResetPin(true));
-            let device = SimcomDevice::new(tx, &RES_SLOT, device_buf, &URC_CHANNEL, config);
-            (ingress, device, rx)
-        }};
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        // All written data is already accepted as we use "quick send mode"
+        Ok(())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use atat::AtatIngress;
+    use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, channel::Channel};
+    use embedded_nal_async::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+    use futures::{
+        future::{self, Either},
+        pin_mut,
+    };
+    use static_cell::make_static;
+
+    use crate::{
+        device::{SocketState, SOCKET_STATE_UNKNOWN, SOCKET_STATE_UNUSED},
+        services::{
+            device_mock::{respond, setup_atat, Config, ResetPin},
+            serial_mock::{ChannelSerial, RxMock, SerialMock},
+        },
+        SimcomConfig, SimcomDevice, SimcomResponseSlot, MAX_SOCKETS,
+    };
+
+    use super::*;
 
     async fn _hello_world_example() {
         const INGRESS_BUF_SIZE: usize = 128;
@@ -505,6 +991,7 @@ mod tests {
     async fn can_read_available_data() {
         let (mut ingress, mut device, mut serial) = setup_atat!();
         let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+        ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await;
 
         let read = async {
             let mut buf = [0; 16];
@@ -531,21 +1018,28 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn can_read_data_with_data_available_before_read_data() {
+    async fn read_waits_for_data_available_urc() {
         let (mut ingress, mut device, mut serial) = setup_atat!();
         let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
 
-        let read = async {
-            let mut buf = [0; 16];
-            socket.read(&mut buf).await.unwrap()
-        };
+        let mut buf = [0; 16];
+        let read = socket.read(&mut buf);
         let sent = async {
+            // Nothing is requested before the modem reports available data for the socket
+            ingress.write(b"\r\n+CIPRXGET: 1,3\r\n").await;
+            assert!(
+                with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                    .await
+                    .is_err()
+            );
+
+            ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await;
+
             // Expect ReadData request
             let sent = with_timeout(Duration::from_millis(100), serial.next_message_pure())
                 .await
                 .unwrap();
 
-            ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await; // Transmitted by modem before it understands our read request
             ingress
                 .write(b"\r\n+CIPRXGET: 2,5,8,0\r\nHTTP\r\n\r\n")
                 .await;
@@ -556,47 +1050,840 @@ mod tests {
 
         let (read, sent) = tokio::join!(read, sent);
 
-        assert_eq!(8, read);
+        assert_eq!(8, read.unwrap());
         assert_eq!(b"AT+CIPRXGET=2,5,16\r", sent.as_slice());
+
+        // The urc for the other socket is still seen by its owner
+        assert!(socket.handle.data_available[3].load(Ordering::Acquire));
     }
 
     #[tokio::test]
-    async fn can_read_data_with_no_data_initially_available_retrying() {
+    async fn read_times_out_without_data_available_urc() {
         let (mut ingress, mut device, mut serial) = setup_atat!();
-        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5)
+            .await
+            .with_read_timeout(Some(Duration::from_millis(100)));
+
+        let mut buf = [0; 16];
+        let read = socket.read(&mut buf).await;
+
+        assert!(matches!(read, Err(SocketError::ReadTimeout)));
+        assert!(serial.try_next_message_pure().is_none());
+
+        // The socket is not dropped as it is just idle
+        assert_eq!(
+            SOCKET_STATE_USED,
+            socket.handle.socket_state[5].load(Ordering::Acquire)
+        );
+    }
+
+    #[tokio::test]
+    async fn read_fails_when_read_timeout_expires() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5)
+            .await
+            .with_read_timeout(Some(Duration::from_millis(100)));
+        ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await;
 
+        let start = Instant::now();
         let read = async {
             let mut buf = [0; 16];
-            socket.read(&mut buf).await.unwrap()
+            socket.read(&mut buf).await
         };
         let sent = async {
-            // Expect ReadData request
-            let sent0 = with_timeout(Duration::from_millis(100), serial.next_message_pure())
+            // Expect ReadData request, but do not respond to it
+            with_timeout(Duration::from_millis(100), serial.next_message_pure())
                 .await
-                .unwrap();
+                .unwrap()
+        };
 
-            ingress.write(b"\r\n+CIPRXGET: 2,5,0,0\r\n").await; // There is no data available
-            ingress.write(b"\r\nOK\r\n").await;
+        let (read, sent) = tokio::join!(read, sent);
 
-            ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await; // Data becomes available
+        assert!(matches!(read, Err(SocketError::ReadTimeout)));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(b"AT+CIPRXGET=2,5,16\r", sent.as_slice());
+    }
 
-            // Expect ReadData request
-            let sent1 = with_timeout(Duration::from_millis(100), serial.next_message_pure())
+    #[tokio::test]
+    async fn read_fails_when_read_data_urc_is_not_received_within_read_timeout() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5)
+            .await
+            .with_read_timeout(Some(Duration::from_millis(100)));
+        ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await;
+
+        let start = Instant::now();
+        let read = async {
+            let mut buf = [0; 16];
+            socket.read(&mut buf).await
+        };
+        let sent = async {
+            // Expect ReadData request, and accept it without the data urc
+            let sent = with_timeout(Duration::from_millis(100), serial.next_message_pure())
                 .await
                 .unwrap();
 
-            ingress
-                .write(b"\r\n+CIPRXGET: 2,5,8,0\r\nHTTP\r\n\r\n")
-                .await;
             ingress.write(b"\r\nOK\r\n").await;
 
-            (sent0, sent1)
+            sent
         };
 
         let (read, sent) = tokio::join!(read, sent);
 
-        assert_eq!(8, read);
-        assert_eq!(b"AT+CIPRXGET=2,5,16\r", sent.0.as_slice());
-        assert_eq!(b"AT+CIPRXGET=2,5,16\r", sent.1.as_slice());
+        assert!(matches!(read, Err(SocketError::ReadTimeout)));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(b"AT+CIPRXGET=2,5,16\r", sent.as_slice());
+    }
+
+    #[tokio::test]
+    async fn read_fails_when_closed_while_waiting_for_data() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+
+        let start = Instant::now();
+        let read = async {
+            let mut buf = [0; 16];
+            socket.read(&mut buf).await
+        };
+        let closed = async {
+            Timer::after(Duration::from_millis(10)).await;
+            ingress.write(b"\r\n5, CLOSED\r\n").await;
+        };
+
+        let (read, _) = tokio::join!(read, closed);
+
+        assert!(matches!(read, Err(SocketError::Closed)));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(serial.try_next_message_pure().is_none());
+        assert_eq!(
+            SOCKET_STATE_UNUSED,
+            socket.handle.socket_state[5].load(Ordering::Acquire)
+        );
+    }
+
+    #[tokio::test]
+    async fn read_fails_when_closed_while_waiting_for_read_response() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+        ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await;
+
+        let start = Instant::now();
+        let read = async {
+            let mut buf = [0; 16];
+            socket.read(&mut buf).await
+        };
+        let sent = async {
+            let sent = with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                .await
+                .unwrap();
+
+            // The socket is closed before any data is returned
+            ingress.write(b"\r\n+CIPRXGET: 2,5,0,0\r\n").await;
+            ingress.write(b"\r\nOK\r\n").await;
+            ingress.write(b"\r\n5, CLOSED\r\n").await;
+
+            sent
+        };
+
+        let (read, sent) = tokio::join!(read, sent);
+
+        assert!(matches!(read, Err(SocketError::Closed)));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(b"AT+CIPRXGET=2,5,16\r", sent.as_slice());
+        assert_eq!(
+            SOCKET_STATE_UNUSED,
+            socket.handle.socket_state[5].load(Ordering::Acquire)
+        );
+    }
+
+    #[tokio::test]
+    async fn read_fails_when_closed_while_background_subscription_is_locked() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+
+        // This is what run() does while it waits for the next urc
+        let background = socket.handle.background_subscription.lock().await;
+
+        let read = async {
+            let mut buf = [0; 16];
+            with_timeout(Duration::from_secs(1), socket.read(&mut buf)).await
+        };
+        let closed = async {
+            Timer::after(Duration::from_millis(10)).await;
+            ingress.write(b"\r\n5, CLOSED\r\n").await;
+        };
+
+        let (read, _) = tokio::join!(read, closed);
+        drop(background);
+
+        assert!(matches!(read, Ok(Err(SocketError::Closed))));
+        assert_eq!(
+            SOCKET_STATE_UNUSED,
+            socket.handle.socket_state[5].load(Ordering::Acquire)
+        );
+    }
+
+    #[tokio::test]
+    async fn read_fails_when_closed_while_run_is_active() {
+        static RX: Channel<CriticalSectionRawMutex, &'static [u8], 4> = Channel::new();
+        let (ingress, mut device, mut serial) = setup_atat!();
+        for _ in 0..MAX_SOCKETS {
+            device
+                .handle
+                .socket_state
+                .push(SocketState::new(SOCKET_STATE_UNKNOWN))
+                .unwrap();
+        }
+        device.handle.socket_state[5].store(SOCKET_STATE_UNUSED, Ordering::Relaxed);
+        let device = &device;
+
+        // The background subscription is held by run() while it waits for urcs
+        let run = device.run(ingress, ChannelSerial(&RX));
+        let read = async {
+            let data = DataService::new(&device.handle, device.urc_channel);
+            let mut socket = data
+                .connect(SocketAddr::new(
+                    IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+                    8080,
+                ))
+                .await
+                .unwrap();
+
+            let mut buf = [0; 16];
+            let read = socket.read(&mut buf).await;
+            (read, socket.handle.socket_state[5].load(Ordering::Acquire))
+        };
+        let modem = async {
+            let sent = with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                .await
+                .unwrap();
+            assert_eq!(
+                b"AT+CIPSTART=5,\"TCP\",\"127.0.0.1\",\"8080\"\r",
+                sent.as_slice()
+            );
+
+            RX.send(b"\r\nOK\r\n").await;
+            RX.send(b"\r\n5, CONNECT OK\r\n").await;
+            Timer::after(Duration::from_millis(10)).await;
+            RX.send(b"\r\n5, CLOSED\r\n").await;
+        };
+        let client = with_timeout(Duration::from_secs(1), async {
+            let (read, _) = tokio::join!(read, modem);
+            read
+        });
+        pin_mut!(run, client);
+
+        let (read, state) = match future::select(run, client).await {
+            Either::Left((never, _)) => never,
+            Either::Right((client, _)) => client.unwrap(),
+        };
+
+        assert!(matches!(read, Err(SocketError::Closed)));
+        assert_eq!(SOCKET_STATE_UNUSED, state);
+    }
+
+    #[tokio::test]
+    async fn write_is_split_by_free_send_buffer() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+
+        let write = socket.write(b"HELLO WORLD");
+        let sent = async {
+            let mut sent = Vec::new();
+
+            // Expect QuerySendBufferSize request
+            sent.push(serial.next_message_pure().await);
+            ingress.write(b"\r\n+CIPSEND: 0,0\r\n+CIPSEND: 1,0\r\n+CIPSEND: 2,0\r\n+CIPSEND: 3,0\r\n+CIPSEND: 4,0\r\n+CIPSEND: 5,5\r\n\r\nOK\r\n").await;
+
+            // Expect SendData and WriteData requests
+            sent.push(serial.next_message_pure().await);
+            ingress.write(b"\r\n> ").await;
+            sent.push(serial.next_message_pure().await);
+            ingress.write(b"\r\nDATA ACCEPT:5,5\r\n").await;
+
+            // The send buffer is full
+            sent.push(serial.next_message_pure().await);
+            ingress.write(b"\r\n+CIPSEND: 0,0\r\n+CIPSEND: 1,0\r\n+CIPSEND: 2,0\r\n+CIPSEND: 3,0\r\n+CIPSEND: 4,0\r\n+CIPSEND: 5,0\r\n\r\nOK\r\n").await;
+
+            sent.push(serial.next_message_pure().await);
+            ingress.write(b"\r\n+CIPSEND: 0,0\r\n+CIPSEND: 1,0\r\n+CIPSEND: 2,0\r\n+CIPSEND: 3,0\r\n+CIPSEND: 4,0\r\n+CIPSEND: 5,1460\r\n\r\nOK\r\n").await;
+
+            sent.push(serial.next_message_pure().await);
+            ingress.write(b"\r\n> ").await;
+            sent.push(serial.next_message_pure().await);
+            ingress.write(b"\r\nDATA ACCEPT:5,6\r\n").await;
+
+            sent
+        };
+
+        let (written, sent) = tokio::join!(write, sent);
+
+        assert_eq!(11, written.unwrap());
+        assert_eq!(b"AT+CIPSEND?\r", sent[0].as_slice());
+        assert_eq!(b"AT+CIPSEND=5,5\r", sent[1].as_slice());
+        assert_eq!(b"HELLO", sent[2].as_slice());
+        assert_eq!(b"AT+CIPSEND?\r", sent[3].as_slice());
+        assert_eq!(b"AT+CIPSEND?\r", sent[4].as_slice());
+        assert_eq!(b"AT+CIPSEND=5,6\r", sent[5].as_slice());
+        assert_eq!(b" WORLD", sent[6].as_slice());
+    }
+
+    #[tokio::test]
+    async fn write_returns_partial_length_when_send_buffer_stays_full() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5)
+            .await
+            .with_write_timeout(Some(Duration::from_millis(50)));
+
+        let write = socket.write(b"HELLO WORLD");
+        let sent = async {
+            // Expect QuerySendBufferSize request
+            let sent = serial.next_message_pure().await;
+            assert_eq!(b"AT+CIPSEND?\r", sent.as_slice());
+            ingress.write(b"\r\n+CIPSEND: 0,0\r\n+CIPSEND: 1,0\r\n+CIPSEND: 2,0\r\n+CIPSEND: 3,0\r\n+CIPSEND: 4,0\r\n+CIPSEND: 5,5\r\n\r\nOK\r\n").await;
+
+            // Expect SendData and WriteData requests
+            let sent = serial.next_message_pure().await;
+            assert_eq!(b"AT+CIPSEND=5,5\r", sent.as_slice());
+            ingress.write(b"\r\n> ").await;
+            let sent = serial.next_message_pure().await;
+            assert_eq!(b"HELLO", sent.as_slice());
+            ingress.write(b"\r\nDATA ACCEPT:5,5\r\n").await;
+
+            // Keep reporting a full send buffer
+            loop {
+                let sent = serial.next_message_pure().await;
+                assert_eq!(b"AT+CIPSEND?\r", sent.as_slice());
+                ingress.write(b"\r\n+CIPSEND: 0,0\r\n+CIPSEND: 1,0\r\n+CIPSEND: 2,0\r\n+CIPSEND: 3,0\r\n+CIPSEND: 4,0\r\n+CIPSEND: 5,0\r\n\r\nOK\r\n").await;
+            }
+        };
+
+        let written = tokio::select! {
+            written = write => written,
+            _ = sent => unreachable!(),
+        };
+
+        assert_eq!(5, written.unwrap());
+    }
+
+    #[tokio::test]
+    async fn write_fails_when_send_buffer_stays_full() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5)
+            .await
+            .with_write_timeout(Some(Duration::from_millis(50)));
+
+        let write = socket.write(b"HELLO");
+        let sent = async {
+            // Keep reporting a full send buffer
+            loop {
+                let sent = serial.next_message_pure().await;
+                assert_eq!(b"AT+CIPSEND?\r", sent.as_slice());
+                ingress.write(b"\r\n+CIPSEND: 0,0\r\n+CIPSEND: 1,0\r\n+CIPSEND: 2,0\r\n+CIPSEND: 3,0\r\n+CIPSEND: 4,0\r\n+CIPSEND: 5,0\r\n\r\nOK\r\n").await;
+            }
+        };
+
+        let written = tokio::select! {
+            written = write => written,
+            _ = sent => unreachable!(),
+        };
+
+        assert!(matches!(written, Err(SocketError::WriteTimeout)));
+    }
+
+    #[tokio::test]
+    async fn flush_acked_waits_until_all_data_is_acknowledged() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+
+        let exchange: &[(&[u8], &[u8])] = &[
+            (b"AT+CIPACK=5\r", b"\r\n+CIPACK: 10,4,6\r\n\r\nOK\r\n"),
+            (b"AT+CIPACK=5\r", b"\r\n+CIPACK: 10,10,0\r\n\r\nOK\r\n"),
+        ];
+        let (flushed, _) = tokio::join!(
+            socket.flush_acked(Duration::from_secs(5)),
+            respond(&mut ingress, &mut serial, exchange)
+        );
+
+        flushed.unwrap();
+    }
+
+    #[tokio::test]
+    async fn flush_acked_fails_when_timeout_expires() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+
+        let exchange: &[(&[u8], &[u8])] = &[
+            (b"AT+CIPACK=5\r", b"\r\n+CIPACK: 10,4,6\r\n\r\nOK\r\n"),
+            (b"AT+CIPACK=5\r", b"\r\n+CIPACK: 10,4,6\r\n\r\nOK\r\n"),
+        ];
+        let (flushed, _) = tokio::join!(
+            socket.flush_acked(Duration::from_millis(100)),
+            respond(&mut ingress, &mut serial, exchange)
+        );
+
+        assert!(matches!(flushed, Err(SocketError::WriteTimeout)));
+    }
+
+    #[tokio::test]
+    async fn is_alive_reports_dead_when_ack_stops_advancing() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+
+        for (response, expected) in [
+            (b"\r\n+CIPACK: 10,4,6\r\n\r\nOK\r\n", true),
+            (b"\r\n+CIPACK: 10,8,2\r\n\r\nOK\r\n", true),
+            (b"\r\n+CIPACK: 10,8,2\r\n\r\nOK\r\n", false),
+        ] {
+            let exchange: &[(&[u8], &[u8])] = &[(b"AT+CIPACK=5\r", response)];
+            let (alive, _) = tokio::join!(
+                socket.is_alive(),
+                respond(&mut ingress, &mut serial, exchange)
+            );
+            assert_eq!(expected, alive.unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn is_alive_when_server_sent_data() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+
+        let (alive, _) = tokio::join!(
+            socket.is_alive(),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[(b"AT+CIPACK=5\r", b"\r\n+CIPACK: 10,4,6\r\n\r\nOK\r\n")]
+            )
+        );
+        assert!(alive.unwrap());
+
+        ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await;
+
+        let (alive, _) = tokio::join!(
+            socket.is_alive(),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[(b"AT+CIPACK=5\r", b"\r\n+CIPACK: 10,4,6\r\n\r\nOK\r\n")]
+            )
+        );
+        assert!(alive.unwrap());
+    }
+
+    #[tokio::test]
+    async fn read_continues_while_data_is_pending() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+        ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await;
+
+        let mut buf = [0; 16];
+        let read = socket.read(&mut buf);
+        let sent = async {
+            // Expect ReadData request
+            let sent0 = with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                .await
+                .unwrap();
+
+            ingress
+                .write(b"\r\n+CIPRXGET: 2,5,4,4\r\nHTTP\r\n\r\nOK\r\n")
+                .await;
+
+            // Expect ReadData request for the remaining buffer
+            let sent1 = with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                .await
+                .unwrap();
+
+            ingress
+                .write(b"\r\n+CIPRXGET: 2,5,4,0\r\n/1.1\r\n\r\nOK\r\n")
+                .await;
+
+            (sent0, sent1)
+        };
+
+        let (read, (sent0, sent1)) = tokio::join!(read, sent);
+
+        assert_eq!(8, read.unwrap());
+        assert_eq!(b"HTTP/1.1", &buf[..8]);
+        assert_eq!(b"AT+CIPRXGET=2,5,16\r", sent0.as_slice());
+        assert_eq!(b"AT+CIPRXGET=2,5,12\r", sent1.as_slice());
+        assert_eq!(0, socket.bytes_available());
+    }
+
+    #[tokio::test]
+    async fn bytes_available_reflects_pending_len() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+        ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await;
+
+        let mut buf = [0; 4];
+        let read = socket.read(&mut buf);
+        let sent = async {
+            // Expect ReadData request
+            with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                .await
+                .unwrap();
+
+            ingress
+                .write(b"\r\n+CIPRXGET: 2,5,4,10\r\nHTTP\r\n\r\nOK\r\n")
+                .await;
+        };
+
+        let (read, _) = tokio::join!(read, sent);
+
+        // The buffer is full so no more data is requested
+        assert_eq!(4, read.unwrap());
+        assert_eq!(10, socket.bytes_available());
+        assert!(serial.try_next_message_pure().is_none());
+    }
+
+    #[tokio::test]
+    async fn bytes_available_is_bumped_by_data_available_urc() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+        ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await;
+
+        let mut buf = [0; 16];
+        let read = socket.read(&mut buf);
+        let sent = async {
+            // Expect ReadData request
+            with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                .await
+                .unwrap();
+
+            ingress
+                .write(b"\r\n+CIPRXGET: 2,5,4,0\r\nHTTP\r\n\r\nOK\r\n")
+                .await;
+        };
+
+        let (read, _) = tokio::join!(read, sent);
+        assert_eq!(4, read.unwrap());
+        assert_eq!(0, socket.bytes_available());
+
+        // More data arrives after the read completed
+        ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await;
+
+        assert_eq!(1, socket.bytes_available());
+    }
+
+    #[tokio::test]
+    async fn can_read_into_vec() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+        ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await;
+
+        let mut buf = heapless::Vec::<u8, 16>::new();
+        let read = socket.read_into(&mut buf);
+        let sent = async {
+            // Expect ReadData request
+            let sent = with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                .await
+                .unwrap();
+
+            ingress
+                .write(b"\r\n+CIPRXGET: 2,5,8,0\r\nHTTP\r\n\r\n")
+                .await;
+            ingress.write(b"\r\nOK\r\n").await;
+
+            sent
+        };
+
+        let (read, sent) = tokio::join!(read, sent);
+
+        assert_eq!(8, read.unwrap());
+        assert_eq!(b"AT+CIPRXGET=2,5,16\r", sent.as_slice());
+        assert_eq!(b"HTTP\r\n\r\n", buf.as_slice());
+    }
+
+    #[tokio::test]
+    async fn read_into_vec_only_requests_remaining_capacity() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+        ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await;
+
+        let mut buf = heapless::Vec::<u8, 6>::new();
+        buf.extend_from_slice(b"GE").unwrap();
+        let read = socket.read_into(&mut buf);
+        let sent = async {
+            // Expect ReadData request
+            let sent = with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                .await
+                .unwrap();
+
+            // The rest of the data stays pending in the modem
+            ingress.write(b"\r\n+CIPRXGET: 2,5,4,4\r\nHTTP\r\n").await;
+            ingress.write(b"\r\nOK\r\n").await;
+
+            sent
+        };
+
+        let (read, sent) = tokio::join!(read, sent);
+
+        assert_eq!(4, read.unwrap());
+        assert_eq!(b"AT+CIPRXGET=2,5,4\r", sent.as_slice());
+        assert_eq!(b"GEHTTP", buf.as_slice());
+        assert_eq!(4, socket.bytes_available());
+
+        // Nothing is requested when there is no remaining capacity
+        let read = socket.read_into(&mut buf).await;
+        assert!(matches!(read, Err(SocketError::BufferFull)));
+        assert_eq!(b"GEHTTP", buf.as_slice());
+        assert!(serial.try_next_message_pure().is_none());
+    }
+
+    #[tokio::test]
+    async fn can_read_data_with_data_available_before_read_data() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+        ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await;
+
+        let read = async {
+            let mut buf = [0; 16];
+            socket.read(&mut buf).await.unwrap()
+        };
+        let sent = async {
+            // Expect ReadData request
+            let sent = with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                .await
+                .unwrap();
+
+            ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await; // Transmitted by modem before it understands our read request
+            ingress
+                .write(b"\r\n+CIPRXGET: 2,5,8,0\r\nHTTP\r\n\r\n")
+                .await;
+            ingress.write(b"\r\nOK\r\n").await;
+
+            sent
+        };
+
+        let (read, sent) = tokio::join!(read, sent);
+
+        assert_eq!(8, read);
+        assert_eq!(b"AT+CIPRXGET=2,5,16\r", sent.as_slice());
+    }
+
+    #[tokio::test]
+    async fn can_read_data_with_no_data_initially_available_retrying() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+        ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await;
+
+        let read = async {
+            let mut buf = [0; 16];
+            socket.read(&mut buf).await.unwrap()
+        };
+        let sent = async {
+            // Expect ReadData request
+            let sent0 = with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                .await
+                .unwrap();
+
+            ingress.write(b"\r\n+CIPRXGET: 2,5,0,0\r\n").await; // There is no data available
+            ingress.write(b"\r\nOK\r\n").await;
+
+            ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await; // Data becomes available
+
+            // Expect ReadData request
+            let sent1 = with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                .await
+                .unwrap();
+
+            ingress
+                .write(b"\r\n+CIPRXGET: 2,5,8,0\r\nHTTP\r\n\r\n")
+                .await;
+            ingress.write(b"\r\nOK\r\n").await;
+
+            (sent0, sent1)
+        };
+
+        let (read, sent) = tokio::join!(read, sent);
+
+        assert_eq!(8, read);
+        assert_eq!(b"AT+CIPRXGET=2,5,16\r", sent.0.as_slice());
+        assert_eq!(b"AT+CIPRXGET=2,5,16\r", sent.1.as_slice());
+    }
+
+    #[tokio::test]
+    async fn closed_urc_triggers_redial_to_same_peer() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        for _ in 0..MAX_SOCKETS {
+            device
+                .handle
+                .socket_state
+                .push(SocketState::new(SOCKET_STATE_UNKNOWN))
+                .unwrap();
+        }
+        device.handle.socket_state[5].store(SOCKET_STATE_UNUSED, Ordering::Relaxed);
+
+        let data = DataService::new(&device.handle, device.urc_channel);
+        let remote = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+
+        let socket = ReconnectingSocket::connect(&data, Peer::Addr(remote), RetryPolicy::default());
+        let sent = async {
+            // Expect StartConnection request
+            let sent = with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                .await
+                .unwrap();
+
+            ingress.write(b"\r\nOK\r\n").await;
+            ingress.write(b"\r\n5, CONNECT OK\r\n").await;
+
+            sent
+        };
+
+        let (socket, sent0) = tokio::join!(socket, sent);
+        let mut socket = socket.unwrap();
+        assert!(socket.take_reconnect_event().is_none());
+
+        // The connection is closed by the peer
+        ingress.write(b"\r\n5, CLOSED\r\n").await;
+
+        let read = async {
+            let mut buf = [0; 16];
+            Read::read(&mut socket, &mut buf).await.unwrap()
+        };
+        let sent = async {
+            // Expect StartConnection request for the same peer
+            let sent1 = with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                .await
+                .unwrap();
+
+            ingress.write(b"\r\nOK\r\n").await;
+            ingress.write(b"\r\n5, CONNECT OK\r\n").await;
+            ingress.write(b"\r\n+CIPRXGET: 1,5\r\n").await;
+
+            // Expect ReadData request
+            with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                .await
+                .unwrap();
+
+            ingress
+                .write(b"\r\n+CIPRXGET: 2,5,8,0\r\nHTTP\r\n\r\n")
+                .await;
+            ingress.write(b"\r\nOK\r\n").await;
+
+            sent1
+        };
+
+        let (read, sent1) = tokio::join!(read, sent);
+
+        assert_eq!(8, read);
+        assert_eq!(
+            b"AT+CIPSTART=5,\"TCP\",\"127.0.0.1\",\"8080\"\r",
+            sent0.as_slice()
+        );
+        assert_eq!(sent0, sent1);
+        assert_eq!(
+            Some(ReconnectEvent {
+                remote,
+                attempts: 1
+            }),
+            socket.take_reconnect_event()
+        );
+    }
+
+    #[tokio::test]
+    async fn connect_with_retry_retries_failed_connect() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        for _ in 0..MAX_SOCKETS {
+            device
+                .handle
+                .socket_state
+                .push(SocketState::new(SOCKET_STATE_UNKNOWN))
+                .unwrap();
+        }
+        device.handle.socket_state[5].store(SOCKET_STATE_UNUSED, Ordering::Relaxed);
+
+        let data = DataService::new(&device.handle, device.urc_channel);
+
+        let socket = data.connect_with_retry("utiliread.dk", 8080, 1, Duration::from_millis(10));
+        let responses = respond(
+            &mut ingress,
+            &mut serial,
+            &[
+                (
+                    b"AT+CDNSGIP=\"utiliread.dk\"\r",
+                    b"\r\nOK\r\n\r\n+CDNSGIP: 1,\"utiliread.dk\",\"1.2.3.4\"\r\n",
+                ),
+                (
+                    b"AT+CIPSTART=5,\"TCP\",\"1.2.3.4\",\"8080\"\r",
+                    b"\r\nOK\r\n\r\n5, CONNECT FAIL\r\n",
+                ),
+                (b"AT+CIPCLOSE=5\r", b"\r\n+CME ERROR: 3\r\n"),
+                (
+                    b"AT+CIPSTATUS=5\r",
+                    b"\r\n+CIPSTATUS: 5,0,\"TCP\",\"1.2.3.4\",\"8080\",\"CLOSED\"\r\n\r\nOK\r\n",
+                ),
+                (
+                    b"AT+CDNSGIP=\"utiliread.dk\"\r",
+                    b"\r\nOK\r\n\r\n+CDNSGIP: 1,\"utiliread.dk\",\"1.2.3.4\"\r\n",
+                ),
+                (
+                    b"AT+CIPSTART=5,\"TCP\",\"1.2.3.4\",\"8080\"\r",
+                    b"\r\nOK\r\n\r\n5, CONNECT OK\r\n",
+                ),
+            ],
+        );
+
+        let (socket, _) = tokio::join!(socket, responses);
+
+        assert_eq!(5, socket.unwrap().id);
+    }
+
+    #[tokio::test]
+    async fn connect_with_retry_returns_last_error() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        for _ in 0..MAX_SOCKETS {
+            device
+                .handle
+                .socket_state
+                .push(SocketState::new(SOCKET_STATE_UNKNOWN))
+                .unwrap();
+        }
+
+        let data = DataService::new(&device.handle, device.urc_channel);
+
+        let socket = data.connect_with_retry("utiliread.dk", 8080, 1, Duration::from_millis(10));
+        let responses = respond(
+            &mut ingress,
+            &mut serial,
+            &[
+                (
+                    b"AT+CDNSGIP=\"utiliread.dk\"\r",
+                    b"\r\nOK\r\n\r\n+CDNSGIP: 1,\"utiliread.dk\",\"1.2.3.4\"\r\n",
+                ),
+                (
+                    b"AT+CDNSGIP=\"utiliread.dk\"\r",
+                    b"\r\nOK\r\n\r\n+CDNSGIP: 1,\"utiliread.dk\",\"1.2.3.4\"\r\n",
+                ),
+            ],
+        );
+
+        let (socket, _) = tokio::join!(socket, responses);
+
+        assert!(matches!(socket, Err(SocketError::NoAvailableSockets)));
+        assert!(serial.try_next_message_pure().is_none());
+    }
+
+    #[tokio::test]
+    async fn connect_rejects_ipv6() {
+        let (_ingress, device, mut serial) = setup_atat!();
+        let data = DataService::new(&device.handle, device.urc_channel);
+
+        let result = data
+            .connect(SocketAddr::new(
+                IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1)),
+                8080,
+            ))
+            .await;
+
+        let error = result.err().unwrap();
+        assert!(matches!(error, SocketError::UnsupportedIpVersion));
+        assert_eq!(
+            embedded_io::ErrorKind::Unsupported,
+            embedded_io::Error::kind(&error)
+        );
+        assert!(serial.try_next_message_pure().is_none());
     }
 }
@@ -0,0 +1,194 @@
+use core::fmt::Write as _;
+
+use atat::asynch::AtatClient;
+use embedded_nal_async::SocketAddr;
+use heapless::String;
+
+use crate::{
+    commands::tcpip::{SetSsl, SetSslOption, SslMode, SslOption, SslOptionState, StartConnection},
+    SimcomUrcSubscription,
+};
+
+use super::{DataService, SocketError, TcpSocket};
+
+impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> DataService<'buf, 'dev, 'sub, AtCl> {
+    /// Connect to a remote using TLS
+    ///
+    /// [`SocketError::TlsNotSupported`] is returned if the modem firmware does not have the SSL stack,
+    /// in which case the caller may fall back to [`TcpConnect::connect()`].
+    ///
+    /// [`TcpConnect::connect()`]: embedded_nal_async::TcpConnect::connect
+    pub async fn connect_tls(
+        &self,
+        remote: SocketAddr,
+    ) -> Result<TcpSocket<'buf, 'dev, 'sub, AtCl>, SocketError> {
+        let SocketAddr::V4(remote) = remote else {
+            return Err(SocketError::UnsupportedIpVersion);
+        };
+
+        self.handle.drain_background_urcs();
+
+        // Close any sockets that have been dropped
+        self.handle.close_dropped_sockets().await;
+
+        let mut socket = TcpSocket::try_new(self.handle, self.urc_channel)?;
+        info!("[{}] TLS socket created", socket.id);
+
+        let mut ip = String::<15>::new();
+        write!(ip, "{}", remote.ip()).unwrap();
+
+        let mut port = String::<5>::new();
+        write!(port, "{}", remote.port()).unwrap();
+
+        let urc_subscription = socket.start_tls_connection(&ip, &port).await?;
+        socket.wait_for_connect(urc_subscription).await?;
+        Ok(socket)
+    }
+
+    /// Set whether the server certificate must be valid for TLS connections
+    pub async fn set_tls_certificate_validation(&self, enable: bool) -> Result<(), SocketError> {
+        let mut client = self.handle.lock_client().await?;
+        client
+            .send(&SetSslOption {
+                option: SslOption::IgnoreInvalidCertificate,
+                enable: if enable {
+                    SslOptionState::Disabled
+                } else {
+                    SslOptionState::Enabled
+                },
+            })
+            .await
+            .map_err(|_| SocketError::TlsNotSupported)?;
+        Ok(())
+    }
+}
+
+impl<'buf, AtCl: AtatClient + 'static> TcpSocket<'buf, '_, '_, AtCl> {
+    /// Send the connection request with SSL enabled, returning a subscription for the URCs that follow
+    async fn start_tls_connection(
+        &mut self,
+        ip: &str,
+        port: &str,
+    ) -> Result<SimcomUrcSubscription<'buf>, SocketError> {
+        self.handle.drain_background_urcs();
+
+        // The client is held while SSL is enabled such that it only applies to this connection
+        let mut client = self.handle.lock_client().await?;
+        let urc_subscription = self.urc_channel.subscribe().unwrap();
+
+        client
+            .send(&SetSsl {
+                mode: SslMode::Enabled,
+            })
+            .await
+            .map_err(|_| SocketError::TlsNotSupported)?;
+
+        let result = client
+            .send(&StartConnection {
+                id: self.id,
+                mode: "TCP",
+                ip,
+                port,
+            })
+            .await;
+
+        client
+            .send(&SetSsl {
+                mode: SslMode::Disabled,
+            })
+            .await?;
+
+        result.map_err(|_| SocketError::UnableToConnect)?;
+        Ok(urc_subscription)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{assert_matches::assert_matches, sync::atomic::Ordering};
+
+    use embedded_nal_async::{IpAddr, Ipv4Addr};
+
+    use crate::{
+        device::{SocketState, SOCKET_STATE_UNKNOWN, SOCKET_STATE_UNUSED},
+        services::device_mock::{respond, setup_atat},
+        MAX_SOCKETS,
+    };
+
+    use super::*;
+
+    const REMOTE: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)), 443);
+
+    #[tokio::test]
+    async fn can_connect_tls() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        for _ in 0..MAX_SOCKETS {
+            device
+                .handle
+                .socket_state
+                .push(SocketState::new(SOCKET_STATE_UNKNOWN))
+                .unwrap();
+        }
+        device.handle.socket_state[2].store(SOCKET_STATE_UNUSED, Ordering::Relaxed);
+        let data = DataService::new(&device.handle, device.urc_channel);
+
+        let (socket, _) = tokio::join!(
+            data.connect_tls(REMOTE),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[
+                    (b"AT+CIPSSL=1\r", b"\r\nOK\r\n"),
+                    (
+                        b"AT+CIPSTART=2,\"TCP\",\"1.2.3.4\",\"443\"\r",
+                        b"\r\nOK\r\n"
+                    ),
+                    (b"AT+CIPSSL=0\r", b"\r\nOK\r\n\r\n2, CONNECT OK\r\n"),
+                ]
+            )
+        );
+
+        assert_eq!(2, socket.unwrap().id);
+    }
+
+    #[tokio::test]
+    async fn connect_tls_fails_when_ssl_is_rejected() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        for _ in 0..MAX_SOCKETS {
+            device
+                .handle
+                .socket_state
+                .push(SocketState::new(SOCKET_STATE_UNUSED))
+                .unwrap();
+        }
+        let data = DataService::new(&device.handle, device.urc_channel);
+
+        let (socket, _) = tokio::join!(
+            data.connect_tls(REMOTE),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[(b"AT+CIPSSL=1\r", b"\r\nERROR\r\n")]
+            )
+        );
+
+        assert_matches!(socket.err(), Some(SocketError::TlsNotSupported));
+    }
+
+    #[tokio::test]
+    async fn can_disable_certificate_validation() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let data = DataService::new(&device.handle, device.urc_channel);
+
+        let (result, _) = tokio::join!(
+            data.set_tls_certificate_validation(false),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[(b"AT+SSLOPT=0,1\r", b"\r\nOK\r\n")]
+            )
+        );
+
+        result.unwrap();
+    }
+}
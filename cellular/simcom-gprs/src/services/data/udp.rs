@@ -0,0 +1,247 @@
+use core::fmt::Write as _;
+
+use atat::asynch::AtatClient;
+use embedded_nal_async::{ConnectedUdp, SocketAddr};
+use heapless::String;
+
+use crate::commands::tcpip::{SendData, WriteData, MAX_WRITE};
+
+use super::{tcp::TcpSocket, DataService, SocketError};
+
+impl<'buf, 'dev, 'sub, AtCl: AtatClient + 'static> DataService<'buf, 'dev, 'sub, AtCl> {
+    /// Create a UDP socket with a fixed remote address
+    ///
+    /// The modem does not establish anything for UDP, so the socket is usable
+    /// as soon as the modem has accepted the request.
+    pub async fn connect_udp(
+        &self,
+        remote: SocketAddr,
+    ) -> Result<UdpSocket<'buf, 'dev, 'sub, AtCl>, SocketError> {
+        let SocketAddr::V4(remote) = remote else {
+            return Err(SocketError::UnsupportedIpVersion);
+        };
+
+        self.handle.drain_background_urcs();
+
+        // Close any sockets that have been dropped
+        self.handle.close_dropped_sockets().await;
+
+        let mut socket = TcpSocket::try_new(self.handle, self.urc_channel)?;
+        info!("[{}] UDP socket created", socket.id);
+
+        let mut ip = String::<15>::new();
+        write!(ip, "{}", remote.ip()).unwrap();
+
+        let mut port = String::<5>::new();
+        write!(port, "{}", remote.port()).unwrap();
+
+        // The "CONNECT OK" urc that may follow is handled in the background
+        socket.start_connection("UDP", &ip, &port).await?;
+        Ok(UdpSocket { socket })
+    }
+}
+
+/// A UDP socket
+///
+/// The socket shares the connection slot and read handling with [`TcpSocket`],
+/// but preserves the datagram boundaries when sending and receiving.
+pub struct UdpSocket<'buf, 'dev, 'sub, AtCl: AtatClient> {
+    socket: TcpSocket<'buf, 'dev, 'sub, AtCl>,
+}
+
+impl<AtCl: AtatClient + 'static> UdpSocket<'_, '_, '_, AtCl> {
+    /// Send a single datagram
+    ///
+    /// The datagram is never split, so [`SocketError::DatagramTooLarge`] is returned
+    /// if it exceeds the maximum write size.
+    pub async fn send(&mut self, data: &[u8]) -> Result<(), SocketError> {
+        if data.len() > MAX_WRITE {
+            return Err(SocketError::DatagramTooLarge);
+        }
+
+        self.socket.drain_background_urcs_and_ensure_in_use()?;
+
+        let id = self.socket.id;
+        let mut client = self.socket.handle.lock_client().await?;
+        // Hold client all the way from request prompt until DATA ACCEPT is received
+
+        client
+            .send(&SendData {
+                id,
+                len: Some(data.len()),
+            })
+            .await?;
+
+        let response = client
+            .send(&WriteData { buf: data })
+            .await
+            .map_err(|_| SocketError::UnableToWrite)?;
+        if response.accepted != data.len() {
+            error!(
+                "[{}] Only {} out of {} datagram bytes were accepted",
+                id,
+                response.accepted,
+                data.len()
+            );
+            return Err(SocketError::UnableToWrite);
+        }
+
+        Ok(())
+    }
+
+    /// Receive a single datagram
+    ///
+    /// The entire datagram is read from the modem even if it does not fit in `buf`.
+    /// The bytes that do not fit are discarded, so that they are not merged with the next datagram.
+    /// The full datagram length is returned to allow for detection of the truncation.
+    pub async fn receive_into(&mut self, buf: &mut [u8]) -> Result<usize, SocketError> {
+        self.socket.drain_background_urcs_and_ensure_in_use()?;
+
+        let max_len = self.socket.max_read_len();
+        let data = self.socket.try_read_data(max_len).await?;
+
+        let len = usize::min(buf.len(), data.len());
+        buf[..len].copy_from_slice(&data[..len]);
+        if len < data.len() {
+            warn!(
+                "[{}] Truncated datagram of {} bytes to {} bytes",
+                self.socket.id,
+                data.len(),
+                len
+            );
+        }
+
+        Ok(data.len())
+    }
+}
+
+impl<AtCl: AtatClient + 'static> ConnectedUdp for UdpSocket<'_, '_, '_, AtCl> {
+    type Error = SocketError;
+
+    async fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.send(data).await
+    }
+
+    async fn receive_into(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        self.receive_into(buffer).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::sync::atomic::Ordering;
+
+    use atat::AtatIngress;
+    use embassy_time::{with_timeout, Duration};
+    use embedded_nal_async::{IpAddr, Ipv4Addr};
+
+    use crate::{
+        device::{SocketState, SOCKET_STATE_UNKNOWN, SOCKET_STATE_UNUSED, SOCKET_STATE_USED},
+        services::{device_mock::setup_atat, serial_mock::RxMock},
+        SimcomConfig, SimcomDevice, MAX_SOCKETS,
+    };
+
+    use super::*;
+
+    async fn connect<'buf, 'dev, 'sub, AtCl: AtatClient + 'static, Config: SimcomConfig>(
+        ingress: &mut impl AtatIngress,
+        device: &'dev mut SimcomDevice<'buf, 'sub, AtCl, Config>,
+        serial: &mut RxMock<'_>,
+        id: usize,
+    ) -> UdpSocket<'buf, 'dev, 'sub, AtCl> {
+        for _ in 0..MAX_SOCKETS {
+            device
+                .handle
+                .socket_state
+                .push(SocketState::new(SOCKET_STATE_UNKNOWN))
+                .unwrap();
+        }
+        device.handle.socket_state[id].store(SOCKET_STATE_UNUSED, Ordering::Relaxed);
+
+        let data = DataService::new(&device.handle, device.urc_channel);
+
+        let socket = async {
+            data.connect_udp(SocketAddr::new(
+                IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+                8080,
+            ))
+            .await
+            .unwrap()
+        };
+        let sent = async {
+            // Expect StartConnection request
+            let sent = with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                .await
+                .unwrap();
+
+            // No "CONNECT OK" urc is required for the socket to be usable
+            ingress.write(b"\r\nOK\r\n").await;
+
+            sent
+        };
+
+        let (socket, sent) = tokio::join!(socket, sent);
+
+        assert_eq!(
+            format!("AT+CIPSTART={},\"UDP\",\"127.0.0.1\",\"8080\"\r", id).as_bytes(),
+            &sent
+        );
+
+        socket
+    }
+
+    #[tokio::test]
+    async fn can_connect() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+
+        assert_eq!(
+            SOCKET_STATE_USED,
+            socket.socket.handle.socket_state[5].load(Ordering::Relaxed)
+        );
+    }
+
+    #[tokio::test]
+    async fn truncated_datagram_is_not_merged_with_next() {
+        let (mut ingress, mut device, mut serial) = setup_atat!();
+        let mut socket = connect(&mut ingress, &mut device, &mut serial, 5).await;
+
+        let mut buf = [0; 4];
+        let read = async {
+            let first = socket.receive_into(&mut buf).await.unwrap();
+            let first_data = buf;
+            let second = socket.receive_into(&mut buf).await.unwrap();
+            (first, first_data, second)
+        };
+        let sent = async {
+            // Expect ReadData request for the entire datagram
+            let sent0 = with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                .await
+                .unwrap();
+
+            ingress
+                .write(b"\r\n+CIPRXGET: 2,5,8,3\r\nABCDEFGH\r\n")
+                .await;
+            ingress.write(b"\r\nOK\r\n").await;
+
+            // Expect ReadData request for the next datagram
+            let sent1 = with_timeout(Duration::from_millis(100), serial.next_message_pure())
+                .await
+                .unwrap();
+
+            ingress.write(b"\r\n+CIPRXGET: 2,5,3,0\r\nXYZ\r\n").await;
+            ingress.write(b"\r\nOK\r\n").await;
+
+            (sent0, sent1)
+        };
+
+        let ((first, first_data, second), sent) = tokio::join!(read, sent);
+
+        assert_eq!(8, first);
+        assert_eq!(b"ABCD", &first_data);
+        assert_eq!(3, second);
+        assert_eq!(b"XYZD", &buf);
+        assert_eq!(b"AT+CIPRXGET=2,5,94\r", sent.0.as_slice());
+        assert_eq!(b"AT+CIPRXGET=2,5,94\r", sent.1.as_slice());
+    }
+}
@@ -1,13 +1,114 @@
+#[cfg(test)]
+pub(crate) mod device_mock {
+    use core::convert::Infallible;
+
+    use atat::AtatIngress;
+    use embassy_time::{with_timeout, Duration};
+    use embedded_hal::digital::{ErrorType, OutputPin};
+
+    use crate::SimcomConfig;
+
+    use super::serial_mock::RxMock;
+
+    pub struct Config(pub ResetPin);
+    pub struct ResetPin(pub bool);
+
+    impl SimcomConfig for Config {
+        type ResetPin = ResetPin;
+
+        fn reset_pin(&mut self) -> &mut Self::ResetPin {
+            &mut self.0
+        }
+    }
+
+    impl OutputPin for ResetPin {
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.0 = false;
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.0 = true;
+            Ok(())
+        }
+    }
+
+    impl ErrorType for ResetPin {
+        type Error = Infallible;
+    }
+
+    macro_rules! setup_atat {
+        () => {{
+            $crate::services::device_mock::setup_atat!($crate::services::device_mock::Config(
+                $crate::services::device_mock::ResetPin(true),
+            ))
+        }};
+        ($config:expr) => {{
+            let ingress_buf = static_cell::make_static!([0; 128]);
+            static RES_SLOT: $crate::SimcomResponseSlot<128> = $crate::SimcomResponseSlot::new();
+            let device_buf = static_cell::make_static!([0; 128]);
+            static URC_CHANNEL: $crate::SimcomUrcChannel = $crate::SimcomUrcChannel::new();
+            static SERIAL: $crate::services::serial_mock::SerialMock =
+                $crate::services::serial_mock::SerialMock::new();
+            let (tx, rx) = SERIAL.split();
+            let ingress = $crate::SimcomIngress::new(ingress_buf, &RES_SLOT, &URC_CHANNEL);
+            let config = $config;
+            let device = $crate::SimcomDevice::new(tx, &RES_SLOT, device_buf, &URC_CHANNEL, config);
+            (ingress, device, rx)
+        }};
+    }
+
+    pub(crate) use setup_atat;
+
+    /// Expect each command in `exchange` to be sent in order,
+    /// and reply to each of them with the paired response
+    pub async fn respond(
+        ingress: &mut impl AtatIngress,
+        serial: &mut RxMock<'_>,
+        exchange: &[(&[u8], &[u8])],
+    ) {
+        for (cmd, response) in exchange {
+            let sent = with_timeout(Duration::from_millis(1000), serial.next_message_pure())
+                .await
+                .unwrap();
+            assert_eq!(
+                core::str::from_utf8(cmd).unwrap(),
+                core::str::from_utf8(&sent).unwrap()
+            );
+            ingress.write(response).await;
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod ingress_mock {
+    /// Create an ingress for parsing responses in command tests, returning the ingress and its response slot
+    macro_rules! setup_ingress {
+        () => {{
+            let buf = static_cell::make_static!([0; 512]);
+            static RES_SLOT: $crate::SimcomResponseSlot<400> = $crate::SimcomResponseSlot::new();
+            static URC_CHANNEL: $crate::SimcomUrcChannel = $crate::SimcomUrcChannel::new();
+            let ingress = $crate::SimcomIngress::<400>::new(buf, &RES_SLOT, &URC_CHANNEL);
+
+            (ingress, &RES_SLOT)
+        }};
+    }
+
+    pub(crate) use setup_ingress;
+}
+
 pub mod data;
 pub mod network;
+pub mod ussd;
 
 #[cfg(test)]
-mod serial_mock {
+pub(crate) mod serial_mock {
     use core::convert::Infallible;
 
     use alloc::vec::Vec;
     use embassy_sync::{
         blocking_mutex::raw::CriticalSectionRawMutex,
+        channel::Channel,
         pubsub::{PubSubChannel, Publisher, Subscriber},
     };
 
@@ -71,4 +172,19 @@ mod serial_mock {
             Ok(())
         }
     }
+
+    /// Serial port receiving the chunks sent on a channel
+    pub struct ChannelSerial(pub &'static Channel<CriticalSectionRawMutex, &'static [u8], 4>);
+
+    impl embedded_io::ErrorType for ChannelSerial {
+        type Error = Infallible;
+    }
+
+    impl embedded_io_async::Read for ChannelSerial {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            let chunk = self.0.receive().await;
+            buf[..chunk.len()].copy_from_slice(chunk);
+            Ok(chunk.len())
+        }
+    }
 }
@@ -1,16 +1,22 @@
 use atat::{asynch::AtatClient, CmeError};
-use embassy_time::{with_timeout, Duration, Instant, Timer};
+use embassy_time::{Duration, Instant, Timer};
 
 use crate::{
     commands::{
         gprs, gsm,
-        simcom::{CallReady, GetCallReady},
+        simcom::{CallReady, GetCallReady, GetPinRemainingAttempts, PinRemainingAttemptsResponse},
         urc::Urc,
     },
     device::Handle,
     SimcomConfig, SimcomDevice, SimcomUrcChannel,
 };
 
+/// The EF_FPLMN (forbidden networks) elementary file id on the SIM
+const EF_FPLMN: u16 = 0x6F7B;
+const EMPTY_FPLMN_LIST: [u8; 12] = [0xFF; 12];
+
+const REGISTRATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum NetworkError {
@@ -19,12 +25,65 @@ pub enum NetworkError {
     NotReady,
     NotRegistered,
     GprsNotRegistered,
+    /// The network or GPRS registration was denied
+    RegistrationDenied,
+    RegistrationTimeout,
     NotAttached,
     PinRequired,
     PukRequired,
+    /// The pin is not entered automatically when a wrong pin would leave the SIM requiring the PUK,
+    /// see `SimcomConfig::PROTECT_LAST_PIN_ATTEMPT`
+    LastPinAttempt,
     PinTimeout,
     InvalidRssi,
     UnexpectedPinStatus(gsm::PinStatusCode),
+    /// An AT command failed during data service setup
+    Step(SetupStep, atat::Error),
+    PoweredDown,
+    /// The FPLMN list was not empty when read back after it was cleared
+    FplmnNotCleared,
+    /// The network did not reply to the USSD request in time
+    UssdTimeout,
+    /// The modem was unable to attach to GPRS before the command timed out
+    AttachTimeout,
+}
+
+/// The AT command steps performed during data service setup
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SetupStep {
+    DeactivateGprsPdpContext,
+    SetManualRxGetMode,
+    StartMultiIpConnection,
+    DefinePdpContext,
+    StartTaskAndSetApn,
+    BringUpWireless,
+    SetMobileEquipmentError,
+    GetLocalIP,
+    GetPdpAddress,
+    GetConnectionStatus,
+    SelectDataTransmittingMode,
+    ConfigureDomainNameServer,
+}
+
+impl SetupStep {
+    /// Get the AT command name of the step, e.g. "CIICR"
+    pub const fn command(&self) -> &'static str {
+        match self {
+            SetupStep::DeactivateGprsPdpContext => "CIPSHUT",
+            SetupStep::SetManualRxGetMode => "CIPRXGET",
+            SetupStep::StartMultiIpConnection => "CIPMUX",
+            SetupStep::DefinePdpContext => "CGDCONT",
+            SetupStep::StartTaskAndSetApn => "CSTT",
+            SetupStep::BringUpWireless => "CIICR",
+            SetupStep::SetMobileEquipmentError => "CMEE",
+            SetupStep::GetLocalIP => "CIFSR",
+            SetupStep::GetPdpAddress => "CGPADDR",
+            SetupStep::GetConnectionStatus => "CIPSTATUS",
+            SetupStep::SelectDataTransmittingMode => "CIPQSEND",
+            SetupStep::ConfigureDomainNameServer => "CDNSCFG",
+        }
+    }
 }
 
 impl From<atat::Error> for NetworkError {
@@ -36,6 +95,7 @@ impl From<atat::Error> for NetworkError {
 pub struct Network<'dev, 'sub, AtCl: AtatClient> {
     handle: &'dev Handle<'sub, AtCl>,
     urc_channel: &'dev SimcomUrcChannel,
+    protect_last_pin_attempt: bool,
 }
 
 impl<'dev, 'sub, AtCl: AtatClient, Config: SimcomConfig> SimcomDevice<'dev, 'sub, AtCl, Config> {
@@ -43,6 +103,7 @@ impl<'dev, 'sub, AtCl: AtatClient, Config: SimcomConfig> SimcomDevice<'dev, 'sub
         Network {
             handle: &self.handle,
             urc_channel: self.urc_channel,
+            protect_last_pin_attempt: Config::PROTECT_LAST_PIN_ATTEMPT,
         }
     }
 }
@@ -59,12 +120,15 @@ impl<AtCl: AtatClient + 'static> Network<'_, '_, AtCl> {
             gsm::PinStatusCode::Ready => {}
             gsm::PinStatusCode::SimPin => {
                 let pin = pin.ok_or(NetworkError::PinRequired)?;
+                if self.protect_last_pin_attempt && self.is_last_pin_attempt().await? {
+                    return Err(NetworkError::LastPinAttempt);
+                }
                 self.enter_pin(pin).await?;
             }
             _ => return Err(NetworkError::UnexpectedPinStatus(status)),
         }
 
-        let mut client = self.handle.client.lock().await;
+        let mut client = self.handle.lock_client().await?;
 
         // AT+COPS? - Ensure that we are using automatic operator selection
         // See https://onomondo.com/blog/at-command-attach-detach-modems-tips/
@@ -115,6 +179,57 @@ impl<AtCl: AtatClient + 'static> Network<'_, '_, AtCl> {
         Ok(())
     }
 
+    /// Wait for the modem to be registered on the network and for GPRS
+    ///
+    /// The registration status is polled until it is home or roaming.
+    /// A denied registration fails immediately with `NetworkError::RegistrationDenied`.
+    pub async fn wait_for_registration(&mut self, timeout: Duration) -> Result<(), NetworkError> {
+        let timeout_instant = Instant::now() + timeout;
+
+        // AT+CREG?
+        loop {
+            let stat = {
+                let mut client = self.handle.lock_client().await?;
+                client.send(&gsm::GetNetworkRegistrationStatus).await?.stat
+            };
+            match stat {
+                _ if stat.is_registered() => break,
+                gsm::NetworkRegistrationStat::RegistrationDenied => {
+                    return Err(NetworkError::RegistrationDenied)
+                }
+                _ => Self::wait_for_next_poll(timeout_instant).await?,
+            }
+        }
+
+        // AT+CGREG?
+        loop {
+            let stat = {
+                let mut client = self.handle.lock_client().await?;
+                client
+                    .send(&gprs::GetGPRSNetworkRegistrationStatus)
+                    .await?
+                    .stat
+            };
+            match stat {
+                _ if stat.is_registered() => return Ok(()),
+                gprs::GPRSNetworkRegistrationStat::RegistrationDenied => {
+                    return Err(NetworkError::RegistrationDenied)
+                }
+                _ => Self::wait_for_next_poll(timeout_instant).await?,
+            }
+        }
+    }
+
+    async fn wait_for_next_poll(timeout_instant: Instant) -> Result<(), NetworkError> {
+        let next_poll = Instant::now() + REGISTRATION_POLL_INTERVAL;
+        if next_poll > timeout_instant {
+            return Err(NetworkError::RegistrationTimeout);
+        }
+
+        Timer::at(next_poll).await;
+        Ok(())
+    }
+
     async fn attach_inner(client: &mut AtCl) -> Result<(), NetworkError> {
         for _ in 0..30 {
             match client
@@ -140,7 +255,7 @@ impl<AtCl: AtatClient + 'static> Network<'_, '_, AtCl> {
     }
 
     async fn ensure_ready(&mut self) -> Result<(), NetworkError> {
-        let mut client = self.handle.client.lock().await;
+        let mut client = self.handle.lock_client().await?;
         for _ in 0..20 {
             let response = client.send(&GetCallReady).await?;
             if response.ready == CallReady::Ready {
@@ -155,11 +270,11 @@ impl<AtCl: AtatClient + 'static> Network<'_, '_, AtCl> {
     /// Read the FPLMN (forbidden network) list
     /// See e.g. https://onomondo.com/blog/how-to-clear-the-fplmn-list-on-a-sim/
     pub async fn get_fplmn_list(&mut self) -> Result<[u8; 12], NetworkError> {
-        let mut client = self.handle.client.lock().await;
+        let mut client = self.handle.lock_client().await?;
         let response = client
             .send(&gsm::RestrictedSimAccess {
                 command: gsm::RestrictedSimAccessCommand::ReadBinary,
-                file_id: 28539,
+                file_id: EF_FPLMN,
                 p0: Some(0),
                 p1: Some(0),
                 p2: Some(12),
@@ -176,24 +291,78 @@ impl<AtCl: AtatClient + 'static> Network<'_, '_, AtCl> {
 
     /// Clear the FPLMN (forbidden network) list
     /// See e.g. https://onomondo.com/blog/how-to-clear-the-fplmn-list-on-a-sim/
+    ///
+    /// The list is only written if it is not already empty,
+    /// and it is read back after the write to verify that it was cleared.
     pub async fn clear_fplmn_list(&mut self) -> Result<(), NetworkError> {
-        let mut client = self.handle.client.lock().await;
+        if self.get_fplmn_list().await? == EMPTY_FPLMN_LIST {
+            return Ok(());
+        }
+
+        {
+            let mut client = self.handle.lock_client().await?;
+            client
+                .send(&gsm::RestrictedSimAccess {
+                    command: gsm::RestrictedSimAccessCommand::UpdateBinary,
+                    file_id: EF_FPLMN,
+                    p0: Some(0),
+                    p1: Some(0),
+                    p2: Some(12),
+                    data: Some("FFFFFFFFFFFFFFFFFFFFFFFF"),
+                })
+                .await?;
+        }
+
+        if self.get_fplmn_list().await? != EMPTY_FPLMN_LIST {
+            return Err(NetworkError::FplmnNotCleared);
+        }
+
+        Ok(())
+    }
+
+    /// Attach to the GPRS service using `AT+CGATT=1`
+    ///
+    /// `NetworkError::AttachTimeout` is returned if the modem was unable to attach.
+    pub async fn attach_gprs(&mut self) -> Result<(), NetworkError> {
+        let mut client = self.handle.lock_client().await?;
+        match client
+            .send(&gprs::SetGPRSAttached {
+                state: gprs::GPRSAttachedState::Attached,
+            })
+            .await
+        {
+            Ok(_) => Ok(()),
+            // sim800 reports CME ERROR 100 if it was unable to attach, sim900 simply times out
+            Err(atat::Error::CmeError(CmeError::Unknown) | atat::Error::Timeout) => {
+                Err(NetworkError::AttachTimeout)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Detach from the GPRS service using `AT+CGATT=0`
+    ///
+    /// This can be used to force a clean re-registration if the modem is stuck.
+    pub async fn detach_gprs(&mut self) -> Result<(), NetworkError> {
+        let mut client = self.handle.lock_client().await?;
         client
-            .send(&gsm::RestrictedSimAccess {
-                command: gsm::RestrictedSimAccessCommand::UpdateBinary,
-                file_id: 28539,
-                p0: Some(0),
-                p1: Some(0),
-                p2: Some(12),
-                data: Some("FFFFFFFFFFFFFFFFFFFFFFFF"),
+            .send(&gprs::SetGPRSAttached {
+                state: gprs::GPRSAttachedState::Detached,
             })
             .await?;
         Ok(())
     }
 
+    /// Get whether the modem is attached to the GPRS service
+    pub async fn is_gprs_attached(&mut self) -> Result<bool, NetworkError> {
+        let mut client = self.handle.lock_client().await?;
+        let response = client.send(&gprs::GetGPRSAttached).await?;
+        Ok(response.state == gprs::GPRSAttachedState::Attached)
+    }
+
     /// Get the current signal quality from modem
     pub async fn get_signal_quality(&self) -> Result<i8, NetworkError> {
-        let mut client = self.handle.client.lock().await;
+        let mut client = self.handle.lock_client().await?;
         client
             .send(&gsm::GetSignalQuality)
             .await?
@@ -203,53 +372,75 @@ impl<AtCl: AtatClient + 'static> Network<'_, '_, AtCl> {
 
     /// Get the pin status
     pub async fn get_pin_status(&mut self) -> Result<gsm::PinStatusCode, NetworkError> {
-        let mut urc_subscription = {
-            let mut client = self.handle.client.lock().await;
-            let subscription = self.urc_channel.subscribe().unwrap();
-
-            client.send(&gsm::GetPinStatus).await?;
-
-            subscription
-        };
+        self.handle.get_pin_status(self.urc_channel).await
+    }
 
-        let timeout_instant = Instant::now() + Duration::from_secs(5);
-        while let Some(remaining) = timeout_instant.checked_duration_since(Instant::now()) {
-            let urc = with_timeout(remaining, urc_subscription.next_message_pure())
-                .await
-                .map_err(|_| NetworkError::PinTimeout)?;
-            self.handle.drain_background_urcs();
+    /// Get the number of remaining attempts to enter the pin and puk codes
+    pub async fn get_pin_remaining_attempts(
+        &mut self,
+    ) -> Result<PinRemainingAttemptsResponse, NetworkError> {
+        let mut client = self.handle.lock_client().await?;
+        Ok(client.send(&GetPinRemainingAttempts).await?)
+    }
 
-            if let Urc::PinStatus(status) = urc {
-                return Ok(status.code);
+    /// Check if only one attempt remains to enter the pin
+    ///
+    /// The check is skipped if the modem does not support AT+SPIC.
+    async fn is_last_pin_attempt(&mut self) -> Result<bool, NetworkError> {
+        // AT+SPIC
+        match self.get_pin_remaining_attempts().await {
+            Ok(response) => Ok(response.pin1 <= 1),
+            Err(NetworkError::Atat(atat::Error::Error)) => {
+                warn!("Unable to get the remaining pin attempts");
+                Ok(false)
             }
+            Err(e) => Err(e),
         }
-
-        Err(NetworkError::PinTimeout)
     }
 
-    async fn enter_pin(&mut self, pin: &str) -> Result<gsm::PinStatusCode, NetworkError> {
-        let mut urc_subscription = {
-            let mut client = self.handle.client.lock().await;
-            let subscription = self.urc_channel.subscribe().unwrap();
-
-            client.send(&gsm::EnterPin { pin }).await?;
+    /// Enter the pin, returning the pin status after it is entered
+    ///
+    /// Use [`Network::get_pin_remaining_attempts()`] before entering a pin that is not known to be correct.
+    pub async fn enter_pin(&mut self, pin: &str) -> Result<gsm::PinStatusCode, NetworkError> {
+        self.handle
+            .send_and_await_urc(
+                self.urc_channel,
+                &gsm::EnterPin { pin },
+                Duration::from_secs(5),
+                NetworkError::PinTimeout,
+                |urc| match urc {
+                    Urc::PinStatus(status) => Some(status.code),
+                    _ => None,
+                },
+            )
+            .await
+    }
 
-            subscription
-        };
+    /// Recover a SIM that requires the puk by entering the puk and a new pin
+    pub async fn enter_puk(&mut self, puk: &str, new_pin: &str) -> Result<(), NetworkError> {
+        let status = self.get_pin_status().await?;
+        if status != gsm::PinStatusCode::SimPuk {
+            return Err(NetworkError::UnexpectedPinStatus(status));
+        }
 
-        let timeout_instant = Instant::now() + Duration::from_secs(5);
-        while let Some(remaining) = timeout_instant.checked_duration_since(Instant::now()) {
-            let urc = with_timeout(remaining, urc_subscription.next_message_pure())
-                .await
-                .map_err(|_| NetworkError::PinTimeout)?;
-            self.handle.drain_background_urcs();
+        let status = self
+            .handle
+            .send_and_await_urc(
+                self.urc_channel,
+                &gsm::EnterPuk { puk, new_pin },
+                Duration::from_secs(5),
+                NetworkError::PinTimeout,
+                |urc| match urc {
+                    Urc::PinStatus(status) => Some(status.code),
+                    _ => None,
+                },
+            )
+            .await?;
 
-            if let Urc::PinStatus(status) = urc {
-                return Ok(status.code);
-            }
+        if status != gsm::PinStatusCode::Ready {
+            return Err(NetworkError::UnexpectedPinStatus(status));
         }
-
-        Err(NetworkError::PinTimeout)
+        Ok(())
     }
 
     pub async fn set_pin(
@@ -266,7 +457,7 @@ impl<AtCl: AtatClient + 'static> Network<'_, '_, AtCl> {
             gsm::PinStatusCode::Ready => {
                 let old_pin = old_pin_or_puk;
 
-                let mut client = self.handle.client.lock().await;
+                let mut client = self.handle.lock_client().await?;
                 client
                     .send(&gsm::ChangePassword {
                         facility: gsm::Facility::SC,
@@ -278,7 +469,7 @@ impl<AtCl: AtatClient + 'static> Network<'_, '_, AtCl> {
             }
             gsm::PinStatusCode::SimPuk => {
                 let puk = old_pin_or_puk;
-                let mut client = self.handle.client.lock().await;
+                let mut client = self.handle.lock_client().await?;
                 client
                     .send(&gsm::ChangePin {
                         password: puk,
@@ -297,7 +488,7 @@ impl<AtCl: AtatClient + 'static> Network<'_, '_, AtCl> {
             return Err(NetworkError::UnexpectedPinStatus(status));
         }
 
-        let mut client = self.handle.client.lock().await;
+        let mut client = self.handle.lock_client().await?;
         client
             .send(&gsm::SetFacilityLock {
                 facility: gsm::Facility::SC,
@@ -318,7 +509,7 @@ impl<AtCl: AtatClient + 'static> Network<'_, '_, AtCl> {
             return Err(NetworkError::UnexpectedPinStatus(status));
         }
 
-        let mut client = self.handle.client.lock().await;
+        let mut client = self.handle.lock_client().await?;
         client
             .send(&gsm::SetFacilityLock {
                 facility: gsm::Facility::SC,
@@ -330,3 +521,287 @@ impl<AtCl: AtatClient + 'static> Network<'_, '_, AtCl> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use core::assert_matches::assert_matches;
+
+    use crate::services::device_mock::{respond, setup_atat, ResetPin};
+
+    use super::*;
+
+    const READ_FPLMN: &[u8] = b"AT+CRSM=176,28539,0,0,12\r";
+    const UPDATE_FPLMN: &[u8] = b"AT+CRSM=214,28539,0,0,12,\"FFFFFFFFFFFFFFFFFFFFFFFF\"\r";
+    const FORBIDDEN: &[u8] = b"\r\n+CRSM: 144,0,\"42F61842F628FFFFFFFFFFFF\"\r\n\r\nOK\r\n";
+    const EMPTY: &[u8] = b"\r\n+CRSM: 144,0,\"FFFFFFFFFFFFFFFFFFFFFFFF\"\r\n\r\nOK\r\n";
+
+    #[tokio::test]
+    async fn can_attach_and_detach_gprs() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut network = device.network();
+
+        let (result, _) = tokio::join!(
+            async {
+                network.attach_gprs().await?;
+                let attached = network.is_gprs_attached().await?;
+                network.detach_gprs().await?;
+                Ok::<_, NetworkError>(attached)
+            },
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[
+                    (b"AT+CGATT=1\r", b"\r\nOK\r\n"),
+                    (b"AT+CGATT?\r", b"\r\n+CGATT: 1\r\n\r\nOK\r\n"),
+                    (b"AT+CGATT=0\r", b"\r\nOK\r\n"),
+                ]
+            )
+        );
+
+        assert!(result.unwrap());
+        assert!(serial.try_next_message_pure().is_none());
+    }
+
+    #[tokio::test]
+    async fn attach_gprs_maps_cme_error_100_to_attach_timeout() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut network = device.network();
+
+        let (result, _) = tokio::join!(
+            network.attach_gprs(),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[(b"AT+CGATT=1\r", b"\r\n+CME ERROR: 100\r\n")]
+            )
+        );
+
+        assert_matches!(result, Err(NetworkError::AttachTimeout));
+    }
+
+    #[tokio::test]
+    async fn can_clear_fplmn_list() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut network = device.network();
+
+        let (result, _) = tokio::join!(
+            network.clear_fplmn_list(),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[
+                    (READ_FPLMN, FORBIDDEN),
+                    (UPDATE_FPLMN, b"\r\n+CRSM: 144,0\r\n\r\nOK\r\n"),
+                    (READ_FPLMN, EMPTY),
+                ]
+            )
+        );
+
+        result.unwrap();
+        assert!(serial.try_next_message_pure().is_none());
+    }
+
+    #[tokio::test]
+    async fn clear_fplmn_list_skips_update_when_empty() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut network = device.network();
+
+        let (result, _) = tokio::join!(
+            network.clear_fplmn_list(),
+            respond(&mut ingress, &mut serial, &[(READ_FPLMN, EMPTY)])
+        );
+
+        result.unwrap();
+        assert!(serial.try_next_message_pure().is_none());
+    }
+
+    #[tokio::test]
+    async fn clear_fplmn_list_fails_when_not_cleared() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut network = device.network();
+
+        let (result, _) = tokio::join!(
+            network.clear_fplmn_list(),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[
+                    (READ_FPLMN, FORBIDDEN),
+                    (UPDATE_FPLMN, b"\r\n+CRSM: 144,0\r\n\r\nOK\r\n"),
+                    (READ_FPLMN, FORBIDDEN),
+                ]
+            )
+        );
+
+        assert_matches!(result, Err(NetworkError::FplmnNotCleared));
+    }
+
+    struct ProtectLastPinAttemptConfig(ResetPin);
+
+    impl SimcomConfig for ProtectLastPinAttemptConfig {
+        type ResetPin = ResetPin;
+
+        const PROTECT_LAST_PIN_ATTEMPT: bool = true;
+
+        fn reset_pin(&mut self) -> &mut Self::ResetPin {
+            &mut self.0
+        }
+    }
+
+    #[tokio::test]
+    async fn attach_enters_pin_on_last_attempt_by_default() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut network = device.network();
+
+        let (result, _) = tokio::join!(
+            network.attach(Some("1234")),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[
+                    (b"AT+CCALR?\r", b"\r\n+CCALR: 1\r\n\r\nOK\r\n"),
+                    (b"AT+CPIN?\r", b"\r\nOK\r\n\r\n+CPIN: SIM PIN\r\n"),
+                    (b"AT+CPIN=\"1234\"\r", b"\r\nOK\r\n\r\n+CPIN: READY\r\n"),
+                    // Stop the attach after the pin is entered
+                    (b"AT+COPS?\r", b"\r\nERROR\r\n"),
+                ]
+            )
+        );
+
+        assert_matches!(result, Err(NetworkError::Atat(atat::Error::Error)));
+        assert!(serial.try_next_message_pure().is_none());
+    }
+
+    #[tokio::test]
+    async fn attach_enters_pin_when_remaining_attempts_are_unsupported() {
+        let (mut ingress, device, mut serial) =
+            setup_atat!(ProtectLastPinAttemptConfig(ResetPin(true)));
+        let mut network = device.network();
+
+        let (result, _) = tokio::join!(
+            network.attach(Some("1234")),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[
+                    (b"AT+CCALR?\r", b"\r\n+CCALR: 1\r\n\r\nOK\r\n"),
+                    (b"AT+CPIN?\r", b"\r\nOK\r\n\r\n+CPIN: SIM PIN\r\n"),
+                    (b"AT+SPIC\r", b"\r\nERROR\r\n"),
+                    (b"AT+CPIN=\"1234\"\r", b"\r\nOK\r\n\r\n+CPIN: READY\r\n"),
+                    // Stop the attach after the pin is entered
+                    (b"AT+COPS?\r", b"\r\nERROR\r\n"),
+                ]
+            )
+        );
+
+        assert_matches!(result, Err(NetworkError::Atat(atat::Error::Error)));
+        assert!(serial.try_next_message_pure().is_none());
+    }
+
+    #[tokio::test]
+    async fn attach_does_not_enter_pin_on_last_attempt() {
+        let (mut ingress, device, mut serial) =
+            setup_atat!(ProtectLastPinAttemptConfig(ResetPin(true)));
+        let mut network = device.network();
+
+        let (result, _) = tokio::join!(
+            network.attach(Some("1234")),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[
+                    (b"AT+CCALR?\r", b"\r\n+CCALR: 1\r\n\r\nOK\r\n"),
+                    (b"AT+CPIN?\r", b"\r\nOK\r\n\r\n+CPIN: SIM PIN\r\n"),
+                    (b"AT+SPIC\r", b"\r\n+SPIC: 1,3,10,10\r\n\r\nOK\r\n"),
+                ]
+            )
+        );
+
+        assert_matches!(result, Err(NetworkError::LastPinAttempt));
+        assert!(serial.try_next_message_pure().is_none());
+    }
+
+    #[tokio::test]
+    async fn can_enter_puk() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut network = device.network();
+
+        let (result, _) = tokio::join!(
+            network.enter_puk("12345678", "4321"),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[
+                    (b"AT+CPIN?\r", b"\r\nOK\r\n\r\n+CPIN: SIM PUK\r\n"),
+                    (
+                        b"AT+CPIN=\"12345678\",\"4321\"\r",
+                        b"\r\nOK\r\n\r\n+CPIN: READY\r\n"
+                    ),
+                ]
+            )
+        );
+
+        result.unwrap();
+    }
+
+    #[tokio::test]
+    async fn can_wait_for_registration() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut network = device.network();
+
+        let (result, _) = tokio::join!(
+            network.wait_for_registration(Duration::from_secs(5)),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[
+                    (b"AT+CREG?\r", b"\r\n+CREG: 0,2\r\n\r\nOK\r\n"),
+                    (b"AT+CREG?\r", b"\r\n+CREG: 0,5\r\n\r\nOK\r\n"),
+                    (b"AT+CGREG?\r", b"\r\n+CGREG: 0,1\r\n\r\nOK\r\n"),
+                ]
+            )
+        );
+
+        result.unwrap();
+        assert!(serial.try_next_message_pure().is_none());
+    }
+
+    #[tokio::test]
+    async fn wait_for_registration_fails_when_denied() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut network = device.network();
+
+        let (result, _) = tokio::join!(
+            network.wait_for_registration(Duration::from_secs(5)),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[(b"AT+CREG?\r", b"\r\n+CREG: 0,3\r\n\r\nOK\r\n")]
+            )
+        );
+
+        assert_matches!(result, Err(NetworkError::RegistrationDenied));
+        assert!(serial.try_next_message_pure().is_none());
+    }
+
+    #[tokio::test]
+    async fn wait_for_registration_times_out() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let mut network = device.network();
+
+        let (result, _) = tokio::join!(
+            network.wait_for_registration(Duration::from_millis(700)),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[
+                    (b"AT+CREG?\r", b"\r\n+CREG: 0,2\r\n\r\nOK\r\n"),
+                    (b"AT+CREG?\r", b"\r\n+CREG: 0,2\r\n\r\nOK\r\n"),
+                ]
+            )
+        );
+
+        assert_matches!(result, Err(NetworkError::RegistrationTimeout));
+        assert!(serial.try_next_message_pure().is_none());
+    }
+}
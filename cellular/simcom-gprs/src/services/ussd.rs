@@ -0,0 +1,101 @@
+use atat::asynch::AtatClient;
+use embassy_time::Duration;
+use heapless::String;
+
+use crate::{
+    commands::{
+        urc::Urc,
+        ussd::{SendUssd, MAX_USSD_LEN},
+    },
+    device::Handle,
+    SimcomConfig, SimcomDevice, SimcomUrcChannel,
+};
+
+use super::network::NetworkError;
+
+pub struct UssdService<'dev, 'sub, AtCl: AtatClient> {
+    handle: &'dev Handle<'sub, AtCl>,
+    urc_channel: &'dev SimcomUrcChannel,
+}
+
+impl<'dev, 'sub, AtCl: AtatClient, Config: SimcomConfig> SimcomDevice<'dev, 'sub, AtCl, Config> {
+    pub fn ussd(&'dev self) -> UssdService<'dev, 'sub, AtCl> {
+        UssdService {
+            handle: &self.handle,
+            urc_channel: self.urc_channel,
+        }
+    }
+}
+
+impl<AtCl: AtatClient + 'static> UssdService<'_, '_, AtCl> {
+    /// Send an USSD code, e.g. "*100#" to check the balance of a prepaid SIM,
+    /// and wait for the network reply
+    ///
+    /// The reply is reported in a "+CUSD" URC after OK, usually within a few seconds.
+    pub async fn query(
+        &self,
+        code: &str,
+        timeout: Duration,
+    ) -> Result<String<MAX_USSD_LEN>, NetworkError> {
+        self.handle.drain_background_urcs();
+
+        self.handle
+            .send_and_await_urc(
+                self.urc_channel,
+                &SendUssd { code, dcs: None },
+                timeout,
+                NetworkError::UssdTimeout,
+                |urc| match urc {
+                    Urc::UssdResponse(text) => Some(text),
+                    _ => None,
+                },
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::assert_matches::assert_matches;
+
+    use crate::services::device_mock::{respond, setup_atat};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn can_query() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let ussd = device.ussd();
+
+        let (result, _) = tokio::join!(
+            ussd.query("*100#", Duration::from_secs(1)),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[(
+                    b"AT+CUSD=1,\"*100#\"\r",
+                    b"\r\nOK\r\n\r\n+CUSD: 0,\"Your balance is 12.50 DKK\",15\r\n"
+                )]
+            )
+        );
+
+        assert_eq!("Your balance is 12.50 DKK", result.unwrap());
+    }
+
+    #[tokio::test]
+    async fn query_times_out_without_reply() {
+        let (mut ingress, device, mut serial) = setup_atat!();
+        let ussd = device.ussd();
+
+        let (result, _) = tokio::join!(
+            ussd.query("*100#", Duration::from_millis(100)),
+            respond(
+                &mut ingress,
+                &mut serial,
+                &[(b"AT+CUSD=1,\"*100#\"\r", b"\r\nOK\r\n")]
+            )
+        );
+
+        assert_matches!(result, Err(NetworkError::UssdTimeout));
+    }
+}
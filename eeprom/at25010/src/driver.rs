@@ -2,8 +2,9 @@ use crate::{opcode::Opcode, Error, PartNumber};
 use bitfield::bitfield;
 use embedded_hal_async::{delay, spi};
 use embedded_storage::nor_flash::ErrorType;
-use embedded_storage_async::nor_flash::{NorFlash, ReadNorFlash};
+use embedded_storage_async::nor_flash::{MultiwriteNorFlash, NorFlash, ReadNorFlash};
 
+/// The page size shared by all supported parts, see [`PartNumber::page_size()`].
 const PAGE_SIZE: usize = 8;
 
 bitfield! {
@@ -19,8 +20,31 @@ bitfield! {
     pub bsy, _: 0;
 }
 
+impl StatusRegister {
+    /// Get the block write protection level
+    pub const fn block_protection(&self) -> BlockProtection {
+        match (self.0 >> 2) & 0b11 {
+            0b00 => BlockProtection::None,
+            0b01 => BlockProtection::UpperQuarter,
+            0b10 => BlockProtection::UpperHalf,
+            _ => BlockProtection::All,
+        }
+    }
+}
+
+/// The array region protected by the BP0 and BP1 bits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BlockProtection {
+    None = 0b00,
+    UpperQuarter = 0b01,
+    UpperHalf = 0b10,
+    All = 0b11,
+}
+
 const INITIAL_TIMEOUT_MS: u32 = 3; // Wait at least 3 ms
 const RETRY_INTERVAL_US: u32 = 100;
+const DEFAULT_WRITE_TIMEOUT_MS: u32 = 10;
 
 pub struct Driver<SpiDevice, Delay>
 where
@@ -30,8 +54,13 @@ where
     spi: SpiDevice,
     delay: Delay,
     part_number: PartNumber,
+    write_timeout_ms: u32,
 }
 
+/// A [`Driver`] that reads and writes sequentially from a current position.
+///
+/// Both drivers are async and use the `embedded-hal-async` spi device and delay,
+/// so the executor is not blocked while polling for write completion.
 pub struct StatefulDriver<SpiDevice, Delay>
 where
     SpiDevice: spi::SpiDevice,
@@ -47,10 +76,23 @@ where
     Delay: delay::DelayNs,
 {
     pub const fn new(spi: SpiDevice, delay: Delay, part_number: PartNumber) -> Self {
+        Self::new_with_write_timeout(spi, delay, part_number, DEFAULT_WRITE_TIMEOUT_MS)
+    }
+
+    /// Create a driver that fails with [`Error::WriteTimeout`] if a write cycle
+    /// does not complete within `write_timeout_ms`.
+    /// The default used by [`Driver::new()`] is 10 ms.
+    pub const fn new_with_write_timeout(
+        spi: SpiDevice,
+        delay: Delay,
+        part_number: PartNumber,
+        write_timeout_ms: u32,
+    ) -> Self {
         Self {
             part_number,
             spi,
             delay,
+            write_timeout_ms,
         }
     }
 
@@ -73,6 +115,65 @@ where
         }
     }
 
+    /// Get the part number the driver is currently configured for
+    pub const fn part_number(&self) -> PartNumber {
+        self.part_number
+    }
+
+    /// Probe the EEPROM capacity and configure the driver for the matching part.
+    ///
+    /// The capacity is inferred by writing a marker to address 0 and detecting at which
+    /// address the memory array wraps around. The original content at address 0 is restored.
+    /// As the B series parts cannot be distinguished, the non-B part with the more conservative timing is selected.
+    /// `None` is returned if the marker could not be written, e.g. if the EEPROM is write protected.
+    pub async fn probe(&mut self) -> Option<PartNumber> {
+        let configured = self.part_number;
+        self.part_number = PartNumber::At25040;
+
+        let probed = self.probe_capacity().await;
+        self.part_number = probed.unwrap_or(configured);
+        probed
+    }
+
+    async fn probe_capacity(&mut self) -> Option<PartNumber> {
+        let mut original = [0; 1];
+        let mut at_128 = [0; 1];
+        let mut at_256 = [0; 1];
+        self.read(0, &mut original).await.ok()?;
+        self.read(128, &mut at_128).await.ok()?;
+        self.read(256, &mut at_256).await.ok()?;
+
+        // Select a marker that is different from the content at all candidate wraparound addresses
+        let marker = (0..=u8::MAX).find(|x| ![original[0], at_128[0], at_256[0]].contains(x))?;
+        self.write(0, &[marker]).await.ok()?;
+        self.flush().await.ok()?;
+
+        let mut readback = [0; 1];
+        self.read(0, &mut readback).await.ok()?;
+        if readback[0] != marker {
+            return None;
+        }
+
+        let part_number = if self.read_equals(128, marker).await? {
+            PartNumber::At25010
+        } else if self.read_equals(256, marker).await? {
+            PartNumber::At25020
+        } else {
+            PartNumber::At25040
+        };
+
+        self.write(0, &original).await.ok()?;
+        self.flush().await.ok()?;
+
+        Some(part_number)
+    }
+
+    async fn read_equals(&mut self, address: u16, value: u8) -> Option<bool> {
+        let mut buf = [0; 1];
+        self.read(address, &mut buf).await.ok()?;
+        Some(buf[0] == value)
+    }
+
     /// Read a sequence of bytes from the EEPROM.
     pub async fn read(&mut self, origin: u16, buffer: &mut [u8]) -> Result<(), Error> {
         if origin as usize + buffer.len() > self.capacity() as usize {
@@ -91,14 +192,24 @@ where
 
     /// Write a sequence of bytes to the EEPROM.
     pub async fn write(&mut self, origin: u16, buffer: &[u8]) -> Result<(), Error> {
+        self.write_page(origin, buffer).await
+    }
+
+    /// Write a sequence of bytes to the EEPROM using page writes.
+    ///
+    /// The data is split at the page boundaries of the configured part.
+    /// Each page is written with its own WREN and WRITE command, and the status register
+    /// is polled until the previous page write has completed before the next is started.
+    pub async fn write_page(&mut self, origin: u16, buffer: &[u8]) -> Result<(), Error> {
         if origin as usize + buffer.len() > self.capacity() as usize {
             return Err(Error::OutOfBounds);
         }
 
+        let page_size = self.part_number.page_size();
         let t_cs_us = (min_tcs_ns(self.part_number) + 999) / 1000;
 
         // Wait for a possible previous write to complete.
-        self.flush().await?;
+        self.wait_write_complete().await?;
 
         // Disable write protection.
         self.enable_write().await?;
@@ -114,32 +225,33 @@ where
 
         let mut address = origin;
         let mut flushed_and_write_enabled = true;
-        let offset_in_first_page = origin as usize % PAGE_SIZE;
+        let offset_in_first_page = origin as usize % page_size;
         let (incomplete_first_page, remaining_pages) =
-            buffer.split_at((PAGE_SIZE - offset_in_first_page) % PAGE_SIZE);
+            buffer.split_at(((page_size - offset_in_first_page) % page_size).min(buffer.len()));
 
-        assert!(incomplete_first_page.len() < 8);
+        assert!(incomplete_first_page.len() < page_size);
         if !incomplete_first_page.is_empty() {
             // Wait until we can send a new spi command.
             self.delay.delay_us(t_cs_us).await;
 
-            self.write_page(address, incomplete_first_page).await?;
+            self.write_within_page(address, incomplete_first_page)
+                .await?;
             address += incomplete_first_page.len() as u16;
 
             // Write is auto-disabled after sending a WRITE command.
             flushed_and_write_enabled = false;
         }
 
-        for page in remaining_pages.chunks(PAGE_SIZE) {
+        for page in remaining_pages.chunks(page_size) {
             if !flushed_and_write_enabled {
-                self.flush().await?;
+                self.wait_write_complete().await?;
                 self.enable_write().await?;
             }
 
             // Wait until we can send a new spi command.
             self.delay.delay_us(t_cs_us).await;
 
-            self.write_page(address, page).await?;
+            self.write_within_page(address, page).await?;
             address += page.len() as u16;
 
             // Write is auto-disabled after sending a WRITE command.
@@ -151,27 +263,68 @@ where
         Ok(())
     }
 
+    /// Wait for a pending write to complete.
     pub async fn flush(&mut self) -> Result<(), Error> {
+        self.wait_write_complete().await
+    }
+
+    /// Poll the busy bit until the write cycle completes or the write timeout expires.
+    async fn wait_write_complete(&mut self) -> Result<(), Error> {
         let sr = self.read_status_register().await?;
         if !sr.bsy() {
             return Ok(());
         }
 
         // Wait for idle.
-        self.delay.delay_ms(INITIAL_TIMEOUT_MS).await;
-        let sr = self.read_status_register().await?;
-        if sr.bsy() {
-            loop {
-                self.delay.delay_us(RETRY_INTERVAL_US).await;
+        let initial_ms = INITIAL_TIMEOUT_MS.min(self.write_timeout_ms);
+        self.delay.delay_ms(initial_ms).await;
+        let mut waited_us = initial_ms * 1000;
+        loop {
+            let sr = self.read_status_register().await?;
+            if !sr.bsy() {
+                return Ok(());
+            }
 
-                let sr = self.read_status_register().await?;
-                if !sr.bsy() {
-                    break;
-                }
+            if waited_us >= self.write_timeout_ms.saturating_mul(1000) {
+                return Err(Error::WriteTimeout);
             }
+
+            self.delay.delay_us(RETRY_INTERVAL_US).await;
+            waited_us = waited_us.saturating_add(RETRY_INTERVAL_US);
         }
+    }
 
-        Ok(())
+    /// Read the status register.
+    pub async fn read_status(&mut self) -> Result<StatusRegister, Error> {
+        self.read_status_register().await
+    }
+
+    /// Write protect a region of the memory array.
+    ///
+    /// The protection is stored in the non-volatile BP0 and BP1 bits of the status register,
+    /// and the method waits for the write to complete.
+    pub async fn set_block_protection(&mut self, level: BlockProtection) -> Result<(), Error> {
+        let t_cs_us = (min_tcs_ns(self.part_number) + 999) / 1000;
+
+        self.wait_write_complete().await?;
+        self.enable_write().await?;
+
+        // Wait until we can send a new spi command.
+        self.delay.delay_us(t_cs_us).await;
+
+        // See if write was enabled (it may have been disabled by the WP pin).
+        let sr = self.read_status_register().await?;
+        if !sr.wel() {
+            return Err(Error::WriteProtection);
+        }
+
+        // Wait until we can send a new spi command.
+        self.delay.delay_us(t_cs_us).await;
+
+        let tx = [Opcode::WRSR.as_u8(), (level as u8) << 2];
+        self.spi.write(&tx).await?;
+
+        self.wait_write_complete().await
     }
 
     async fn enable_write(&mut self) -> Result<(), Error> {
@@ -187,10 +340,19 @@ where
         Ok(StatusRegister(rx[1]))
     }
 
-    async fn write_page(&mut self, address: u16, buffer: &[u8]) -> Result<(), Error> {
+    /// Convert a storage trait offset to an EEPROM address without truncating it.
+    fn storage_origin(&self, offset: u32) -> Result<u16, Error> {
+        if offset > self.capacity() as u32 {
+            return Err(Error::OutOfBounds);
+        }
+        Ok(offset as u16)
+    }
+
+    async fn write_within_page(&mut self, address: u16, buffer: &[u8]) -> Result<(), Error> {
+        let page_size = self.part_number.page_size();
         let len = buffer.len();
         assert!(len > 0);
-        assert!(len <= PAGE_SIZE - (address as usize % PAGE_SIZE));
+        assert!(len <= page_size - (address as usize % page_size));
 
         self.spi
             .transaction(&mut [
@@ -203,6 +365,31 @@ where
     }
 }
 
+impl<SpiDevice, Delay> StatefulDriver<SpiDevice, Delay>
+where
+    SpiDevice: spi::SpiDevice,
+    Delay: delay::DelayNs,
+{
+    /// Read bytes from the current position and advance it.
+    pub async fn read(&mut self, buffer: &mut [u8]) -> Result<(), Error> {
+        self.driver.read(self.position, buffer).await?;
+        self.position += buffer.len() as u16;
+        Ok(())
+    }
+
+    /// Write bytes at the current position and advance it.
+    pub async fn write(&mut self, buffer: &[u8]) -> Result<(), Error> {
+        self.driver.write(self.position, buffer).await?;
+        self.position += buffer.len() as u16;
+        Ok(())
+    }
+
+    /// Wait for a pending write to complete.
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        self.driver.flush().await
+    }
+}
+
 impl<SpiDevice, Delay> ErrorType for Driver<SpiDevice, Delay>
 where
     SpiDevice: spi::SpiDevice,
@@ -219,7 +406,8 @@ where
     const READ_SIZE: usize = 1;
 
     async fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
-        self.read(offset as u16, bytes).await
+        let origin = self.storage_origin(offset)?;
+        self.read(origin, bytes).await
     }
 
     fn capacity(&self) -> usize {
@@ -236,7 +424,8 @@ where
     const ERASE_SIZE: usize = PAGE_SIZE;
 
     async fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
-        self.write(offset as u16, bytes).await
+        let origin = self.storage_origin(offset)?;
+        self.write(origin, bytes).await
     }
 
     async fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
@@ -244,7 +433,7 @@ where
             return Err(Error::NotAligned);
         }
 
-        let mut origin = from as u16;
+        let mut origin = self.storage_origin(from)?;
         while (origin as u32) < to {
             self.write(origin, &[0xff; PAGE_SIZE]).await?;
             origin += PAGE_SIZE as u16;
@@ -254,6 +443,14 @@ where
     }
 }
 
+/// EEPROM cells can be rewritten without an erase, so any bit may be written any number of times.
+impl<SpiDevice, Delay> MultiwriteNorFlash for Driver<SpiDevice, Delay>
+where
+    SpiDevice: spi::SpiDevice,
+    Delay: delay::DelayNs,
+{
+}
+
 /// Get the minimum t_cs time in ns, i.e. the minimum time the CS pin must be de-asserted betweeen commands.
 const fn min_tcs_ns(kind: PartNumber) -> u32 {
     match kind {
@@ -355,6 +552,227 @@ mod tests {
         // Then
     }
 
+    #[tokio::test]
+    async fn write_page_crossing_page_boundary() {
+        // Given
+        let mut seq = Sequence::new();
+        let mut spi = MockSpiDevice::new();
+
+        expect_read_status_register(&mut spi, &mut seq, StatusRegister(0x00));
+
+        expect_write_wren(&mut spi, &mut seq);
+        expect_read_status_register(&mut spi, &mut seq, StatusRegister(0x02));
+
+        expect_write_page(&mut spi, &mut seq, 0x06, &[0x10, 0x20]);
+        expect_read_status_register(&mut spi, &mut seq, StatusRegister(0x01));
+        expect_read_status_register(&mut spi, &mut seq, StatusRegister(0x00));
+
+        expect_write_wren(&mut spi, &mut seq);
+        expect_write_page(&mut spi, &mut seq, 0x08, &[0x30, 0x40]);
+
+        let mut delay = MockDelay::new();
+        delay.expect_delay_us().withf(|_| true).return_const(());
+        delay.expect_delay_ms().withf(|_| true).return_const(());
+
+        // When
+        let mut driver = Driver::new(spi, delay, PartNumber::At25010);
+
+        driver
+            .write_page(0x06, &[0x10, 0x20, 0x30, 0x40])
+            .await
+            .unwrap();
+
+        // Then
+    }
+
+    #[tokio::test]
+    async fn set_block_protection() {
+        // Given
+        let mut seq = Sequence::new();
+        let mut spi = MockSpiDevice::new();
+
+        expect_read_status_register(&mut spi, &mut seq, StatusRegister(0x00));
+        expect_write_wren(&mut spi, &mut seq);
+        expect_read_status_register(&mut spi, &mut seq, StatusRegister(0x02));
+        spi.expect_transaction()
+            .withf(|ops| ops[0] == spi::Operation::Write(&[Opcode::WRSR.as_u8(), 0b0000_1000]))
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_const(Ok(()));
+        expect_read_status_register(&mut spi, &mut seq, StatusRegister(0x08));
+
+        let mut delay = MockDelay::new();
+        delay.expect_delay_us().withf(|_| true).return_const(());
+
+        // When
+        let mut driver = Driver::new(spi, delay, PartNumber::At25010);
+        driver
+            .set_block_protection(BlockProtection::UpperHalf)
+            .await
+            .unwrap();
+
+        // Then
+    }
+
+    #[tokio::test]
+    async fn set_block_protection_fails_when_write_protected() {
+        // Given
+        let mut seq = Sequence::new();
+        let mut spi = MockSpiDevice::new();
+
+        expect_read_status_register(&mut spi, &mut seq, StatusRegister(0x00));
+        expect_write_wren(&mut spi, &mut seq);
+        expect_read_status_register(&mut spi, &mut seq, StatusRegister(0x00));
+
+        let mut delay = MockDelay::new();
+        delay.expect_delay_us().withf(|_| true).return_const(());
+
+        // When
+        let mut driver = Driver::new(spi, delay, PartNumber::At25010);
+        let result = driver.set_block_protection(BlockProtection::All).await;
+
+        // Then
+        assert!(matches!(result, Err(Error::WriteProtection)));
+    }
+
+    #[tokio::test]
+    async fn flush_times_out() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        spi.expect_transaction().times(1 + 1 + 20).returning(|ops| {
+            if let spi::Operation::Transfer(rx, _tx) = &mut ops[0] {
+                rx[1] = 0x01;
+            }
+            Ok(())
+        });
+
+        let mut delay = MockDelay::new();
+        delay
+            .expect_delay_ms()
+            .withf(|ms| *ms == 3)
+            .times(1)
+            .return_const(());
+        delay
+            .expect_delay_us()
+            .withf(|us| *us == 100)
+            .times(20)
+            .return_const(());
+
+        // When
+        let mut driver = Driver::new_with_write_timeout(spi, delay, PartNumber::At25010, 5);
+        let result = driver.flush().await;
+
+        // Then
+        assert!(matches!(result, Err(Error::WriteTimeout)));
+    }
+
+    #[tokio::test]
+    async fn stateful_read_advances_position() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        spi.expect_transaction()
+            .withf(|ops| ops[0] == spi::Operation::Write(&[Opcode::READ(0x10).as_u8(), 0x10]))
+            .times(1)
+            .returning(|ops| {
+                if let spi::Operation::Read(rx) = &mut ops[1] {
+                    rx.copy_from_slice(&[0x01, 0x02]);
+                }
+                Ok(())
+            });
+        let delay = MockDelay::new();
+        let mut driver = Driver::new(spi, delay, PartNumber::At25010).to_stateful();
+        driver.position = 0x10;
+
+        // When
+        let mut buf = [0; 2];
+        driver.read(&mut buf).await.unwrap();
+
+        // Then
+        assert_eq!([0x01, 0x02], buf);
+        assert_eq!(0x12, driver.position);
+    }
+
+    #[test]
+    fn block_protection() {
+        assert_eq!(
+            BlockProtection::None,
+            StatusRegister(0x03).block_protection()
+        );
+        assert_eq!(
+            BlockProtection::UpperQuarter,
+            StatusRegister(0x04).block_protection()
+        );
+        assert_eq!(
+            BlockProtection::UpperHalf,
+            StatusRegister(0x08).block_protection()
+        );
+        assert_eq!(
+            BlockProtection::All,
+            StatusRegister(0x0C).block_protection()
+        );
+    }
+
+    #[tokio::test]
+    async fn storage_offset_beyond_address_range_is_out_of_bounds() {
+        // Given
+        let spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+        let mut driver = Driver::new(spi, delay, PartNumber::At25040);
+
+        // When
+        let mut buf = [0; 1];
+        let read = ReadNorFlash::read(&mut driver, 0x10000, &mut buf).await;
+        let write = NorFlash::write(&mut driver, 0x10000, &[0; 8]).await;
+
+        // Then
+        assert!(matches!(read, Err(Error::OutOfBounds)));
+        assert!(matches!(write, Err(Error::OutOfBounds)));
+        assert_eq!(512, ReadNorFlash::capacity(&driver));
+    }
+
+    #[tokio::test]
+    async fn probe_identifies_128_byte_wraparound_as_at25010() {
+        // Given
+        let memory = std::sync::Arc::new(std::sync::Mutex::new([0x5A; 128]));
+        let mut spi = MockSpiDevice::new();
+        let mock_memory = memory.clone();
+        spi.expect_transaction().returning(move |ops| {
+            let mut memory = mock_memory.lock().unwrap();
+            match ops {
+                [spi::Operation::Transfer(rx, _)] => rx[1] = 0x02, // RDSR, write enabled
+                [spi::Operation::Write(_)] => {}                   // WREN
+                [spi::Operation::Write(header), spi::Operation::Read(rx)] => {
+                    let address = header[1] as usize;
+                    for (i, x) in rx.iter_mut().enumerate() {
+                        *x = memory[(address + i) % 128];
+                    }
+                }
+                [spi::Operation::Write(header), spi::Operation::Write(tx)] => {
+                    let address = header[1] as usize;
+                    for (i, x) in tx.iter().enumerate() {
+                        memory[(address + i) % 128] = *x;
+                    }
+                }
+                _ => panic!("Unexpected transaction"),
+            }
+            Ok(())
+        });
+
+        let mut delay = MockDelay::new();
+        delay.expect_delay_us().withf(|_| true).return_const(());
+        delay.expect_delay_ms().withf(|_| true).return_const(());
+
+        // When
+        let mut driver = Driver::new(spi, delay, PartNumber::At25040b);
+        let part_number = driver.probe().await;
+
+        // Then
+        assert_eq!(Some(PartNumber::At25010), part_number);
+        assert_eq!(PartNumber::At25010, driver.part_number());
+        assert_eq!(128, driver.capacity());
+        assert_eq!(0x5A, memory.lock().unwrap()[0]);
+    }
+
     fn expect_write_wren(spi: &mut MockSpiDevice<u8>, seq: &mut Sequence) {
         spi.expect_transaction()
             .withf(|ops| {
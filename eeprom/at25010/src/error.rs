@@ -6,6 +6,7 @@ pub enum Error {
     NotAligned,
     OutOfBounds,
     WriteProtection,
+    WriteTimeout,
     Spi,
 }
 
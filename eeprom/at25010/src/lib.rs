@@ -4,7 +4,7 @@ mod driver;
 mod error;
 mod opcode;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PartNumber {
     At25010,
     At25020,
@@ -14,5 +14,19 @@ pub enum PartNumber {
     At25040b,
 }
 
-pub use driver::{Driver, StatefulDriver};
+impl PartNumber {
+    /// Get the write page size in bytes
+    pub const fn page_size(&self) -> usize {
+        match self {
+            PartNumber::At25010 => 8,
+            PartNumber::At25020 => 8,
+            PartNumber::At25040 => 8,
+            PartNumber::At25010b => 8,
+            PartNumber::At25020b => 8,
+            PartNumber::At25040b => 8,
+        }
+    }
+}
+
+pub use driver::{BlockProtection, Driver, StatefulDriver, StatusRegister};
 pub use error::Error;
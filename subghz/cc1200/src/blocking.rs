@@ -0,0 +1,351 @@
+use embedded_hal::spi::{Operation, SpiDevice};
+
+use crate::{
+    cmd::{BurstHeader, Response, SingleCommand, Strobe, StrobeCommand},
+    driver::extended_address,
+    regs::{self, Register, RegisterAddress},
+    statusbyte::{State, StatusByte},
+    ConfigPatch, DriverError, PartNumber, RX_FIFO_SIZE, TX_FIFO_SIZE,
+};
+
+/// Register access driver using a blocking spi device.
+///
+/// This provides the register, fifo and strobe operations of `Driver` for projects without an async executor.
+/// The controllers require the async `Driver`.
+pub struct BlockingDriver<Spi: SpiDevice> {
+    spi: Spi,
+    last_status: Option<StatusByte>,
+}
+
+impl<Spi: SpiDevice> BlockingDriver<Spi> {
+    /// Create a new blocking driver
+    pub const fn new(spi: Spi) -> Self {
+        Self {
+            spi,
+            last_status: None,
+        }
+    }
+
+    /// Send a reset strobe to chip and wait for it to become ready.
+    pub fn reset(&mut self) -> Result<(), DriverError> {
+        let mut cmd = StrobeCommand::new(Strobe::SRES);
+        self.spi
+            .transfer(cmd.response.as_mut(), cmd.request.as_ref())?;
+
+        self.strobe_until(Strobe::SNOP, |status| status.chip_rdy())
+    }
+
+    /// Get the spi status returned by the last spi operation.
+    pub fn last_status(&self) -> Option<StatusByte> {
+        self.last_status
+    }
+
+    fn set_last_status(&mut self, status: Option<StatusByte>) {
+        #[cfg(feature = "defmt")]
+        if let Some(status) = status {
+            status.log_transition(self.last_status);
+        }
+
+        self.last_status = status;
+    }
+
+    /// Read the chip part number.
+    pub fn read_part_number(&mut self) -> Result<PartNumber, DriverError> {
+        let partnumber = self.read_reg::<regs::ext::Partnumber>()?;
+        match partnumber.partnum() {
+            0x20 => Ok(PartNumber::Cc1200),
+            0x21 => Ok(PartNumber::Cc1201),
+            _ => Err(DriverError::InvalidPartNumber),
+        }
+    }
+
+    /// Read a single register value from chip.
+    pub fn read_reg<R: Register>(&mut self) -> Result<R, DriverError> {
+        let value = self.read_single(R::ADDRESS)?;
+        Ok(R::from(value))
+    }
+
+    /// Read an arbitrary extended register value from chip.
+    ///
+    /// The address is the full extended address including the 0x2F prefix, e.g. 0x2F8F for PARTNUMBER.
+    /// `DriverError::InvalidAddress` is returned for any other address.
+    pub fn read_ext(&mut self, addr: u16) -> Result<u8, DriverError> {
+        self.read_single(extended_address(addr)?)
+    }
+
+    fn read_single(&mut self, address: RegisterAddress) -> Result<u8, DriverError> {
+        let mut cmd = SingleCommand::read(address);
+
+        self.spi
+            .transfer(cmd.response.as_mut(), cmd.request.as_ref())?;
+
+        self.set_last_status(Some(cmd.response.status_byte()));
+        Ok(cmd.response.value())
+    }
+
+    /// Read a sequence of register values from chip.
+    pub fn read_regs(
+        &mut self,
+        first: RegisterAddress,
+        buffer: &mut [u8],
+    ) -> Result<(), DriverError> {
+        let mut header = BurstHeader::read(first);
+
+        self.spi.transaction(&mut [
+            Operation::Transfer(header.response.as_mut(), header.request.as_ref()),
+            Operation::Read(buffer),
+        ])?;
+
+        self.set_last_status(Some(header.response.status_byte()));
+        Ok(())
+    }
+
+    /// Write a single register value to chip.
+    pub fn write_reg<R: Register>(&mut self, reg: R) -> Result<(), DriverError> {
+        self.write_single(R::ADDRESS, reg.value())
+    }
+
+    /// Write an arbitrary extended register value to chip.
+    ///
+    /// The address is the full extended address including the 0x2F prefix, e.g. 0x2F0C for FREQ2.
+    /// `DriverError::InvalidAddress` is returned for any other address.
+    pub fn write_ext(&mut self, addr: u16, val: u8) -> Result<(), DriverError> {
+        self.write_single(extended_address(addr)?, val)
+    }
+
+    fn write_single(&mut self, address: RegisterAddress, value: u8) -> Result<(), DriverError> {
+        let mut cmd = SingleCommand::write(address, value);
+
+        self.spi
+            .transfer(cmd.response.as_mut(), cmd.request.as_ref())?;
+
+        self.set_last_status(Some(cmd.response.status_byte()));
+        Ok(())
+    }
+
+    /// Write a sequence of register values to chip.
+    pub fn write_regs(&mut self, first: RegisterAddress, values: &[u8]) -> Result<(), DriverError> {
+        let mut header = BurstHeader::write(first);
+
+        self.spi.transaction(&mut [
+            Operation::Transfer(header.response.as_mut(), header.request.as_ref()),
+            Operation::Write(values),
+        ])?;
+
+        self.set_last_status(Some(header.response.status_byte()));
+        Ok(())
+    }
+
+    /// Read a register, modify it and write it back to chip.
+    ///
+    /// Returns the register value that was written.
+    pub fn modify<R: Register>(&mut self, modify: impl FnOnce(&mut R)) -> Result<R, DriverError> {
+        let mut reg = self.read_reg::<R>()?;
+        modify(&mut reg);
+        self.write_reg(reg)?;
+        Ok(reg)
+    }
+
+    /// Write a configuration patch to chip.
+    pub fn write_patch(&mut self, patch: ConfigPatch<'_>) -> Result<(), DriverError> {
+        let (pri, ext) = patch.split_pri_ext();
+        if !pri.is_empty() {
+            self.write_regs(pri.first_address, pri.values)?;
+        }
+
+        if !ext.is_empty() {
+            self.write_regs(ext.first_address, ext.values)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read bytes from the RX fifo.
+    ///
+    /// The caller must ensure that at least `buffer.len()` bytes are available in the fifo.
+    pub fn read_fifo(&mut self, buffer: &mut [u8]) -> Result<(), DriverError> {
+        assert!(buffer.len() <= RX_FIFO_SIZE);
+
+        let mut header = BurstHeader::read_fifo();
+
+        self.spi.transaction(&mut [
+            Operation::Transfer(header.response.as_mut(), header.request.as_ref()),
+            Operation::Read(buffer),
+        ])?;
+
+        self.set_last_status(Some(header.response.status_byte()));
+        Ok(())
+    }
+
+    /// Write bytes to the TX fifo.
+    pub fn write_fifo(&mut self, buffer: &[u8]) -> Result<(), DriverError> {
+        assert!(buffer.len() <= TX_FIFO_SIZE);
+
+        let mut header = BurstHeader::write_fifo();
+
+        self.spi.transaction(&mut [
+            Operation::Transfer(header.response.as_mut(), header.request.as_ref()),
+            Operation::Write(buffer),
+        ])?;
+
+        self.set_last_status(Some(header.response.status_byte()));
+        Ok(())
+    }
+
+    /// Strobe a command to the chip.
+    pub fn strobe(&mut self, strobe: Strobe) -> Result<(), DriverError> {
+        assert_ne!(Strobe::SRES, strobe);
+
+        let mut cmd = StrobeCommand::new(strobe);
+
+        self.spi
+            .transfer(cmd.response.as_mut(), cmd.request.as_ref())?;
+
+        self.set_last_status(Some(cmd.response.status_byte()));
+        Ok(())
+    }
+
+    /// Strobe a command to the chip, and continue to do so until `pred` is satisfied.
+    pub fn strobe_until<Pred>(&mut self, strobe: Strobe, pred: Pred) -> Result<(), DriverError>
+    where
+        Pred: Fn(StatusByte) -> bool,
+    {
+        let mut cmd = StrobeCommand::new(strobe);
+
+        loop {
+            self.spi
+                .transfer(cmd.response.as_mut(), cmd.request.as_ref())?;
+            let status = cmd.response.status_byte();
+            if pred(status) {
+                self.set_last_status(Some(status));
+                return Ok(());
+            }
+        }
+    }
+
+    /// Strobe a command to the chip, and continue to do so until the chip enters the IDLE state.
+    pub fn strobe_until_idle(&mut self, strobe: Strobe) -> Result<(), DriverError> {
+        self.strobe_until(strobe, |status| status.state() == State::IDLE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use embedded_hal::spi::{ErrorKind, ErrorType};
+
+    use crate::regs::pri::Iocfg2;
+
+    use super::*;
+
+    /// Blocking spi device that expects a sequence of transactions, each given as the bytes written and the bytes read.
+    #[derive(Default)]
+    struct FakeSpiDevice {
+        expected: VecDeque<(Vec<u8>, Vec<u8>)>,
+    }
+
+    impl FakeSpiDevice {
+        fn expect(&mut self, written: &[u8], read: &[u8]) {
+            self.expected.push_back((written.to_vec(), read.to_vec()));
+        }
+    }
+
+    impl ErrorType for FakeSpiDevice {
+        type Error = ErrorKind;
+    }
+
+    impl SpiDevice for FakeSpiDevice {
+        fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), ErrorKind> {
+            let (written, read) = self.expected.pop_front().expect("Unexpected transaction");
+            let mut actual = Vec::new();
+            let mut read = read.into_iter();
+            for operation in operations {
+                match operation {
+                    Operation::Read(buf) => buf.fill_with(|| read.next().unwrap()),
+                    Operation::Write(buf) => actual.extend_from_slice(buf),
+                    Operation::Transfer(rx, tx) => {
+                        actual.extend_from_slice(tx);
+                        rx.fill_with(|| read.next().unwrap());
+                    }
+                    Operation::TransferInPlace(buf) => {
+                        actual.extend_from_slice(buf);
+                        buf.fill_with(|| read.next().unwrap());
+                    }
+                    Operation::DelayNs(_) => {}
+                }
+            }
+            assert_eq!(written, actual);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn read_reg_extended() {
+        // Given
+        let mut spi = FakeSpiDevice::default();
+        spi.expect(&[0x80 | 0x2F, 0x8F, 0x00], &[0x22, 0x00, 0x20]);
+
+        // When
+        let mut driver = BlockingDriver::new(spi);
+        let part_number = driver.read_part_number().unwrap();
+
+        // Then
+        assert_eq!(PartNumber::Cc1200, part_number);
+        assert_eq!(0x22, driver.last_status.unwrap().0);
+    }
+
+    #[test]
+    fn modify_primary() {
+        // Given
+        let mut spi = FakeSpiDevice::default();
+        spi.expect(&[0x80 | 0x01, 0x00], &[0x00, 0x13]); // PKT_CRC_OK
+        spi.expect(&[0x01, 0x46], &[0x00, 0x00]); // GPIO2_INV and PKT_SYNC_RXTX
+
+        // When
+        let mut driver = BlockingDriver::new(spi);
+        let iocfg2 = driver
+            .modify::<Iocfg2>(|iocfg2| {
+                iocfg2.set_gpio2_inv(true);
+                iocfg2.set_gpio2_cfg(0x06);
+            })
+            .unwrap();
+
+        // Then
+        assert_eq!(0x46, iocfg2.value());
+    }
+
+    #[test]
+    fn write_regs_extended() {
+        // Given
+        let mut spi = FakeSpiDevice::default();
+        spi.expect(
+            &[0x40 | 0x2F, 0x0C, 0x56, 0xCC, 0xCD],
+            &[0x22, 0x00, 0, 0, 0],
+        );
+
+        // When
+        let mut driver = BlockingDriver::new(spi);
+        driver
+            .write_regs(regs::ext::Freq2::ADDRESS, &[0x56, 0xCC, 0xCD])
+            .unwrap();
+
+        // Then
+        assert_eq!(0x22, driver.last_status.unwrap().0);
+    }
+
+    #[test]
+    fn strobe_until_idle() {
+        // Given
+        let mut spi = FakeSpiDevice::default();
+        spi.expect(&[0x3D], &[0x10]); // RX
+        spi.expect(&[0x3D], &[0x00]); // IDLE
+
+        // When
+        let mut driver = BlockingDriver::new(spi);
+        driver.strobe_until_idle(Strobe::SNOP).unwrap();
+
+        // Then
+        assert_eq!(0x00, driver.last_status.unwrap().0);
+    }
+}
@@ -1,4 +1,22 @@
-use crate::regs::{Register, RegisterAddress};
+use crate::{
+    driver::{freq_value, frequency_band},
+    regs::{
+        ext::{
+            FocCfgValue, Freq0, Freq1, Freq2, FreqoffCfg, FsDig0, Mdmcfg2, RxLpfBwValue,
+            TxLpfBwValue,
+        },
+        pri::{
+            AdcCicDecfactValue, AddrCheckCfgValue, AgcAskBwValue, AgcAskDecayValue, AgcCfg0,
+            AgcCfg2, AskCfg, ChanBw, DevAddr, DeviationM, DvgaGainValue, FePerformanceModeValue,
+            FsCfg, LengthConfigValue, Mdmcfg1, ModFormatValue, ModcfgDevE, PaCfg1, PktCfg0,
+            PktCfg1, PktCfg2, PreambleCfg0, PreambleCfg1, RcModeValue, RfendCfg1,
+            RssiValidCntValue, RxoffModeValue, SymbolRate0, SymbolRate1, SymbolRate2, SyncCfg0,
+            SyncCfg1, WorCfg0, WorCfg1, WorEvent0Lsb, WorEvent0Msb, WorResValue,
+        },
+        Register, RegisterAddress,
+    },
+    ConfigError, XOSC_FREQUENCY,
+};
 
 const PRI_MIN: RegisterAddress = RegisterAddress::PRI_MIN;
 const PRI_MAX: RegisterAddress = RegisterAddress::PRI_MAX;
@@ -7,10 +25,721 @@ const EXT_MAX: RegisterAddress = RegisterAddress::EXT_MAX;
 
 pub struct Config(pub [u8; 105]);
 
+/// The eWOR (enhanced Wake On Radio) timer mode in `WOR_CFG1.WOR_MODE`.
+///
+/// The modes apply when eWOR is started with the SWOR strobe.
+/// eWOR and RXDCM (RX duty cycle mode in `WOR_CFG0`) cannot be enabled at the same time,
+/// so RXDCM must be disabled when any of these modes are used for RX sniff mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WorMode {
+    /// The next Event0 is scheduled relative to when the chip returns to SLEEP,
+    /// so the duty cycle adapts to the time spent in RX
+    Feedback = 0b000,
+    /// Event0 is scheduled periodically, independent of the time spent in RX
+    Normal = 0b001,
+    /// Compatible with the CC1101 WOR behaviour
+    Legacy = 0b010,
+    /// Event1 is masked and the chip wakes directly on Event0
+    Event1Mask = 0b011,
+    /// Event0 is masked and only Event1 is generated
+    Event0Mask = 0b100,
+}
+
+/// The modem mode in `MODCFG_DEV_E.MODEM_MODE`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ModemMode {
+    /// Normal mode
+    Normal = 0b00,
+    /// DSSS repeat mode
+    DsssRepeat = 0b01,
+    /// DSSS PN mode
+    DsssPn = 0b10,
+    /// Carrier sense mode, where carrier sense can be asserted much faster
+    CarrierSense = 0b11,
+}
+
+/// The data bit to symbol mapping in `MDMCFG2.SYMBOL_MAP_CFG`.
+///
+/// The deviation of each symbol is listed for 2'ary (0, 1) and 4'ary (00, 01, 10, 11) modulation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SymbolMap {
+    /// 2'ary: -dev, dev. 4'ary: -dev/3, -dev, dev/3, dev
+    Map0 = 0b00,
+    /// 2'ary: dev, -dev. 4'ary: -dev, -dev/3, dev, dev/3
+    Map1 = 0b01,
+    /// 2'ary: dev, -dev. 4'ary: dev/3, dev, -dev/3, -dev
+    Map2 = 0b10,
+    /// 2'ary: dev, -dev. 4'ary: dev, dev/3, -dev, -dev/3
+    Map3 = 0b11,
+}
+
+/// The receiver front-end mode, trading sensitivity, linearity, and power consumption.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FrontendMode {
+    /// Optimized linearity mode
+    OptimizedLinearity,
+    /// Normal operation mode
+    Normal,
+    /// Low power mode with reduced gain range
+    LowPower,
+    /// Zero-IF mode
+    ZeroIf,
+}
+
+/// The frequency offset correction mode in `FREQOFF_CFG.FOC_CFG`.
+///
+/// FOC in the frequency synthesizer allows a narrower RX filter bandwidth than FOC after the channel filter,
+/// but needs more preamble to settle. The chip switches to FOC after the channel filter when sync is found.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FocMode {
+    /// FOC after the channel filter, typically settling within 0 - 1 preamble bytes
+    AfterChannelFilter,
+    /// FOC in the frequency synthesizer with loop gain factor 1/128
+    InFsGain128,
+    /// FOC in the frequency synthesizer with loop gain factor 1/256
+    InFsGain256,
+    /// FOC in the frequency synthesizer with loop gain factor 1/512
+    InFsGain512,
+}
+
+impl FocMode {
+    const fn foc_cfg(self) -> FocCfgValue {
+        match self {
+            FocMode::AfterChannelFilter => FocCfgValue::FocAfterChannelFilter,
+            FocMode::InFsGain128 => FocCfgValue::FocInFsEnabledFactorOneOver128,
+            FocMode::InFsGain256 => FocCfgValue::FocInFsEnabledFactorOneOver256,
+            FocMode::InFsGain512 => FocCfgValue::FocInFsEnabledFactorOneOver512,
+        }
+    }
+}
+
+/// The address check and broadcast addresses in `PKT_CFG1.ADDR_CHECK_CFG`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BroadcastMode {
+    /// No address check
+    NoAddressCheck = 0b00,
+    /// Address check, no broadcast
+    NoBroadcast = 0b01,
+    /// Address check, 0x00 broadcast
+    Broadcast00 = 0b10,
+    /// Address check, 0x00 and 0xFF broadcast
+    Broadcast00AndFF = 0b11,
+}
+
+impl BroadcastMode {
+    pub(crate) const fn addr_check_cfg(self) -> AddrCheckCfgValue {
+        match self {
+            BroadcastMode::NoAddressCheck => AddrCheckCfgValue::NoAddressCheck,
+            BroadcastMode::NoBroadcast => AddrCheckCfgValue::AddressCheckNoBroadcast,
+            BroadcastMode::Broadcast00 => AddrCheckCfgValue::AddressCheck0x00Broadcast,
+            BroadcastMode::Broadcast00AndFF => AddrCheckCfgValue::AddressCheck0x00And0xffBroadcast,
+        }
+    }
+}
+
 impl Config {
     pub const fn patch(&self) -> ConfigPatch {
         ConfigPatch::new(self)
     }
+
+    /// Get a register value from the configuration.
+    pub fn get<R: Register>(&self) -> R {
+        assert!(R::ADDRESS <= EXT_MAX);
+        R::from(self.0[R::ADDRESS.idx()])
+    }
+
+    /// Set a register value in the configuration.
+    pub fn set<R: Register>(&mut self, reg: R) {
+        assert!(R::ADDRESS <= EXT_MAX);
+        self.0[R::ADDRESS.idx()] = reg.value();
+    }
+
+    /// Set the frequency synthesizer loop bandwidth in RX and TX.
+    ///
+    /// Valid bandwidths are 200, 300, 400, and 500 kHz.
+    /// A wider loop bandwidth gives faster settling at the cost of more phase noise.
+    pub fn set_fs_loop_bw(&mut self, rx_khz: u16, tx_khz: u16) -> Result<(), ConfigError> {
+        let rx_lpf_bw = match rx_khz {
+            200 => RxLpfBwValue::Khz200,
+            300 => RxLpfBwValue::Khz300,
+            400 => RxLpfBwValue::Khz400,
+            500 => RxLpfBwValue::Khz500,
+            _ => return Err(ConfigError::InvalidLoopBandwidth),
+        };
+        let tx_lpf_bw = match tx_khz {
+            200 => TxLpfBwValue::Khz200,
+            300 => TxLpfBwValue::Khz300,
+            400 => TxLpfBwValue::Khz400,
+            500 => TxLpfBwValue::Khz500,
+            _ => return Err(ConfigError::InvalidLoopBandwidth),
+        };
+
+        let mut fs_dig0 = self.get::<FsDig0>();
+        fs_dig0.set_rx_lpf_bw(rx_lpf_bw);
+        fs_dig0.set_tx_lpf_bw(tx_lpf_bw);
+        self.set(fs_dig0);
+        Ok(())
+    }
+
+    /// Set the number of new RSSI samples required before the RSSI value is updated.
+    ///
+    /// Valid counts are 1, 2, 5, and 9.
+    pub fn set_rssi_valid_count(&mut self, n: u8) -> Result<(), ConfigError> {
+        let mut agc_cfg0 = self.get::<AgcCfg0>();
+        agc_cfg0.set_rssi_valid_cnt(match n {
+            1 => RssiValidCntValue::Count1,
+            2 => RssiValidCntValue::Count2,
+            5 => RssiValidCntValue::Count5,
+            9 => RssiValidCntValue::Count9,
+            _ => return Err(ConfigError::InvalidRssiValidCount),
+        });
+        self.set(agc_cfg0);
+        Ok(())
+    }
+
+    /// Set whether sync search should not start before carrier sense is asserted.
+    pub fn set_carrier_sense_gate(&mut self, enable: bool) {
+        let mut mdmcfg1 = self.get::<Mdmcfg1>();
+        mdmcfg1.set_carrier_sense_gate(enable);
+        self.set(mdmcfg1);
+    }
+
+    /// Set the soft decision preamble quality threshold in `PREAMBLE_CFG0.PQT`.
+    ///
+    /// A preamble is detected when the preamble qualifier value is less than `threshold`, which must be in 0..=15.
+    /// A low threshold is strict and reduces false preamble detection in noisy environments.
+    pub fn set_pqt_threshold(&mut self, threshold: u8) -> Result<(), ConfigError> {
+        if threshold > 15 {
+            return Err(ConfigError::InvalidPqtThreshold);
+        }
+        let mut preamble_cfg0 = self.get::<PreambleCfg0>();
+        preamble_cfg0.set_pqt(threshold);
+        self.set(preamble_cfg0);
+        Ok(())
+    }
+
+    /// Set the soft decision sync word threshold in `SYNC_CFG1.SYNC_THR`.
+    ///
+    /// A sync word is accepted when the sync word qualifier value is less than `threshold`/2, and `threshold` must be in 0..=31.
+    /// A low threshold is strict and reduces false sync word detection in noisy environments.
+    pub fn set_sync_threshold(&mut self, threshold: u8) -> Result<(), ConfigError> {
+        if threshold > 31 {
+            return Err(ConfigError::InvalidSyncThreshold);
+        }
+        let mut sync_cfg1 = self.get::<SyncCfg1>();
+        sync_cfg1.set_sync_thr(threshold);
+        self.set(sync_cfg1);
+        Ok(())
+    }
+
+    /// Set the carrier frequency in Hz.
+    ///
+    /// The band select setting in `FS_CFG.FSD_BANDSELECT` is selected from the frequency.
+    /// Returns the frequency in Hz that is actually achieved given the register quantization,
+    /// or `ConfigError::InvalidFrequency` if the frequency is outside all supported bands.
+    pub fn set_frequency(&mut self, frequency: u32) -> Result<u32, ConfigError> {
+        let (bandselect, _) = frequency_band(frequency).ok_or(ConfigError::InvalidFrequency)?;
+        let mut fs_cfg = self.get::<FsCfg>();
+        fs_cfg.set_fsd_bandselect(bandselect);
+        self.set(fs_cfg);
+
+        let (freq, achieved) = freq_value(frequency);
+        self.set(Freq2::from((freq >> 16) as u8));
+        self.set(Freq1::from((freq >> 8) as u8));
+        self.set(Freq0::from(freq as u8));
+        Ok(achieved)
+    }
+
+    /// Set the symbol rate in symbols per second.
+    ///
+    /// Returns the symbol rate that is actually achieved given the register quantization,
+    /// or `ConfigError::InvalidSymbolRate` if the symbol rate is too high.
+    pub fn set_symbol_rate(&mut self, symbol_rate: u32) -> Result<u32, ConfigError> {
+        // SRATE_E > 0: R_sym = (2^20 + SRATE_M) * 2^SRATE_E / 2^39 * f_xosc
+        // SRATE_E = 0: R_sym = SRATE_M / 2^38 * f_xosc
+        let xosc = XOSC_FREQUENCY as u64;
+        let scaled = (symbol_rate as u64) << 39;
+        let (mut e, mut m) = if scaled / xosc < 1 << 21 {
+            (0, (scaled / 2 + xosc / 2) / xosc)
+        } else {
+            let e = (scaled / xosc).ilog2() - 20;
+            (e, (scaled + (xosc << e) / 2) / (xosc << e) - (1 << 20))
+        };
+        if m == 1 << 20 {
+            // Rounding overflowed the mantissa
+            e += 1;
+            m = 0;
+        }
+        if e > 15 {
+            return Err(ConfigError::InvalidSymbolRate);
+        }
+
+        let mut symbol_rate2 = SymbolRate2::default();
+        symbol_rate2.set_srate_e(e as u8);
+        symbol_rate2.set_srate_m_19_16((m >> 16) as u8);
+        self.set(symbol_rate2);
+        self.set(SymbolRate1::from((m >> 8) as u8));
+        self.set(SymbolRate0::from(m as u8));
+
+        Ok(self.symbol_rate())
+    }
+
+    /// Get the symbol rate in symbols per second.
+    pub fn symbol_rate(&self) -> u32 {
+        let symbol_rate2 = self.get::<SymbolRate2>();
+        let e = symbol_rate2.srate_e() as u64;
+        let m = (symbol_rate2.srate_m_19_16() as u64) << 16
+            | (self.get::<SymbolRate1>().value() as u64) << 8
+            | self.get::<SymbolRate0>().value() as u64;
+
+        let xosc = XOSC_FREQUENCY as u64;
+        let rate = if e > 0 {
+            (((1 << 20) + m) << e) * xosc
+        } else {
+            (m << 1) * xosc
+        };
+        ((rate + (1 << 38)) >> 39) as u32
+    }
+
+    /// Configure the radio as a transparent bridge for a UART running at `baud`.
+    ///
+    /// The symbol rate is set to match the baud rate, and UART mode in `PKT_CFG0.UART_MODE_EN`
+    /// is enabled so that the packet engine inserts and removes the start and stop bits.
+    /// Returns the symbol rate that is actually achieved given the register quantization,
+    /// or `ConfigError::InvalidSymbolRate` if the baud rate is too high.
+    pub fn set_uart_bridge(&mut self, baud: u32) -> Result<u32, ConfigError> {
+        let achieved = self.set_symbol_rate(baud)?;
+        let mut pkt_cfg0 = self.get::<PktCfg0>();
+        pkt_cfg0.set_uart_mode_en(true);
+        self.set(pkt_cfg0);
+        Ok(achieved)
+    }
+
+    /// Set the frequency deviation in Hz.
+    ///
+    /// Returns the deviation in Hz that is actually achieved given the register quantization,
+    /// or `ConfigError::InvalidDeviation` if the deviation is too high.
+    pub fn set_deviation(&mut self, deviation: u32) -> Result<u32, ConfigError> {
+        // DEV_E > 0: f_dev = (256 + DEV_M) * 2^DEV_E / 2^22 * f_xosc
+        // DEV_E = 0: f_dev = DEV_M / 2^21 * f_xosc
+        let xosc = XOSC_FREQUENCY as u64;
+        let scaled = (deviation as u64) << 22;
+        let (mut e, mut m) = if scaled / xosc < 1 << 9 {
+            (0, (scaled / 2 + xosc / 2) / xosc)
+        } else {
+            let e = (scaled / xosc).ilog2() - 8;
+            (e, (scaled + (xosc << e) / 2) / (xosc << e) - 256)
+        };
+        if m == 256 {
+            // Rounding overflowed the mantissa
+            e += 1;
+            m = 0;
+        }
+        if e > 7 {
+            return Err(ConfigError::InvalidDeviation);
+        }
+
+        self.set(DeviationM::from(m as u8));
+        let mut modcfg_dev_e = self.get::<ModcfgDevE>();
+        modcfg_dev_e.set_dev_e(e as u8);
+        self.set(modcfg_dev_e);
+
+        Ok(self.deviation())
+    }
+
+    /// Get the frequency deviation in Hz.
+    pub fn deviation(&self) -> u32 {
+        let e = self.get::<ModcfgDevE>().dev_e() as u64;
+        let m = self.get::<DeviationM>().value() as u64;
+
+        let xosc = XOSC_FREQUENCY as u64;
+        let deviation = if e > 0 {
+            ((256 + m) << e) * xosc
+        } else {
+            (m << 1) * xosc
+        };
+        ((deviation + (1 << 21)) >> 22) as u32
+    }
+
+    /// Set the receiver front-end mode.
+    ///
+    /// The AGC maximum gain is limited to the gain table range of the mode,
+    /// and the DVGA gain is set to the preferred setting for the current RX filter bandwidth in `CHAN_BW`.
+    pub fn set_frontend_mode(&mut self, mode: FrontendMode) {
+        let (fe_performance_mode, max_gain) = match mode {
+            FrontendMode::OptimizedLinearity => {
+                (FePerformanceModeValue::OptimizedLinearityMode, 17)
+            }
+            FrontendMode::Normal => (FePerformanceModeValue::NormalOperationMode, 17),
+            FrontendMode::LowPower => {
+                (FePerformanceModeValue::LowPowerModeWithReducedGainRange, 13)
+            }
+            FrontendMode::ZeroIf => (FePerformanceModeValue::ZeroIfMode, 7),
+        };
+        let mut agc_cfg2 = self.get::<AgcCfg2>();
+        agc_cfg2.set_fe_performance_mode(fe_performance_mode);
+        agc_cfg2.set_agc_max_gain(agc_cfg2.agc_max_gain().min(max_gain));
+        self.set(agc_cfg2);
+
+        let mut mdmcfg1 = self.get::<Mdmcfg1>();
+        mdmcfg1.set_dvga_gain(if self.rx_filter_bw().is_some_and(|bw| bw < 100_000) {
+            DvgaGainValue::Db0
+        } else {
+            DvgaGainValue::DbMinus18
+        });
+        self.set(mdmcfg1);
+    }
+
+    /// Get the RX filter bandwidth in Hz, or `None` if the decimation factors in `CHAN_BW` are invalid.
+    pub fn rx_filter_bw(&self) -> Option<u32> {
+        // RX filter BW = f_xosc / (Decimation Factor * BB_CIC_DECFACT * 2)
+        let decimation = decimation(self.get::<ChanBw>())?;
+        Some(XOSC_FREQUENCY / (decimation * 2))
+    }
+
+    /// Set the RX filter bandwidth in Hz.
+    ///
+    /// Returns the bandwidth in Hz that is actually achieved given the decimation factors in `CHAN_BW`.
+    pub fn set_rx_filter_bw(&mut self, bandwidth: u32) -> Result<u32, ConfigError> {
+        if bandwidth == 0 {
+            return Err(ConfigError::InvalidRxFilterBandwidth);
+        }
+
+        let mut best: Option<(AdcCicDecfactValue, u32, u32)> = None;
+        for (adc_cic_decfact, decimation_factor) in [
+            (AdcCicDecfactValue::DecimationFactor12, 12u32),
+            (AdcCicDecfactValue::DecimationFactor24, 24),
+            (AdcCicDecfactValue::DecimationFactor48, 48),
+        ] {
+            // A bandwidth too wide to compute the divisor is clamped to the widest setting below
+            let bb = (decimation_factor * 2)
+                .checked_mul(bandwidth)
+                .map_or(0, |divisor| XOSC_FREQUENCY / divisor);
+            for bb in [bb, bb + 1] {
+                let bb = bb.clamp(1, 44);
+                let achieved = XOSC_FREQUENCY / (decimation_factor * bb * 2);
+                if best.map_or(true, |(_, _, x)| {
+                    achieved.abs_diff(bandwidth) < x.abs_diff(bandwidth)
+                }) {
+                    best = Some((adc_cic_decfact, bb, achieved));
+                }
+            }
+        }
+
+        let (adc_cic_decfact, bb, achieved) = best.unwrap();
+        let mut chan_bw = self.get::<ChanBw>();
+        chan_bw.set_adc_cic_decfact(adc_cic_decfact);
+        chan_bw.set_bb_cic_decfact(bb as u8);
+        self.set(chan_bw);
+        Ok(achieved)
+    }
+
+    /// Configure the modem for OOK reception.
+    ///
+    /// The modulation format is set to ASK/OOK, and the symbol rate and RX filter bandwidth are set.
+    /// The ASK data filter bandwidth is the narrowest with a cut-off of at least 5 times the symbol rate,
+    /// and the AGC peak decay is the fastest that spans at least 8 symbols.
+    /// `ASK_DEPTH` is set to `PA_POWER_RAMP` to get the OOK off state, so this must be called after the PA power is set.
+    /// Returns the achieved symbol rate and RX filter bandwidth.
+    pub fn configure_ook(
+        &mut self,
+        symbol_rate: u32,
+        rx_bw: u32,
+    ) -> Result<(u32, u32), ConfigError> {
+        if symbol_rate == 0 {
+            return Err(ConfigError::InvalidSymbolRate);
+        }
+        if rx_bw == 0 {
+            return Err(ConfigError::InvalidRxFilterBandwidth);
+        }
+
+        let symbol_rate = self.set_symbol_rate(symbol_rate)?;
+
+        let mut modcfg_dev_e = self.get::<ModcfgDevE>();
+        modcfg_dev_e.set_mod_format(ModFormatValue::AskOok);
+        self.set(modcfg_dev_e);
+
+        let rx_bw = self.set_rx_filter_bw(rx_bw)?;
+        let rx_config_limitation = self.get::<SyncCfg0>().rx_config_limitation();
+
+        // f_cut-off = 4 * ASK BW Scale Factor * RX filter BW, or 8 * ... if RX_CONFIG_LIMITATION is set
+        let cutoff_factor = if rx_config_limitation { 8 } else { 4 };
+        let agc_ask_bw = [
+            (14, AgcAskBwValue::AskBwScaleFactorEqualPoint14),
+            (15, AgcAskBwValue::AskBwScaleFactorEqualPoint15),
+            (18, AgcAskBwValue::AskBwScaleFactorEqualPoint18),
+        ]
+        .into_iter()
+        .find(|(scale, _)| cutoff_factor * scale * rx_bw as u64 / 100 >= 5 * symbol_rate as u64)
+        .map_or(AgcAskBwValue::AskBwScaleFactorEqualPoint28, |(_, x)| x);
+
+        let mut ask_cfg = self.get::<AskCfg>();
+        ask_cfg.set_agc_ask_bw(agc_ask_bw);
+        ask_cfg.set_ask_depth(self.get::<PaCfg1>().pa_power_ramp());
+        self.set(ask_cfg);
+
+        // Sample rate = f_xosc * Interpolation Factor / (Decimation Factor * BB_CIC_DECFACT)
+        let decimation =
+            decimation(self.get::<ChanBw>()).ok_or(ConfigError::InvalidRxFilterBandwidth)?;
+        let interpolation_factor = if rx_config_limitation { 2 } else { 1 };
+        let sample_rate = XOSC_FREQUENCY * interpolation_factor / decimation;
+        let samples = 8 * sample_rate / symbol_rate;
+        let agc_ask_decay = [
+            (1200, AgcAskDecayValue::Samples1200),
+            (2400, AgcAskDecayValue::Samples2400),
+            (4700, AgcAskDecayValue::Samples4700),
+        ]
+        .into_iter()
+        .find(|(n, _)| *n >= samples)
+        .map_or(AgcAskDecayValue::Samples9500, |(_, x)| x);
+
+        let mut agc_cfg0 = self.get::<AgcCfg0>();
+        agc_cfg0.set_agc_ask_decay(agc_ask_decay);
+        self.set(agc_cfg0);
+
+        Ok((symbol_rate, rx_bw))
+    }
+
+    /// Set the ASK depth in dB for ASK modulation with a non-zero low level.
+    ///
+    /// The low level is `A_Min = (PA_POWER_RAMP + 1 - ASK_DEPTH) / 2 - 18` dBm and must not be below
+    /// the minimum PA power of -16 dBm, so this must be called after the PA power is set.
+    /// Use [`Config::configure_ook()`] for the OOK off state.
+    pub fn set_ook_depth_db(&mut self, depth_db: u8) -> Result<(), ConfigError> {
+        let pa_power_ramp = self.get::<PaCfg1>().pa_power_ramp();
+        let ask_depth = depth_db as u16 * 2;
+        if ask_depth + 3 > pa_power_ramp as u16 {
+            return Err(ConfigError::AskDepthBelowMinimumPower);
+        }
+
+        let mut ask_cfg = self.get::<AskCfg>();
+        ask_cfg.set_ask_depth(ask_depth as u8);
+        self.set(ask_cfg);
+        Ok(())
+    }
+
+    /// Set the device address used for packet filtering in RX, and the broadcast addresses that are also accepted.
+    ///
+    /// The address is the first byte after the length byte in variable packet length mode.
+    pub fn set_device_address(&mut self, address: u8, broadcast: BroadcastMode) {
+        self.set(DevAddr(address));
+        let mut pkt_cfg1 = self.get::<PktCfg1>();
+        pkt_cfg1.set_addr_check_cfg(broadcast.addr_check_cfg());
+        self.set(pkt_cfg1);
+    }
+
+    /// Enable 802.15.4g packet mode, where each packet starts with a 2 byte PHR.
+    ///
+    /// The packet length, whitening and crc are then given by the PHR of each packet,
+    /// so `PKT_CFG0.LENGTH_CONFIG`, `PKT_CFG1.CRC_CFG` and `PKT_CFG1.WHITE_DATA` are ignored by the chip.
+    pub fn enable_154g_mode(&mut self) {
+        let mut pkt_cfg2 = self.get::<PktCfg2>();
+        pkt_cfg2.set_fg_mode_en(true);
+        self.set(pkt_cfg2);
+    }
+
+    /// Enable or disable data whitening with the PN9 sequence.
+    pub fn enable_whitening(&mut self, enable: bool) {
+        let mut pkt_cfg1 = self.get::<PktCfg1>();
+        pkt_cfg1.set_white_data(enable);
+        self.set(pkt_cfg1);
+    }
+
+    /// Enable or disable forward error correction.
+    ///
+    /// FEC can only be enabled for 2'ary modulation formats and in fixed or variable packet length mode.
+    pub fn enable_fec(&mut self, enable: bool) -> Result<(), ConfigError> {
+        if enable {
+            let mod_format = self.get::<ModcfgDevE>().mod_format();
+            if matches!(mod_format, ModFormatValue::Fsk4 | ModFormatValue::Gfsk4) {
+                return Err(ConfigError::FecRequires2aryModulation);
+            }
+
+            let length_config = self.get::<PktCfg0>().length_config();
+            if length_config == LengthConfigValue::InfinitePacketLengthMode {
+                return Err(ConfigError::FecInInfinitePacketLengthMode);
+            }
+        }
+
+        let mut pkt_cfg1 = self.get::<PktCfg1>();
+        pkt_cfg1.set_fec_en(enable);
+        self.set(pkt_cfg1);
+        Ok(())
+    }
+
+    /// Enable or disable frequency offset correction, and select where the correction is applied.
+    ///
+    /// Disabling FOC may be preferred for very short packets where the correction does not settle in time.
+    /// FOC in the frequency synthesizer typically needs 2 - 4 preamble bytes to settle,
+    /// so it can only be enabled when at least 2 preamble bytes are configured.
+    /// `FOC_LIMIT` and `FOC_KI_FACTOR` are left unchanged.
+    pub fn set_foc(&mut self, enable: bool, mode: FocMode) -> Result<(), ConfigError> {
+        if enable && mode != FocMode::AfterChannelFilter {
+            // NUM_PREAMBLE 0100b is 2 bytes and the values up to 1101b are increasingly longer
+            let num_preamble = self.get::<PreambleCfg1>().num_preamble() as u8;
+            if !(0b0100..=0b1101).contains(&num_preamble) {
+                return Err(ConfigError::InsufficientPreambleForFocInFs);
+            }
+        }
+
+        let mut freqoff_cfg = self.get::<FreqoffCfg>();
+        freqoff_cfg.set_foc_en(enable);
+        freqoff_cfg.set_foc_cfg(mode.foc_cfg());
+        self.set(freqoff_cfg);
+        Ok(())
+    }
+
+    /// Set the modem mode, e.g. to select DSSS for spread spectrum operation.
+    pub fn set_modem_mode(&mut self, mode: ModemMode) {
+        let mut modcfg_dev_e = self.get::<ModcfgDevE>();
+        modcfg_dev_e.set_modem_mode(mode as u8);
+        self.set(modcfg_dev_e);
+    }
+
+    /// Get the modem mode.
+    pub fn modem_mode(&self) -> ModemMode {
+        match self.get::<ModcfgDevE>().modem_mode() {
+            0b00 => ModemMode::Normal,
+            0b01 => ModemMode::DsssRepeat,
+            0b10 => ModemMode::DsssPn,
+            _ => ModemMode::CarrierSense,
+        }
+    }
+
+    /// Set the data bit to symbol mapping, which must match the transmitter.
+    pub fn set_symbol_map(&mut self, map: SymbolMap) {
+        let mut mdmcfg2 = self.get::<Mdmcfg2>();
+        mdmcfg2.set_symbol_map_cfg(map as u8);
+        self.set(mdmcfg2);
+    }
+
+    /// Get the data bit to symbol mapping.
+    pub fn symbol_map(&self) -> SymbolMap {
+        match self.get::<Mdmcfg2>().symbol_map_cfg() {
+            0b00 => SymbolMap::Map0,
+            0b01 => SymbolMap::Map1,
+            0b10 => SymbolMap::Map2,
+            _ => SymbolMap::Map3,
+        }
+    }
+
+    /// Set the eWOR timer mode.
+    pub fn set_wor_mode(&mut self, mode: WorMode) {
+        let mut wor_cfg1 = self.get::<WorCfg1>();
+        wor_cfg1.set_wor_mode(mode as u8);
+        self.set(wor_cfg1);
+    }
+
+    /// Get the eWOR timer mode, or None if the mode is reserved.
+    pub fn wor_mode(&self) -> Option<WorMode> {
+        match self.get::<WorCfg1>().wor_mode() {
+            0b000 => Some(WorMode::Feedback),
+            0b001 => Some(WorMode::Normal),
+            0b010 => Some(WorMode::Legacy),
+            0b011 => Some(WorMode::Event1Mask),
+            0b100 => Some(WorMode::Event0Mask),
+            _ => None,
+        }
+    }
+
+    /// Set the RX timeout for sync word search in RX.
+    ///
+    /// The timeout is computed from the current eWOR resolution in `WOR_CFG1.WOR_RES`,
+    /// and is rounded to the nearest multiple of 2^(4*WOR_RES)*1250/f_xosc.
+    /// When `qual` is false, RX continues on timeout only if a sync word is found.
+    /// When `qual` is true, RX also continues if PQT is reached or CS is asserted.
+    /// `ConfigError::RxTimeoutTooLong` is returned if the timeout requires a coarser resolution.
+    pub fn set_rx_timeout(&mut self, timeout_us: u32, qual: bool) -> Result<(), ConfigError> {
+        // RX Timeout = MAX[1,FLOOR[EVENT0/2^(RX_TIME+3)]]*2^(4*WOR_RES)*1250/f_xosc [s]
+        // RX_TIME = 0 gives both the finest resolution and the longest timeout.
+        let wor_res = self.get::<WorCfg1>().wor_res() as u32;
+        let period = 1250u64 << (4 * wor_res);
+        let cycles = timeout_us as u64 * XOSC_FREQUENCY as u64 / 1_000_000;
+        let units = ((cycles + period / 2) / period).max(1);
+        if units > u16::MAX as u64 >> 3 {
+            return Err(ConfigError::RxTimeoutTooLong);
+        }
+        let event0 = (units << 3) as u16;
+
+        self.set(WorEvent0Msb((event0 >> 8) as u8));
+        self.set(WorEvent0Lsb(event0 as u8));
+
+        let mut rfend_cfg1 = self.get::<RfendCfg1>();
+        rfend_cfg1.set_rx_time(0);
+        rfend_cfg1.set_rx_time_qual(qual);
+        self.set(rfend_cfg1);
+        Ok(())
+    }
+
+    /// Configure eWOR RX sniff mode, where the chip wakes every `period_us` and listens for a sync word for `rx_window_us`.
+    ///
+    /// The finest eWOR resolution in `WOR_CFG1.WOR_RES` that can represent the period is used,
+    /// and `RFEND_CFG1.RX_TIME` is selected to give the RX timeout closest to the window.
+    /// The RCOSC is enabled with calibration, as required by eWOR, and RXDCM is disabled.
+    /// The chip enters IDLE when a packet is received so that it can be read from the fifo.
+    /// Returns the period and RX window in us that are actually achieved,
+    /// or `ConfigError::SniffPeriodTooLong` if the achieved period is too long.
+    pub fn set_sniff_mode(
+        &mut self,
+        period_us: u32,
+        rx_window_us: u32,
+    ) -> Result<(u32, u32), ConfigError> {
+        // t_EVENT0 = 2^(5*WOR_RES)*EVENT0/f_rcosc, where the calibrated f_rcosc is f_xosc/1000
+        let cycles = period_us as u64 * XOSC_FREQUENCY as u64 / 1_000_000;
+        let (wor_res, event0) = [
+            WorResValue::HighResolution,
+            WorResValue::MediumHighResolution,
+            WorResValue::MediumLowResolution,
+            WorResValue::LowResolution,
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(res, value)| {
+            let tick = 1000u64 << (5 * res);
+            (value, (cycles + tick / 2) / tick)
+        })
+        .find(|(_, event0)| *event0 <= u16::MAX as u64)
+        .ok_or(ConfigError::SniffPeriodTooLong)?;
+        let event0 = event0.max(1);
+
+        let period_us = event0 * (1000 << (5 * wor_res as u64)) * 1_000_000 / XOSC_FREQUENCY as u64;
+        let period_us = u32::try_from(period_us).map_err(|_| ConfigError::SniffPeriodTooLong)?;
+
+        let mut wor_cfg1 = self.get::<WorCfg1>();
+        wor_cfg1.set_wor_res(wor_res);
+        self.set(wor_cfg1);
+        self.set_wor_mode(WorMode::Normal);
+        self.set(WorEvent0Msb((event0 >> 8) as u8));
+        self.set(WorEvent0Lsb(event0 as u8));
+
+        // RX Timeout = MAX[1,FLOOR[EVENT0/2^(RX_TIME+3)]]*2^(4*WOR_RES)*1250/f_xosc
+        let timeout_period_us = |rx_time: u8| {
+            let units = (event0 >> (rx_time + 3)).max(1);
+            (units * (1250 << (4 * wor_res as u64)) * 1_000_000 / XOSC_FREQUENCY as u64) as u32
+        };
+        let rx_time = (0..=6)
+            .min_by_key(|&rx_time| timeout_period_us(rx_time).abs_diff(rx_window_us))
+            .unwrap();
+
+        let mut rfend_cfg1 = self.get::<RfendCfg1>();
+        rfend_cfg1.set_rx_time(rx_time);
+        rfend_cfg1.set_rxoff_mode(RxoffModeValue::Idle);
+        self.set(rfend_cfg1);
+
+        // Disable RXDCM, and run the RCOSC with calibration enabled
+        let mut wor_cfg0 = WorCfg0::from(self.get::<WorCfg0>().value() & 0x3F);
+        wor_cfg0.set_rc_pd(false);
+        wor_cfg0.set_rc_mode(RcModeValue::RcoscCalibrationEnabled);
+        self.set(wor_cfg0);
+
+        Ok((period_us, timeout_period_us(rx_time)))
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -51,6 +780,22 @@ impl<'a> ConfigPatch<'a> {
         }
     }
 
+    /// Get the addresses and values of the registers in the patch that differ from `current`.
+    pub fn diff<'c>(&self, current: &'c Config) -> impl Iterator<Item = (RegisterAddress, u8)> + 'c
+    where
+        'a: 'c,
+    {
+        let (pri, ext) = self.split_pri_ext();
+        [pri, ext]
+            .into_iter()
+            .flat_map(|patch| {
+                patch.values.iter().enumerate().map(move |(index, &value)| {
+                    (RegisterAddress(patch.first_address.0 + index as u16), value)
+                })
+            })
+            .filter(|(address, value)| current.0[address.idx()] != *value)
+    }
+
     pub const fn split_pri_ext(self) -> (ConfigPatch<'a>, ConfigPatch<'a>) {
         let first = self.first_address;
         let len = self.values.len() as u16;
@@ -82,6 +827,20 @@ impl<'a> ConfigPatch<'a> {
     }
 }
 
+/// Get the total decimation `Decimation Factor * BB_CIC_DECFACT` in `CHAN_BW`, or `None` if it is invalid.
+fn decimation(chan_bw: ChanBw) -> Option<u32> {
+    let decimation_factor = match chan_bw.adc_cic_decfact() {
+        AdcCicDecfactValue::DecimationFactor12 => 12,
+        AdcCicDecfactValue::DecimationFactor24 => 24,
+        AdcCicDecfactValue::DecimationFactor48 => 48,
+        AdcCicDecfactValue::Reserved_11 => return None,
+    };
+    match chan_bw.bb_cic_decfact() {
+        0 => None,
+        bb_cic_decfact => Some(decimation_factor * bb_cic_decfact as u32),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -103,6 +862,651 @@ mod tests {
         assert_eq!(freqoff_cfg, ext.get::<FreqoffCfg>().unwrap());
     }
 
+    #[test]
+    fn can_set_fs_loop_bw() {
+        let mut config = wmbus_modecmto::<0>();
+        config.set_fs_loop_bw(300, 300).unwrap();
+
+        let fs_dig0 = config.get::<FsDig0>();
+        assert_eq!(RxLpfBwValue::Khz300, fs_dig0.rx_lpf_bw());
+        assert_eq!(TxLpfBwValue::Khz300, fs_dig0.tx_lpf_bw());
+        assert_eq!(0b01, (fs_dig0.value() >> 2) & 0b11);
+        assert_eq!(0b01, fs_dig0.value() & 0b11);
+        assert_eq!(fs_dig0, config.patch().get::<FsDig0>().unwrap());
+    }
+
+    #[test]
+    fn cannot_set_invalid_fs_loop_bw() {
+        let mut config = wmbus_modecmto::<0>();
+        let fs_dig0 = config.get::<FsDig0>();
+
+        assert_eq!(
+            Err(ConfigError::InvalidLoopBandwidth),
+            config.set_fs_loop_bw(300, 250)
+        );
+        assert_eq!(fs_dig0, config.get::<FsDig0>());
+    }
+
+    #[test]
+    fn can_set_rssi_valid_count() {
+        let mut config = wmbus_modecmto::<0>();
+        config.set_rssi_valid_count(1).unwrap();
+
+        let agc_cfg0 = config.get::<AgcCfg0>();
+        assert_eq!(RssiValidCntValue::Count1, agc_cfg0.rssi_valid_cnt());
+        assert_eq!(0b00, (agc_cfg0.value() >> 2) & 0b11);
+
+        assert_eq!(
+            Err(ConfigError::InvalidRssiValidCount),
+            config.set_rssi_valid_count(3)
+        );
+        assert_eq!(agc_cfg0, config.get::<AgcCfg0>());
+    }
+
+    #[test]
+    fn can_set_carrier_sense_gate() {
+        let mut config = wmbus_modecmto::<0>();
+        config.set_carrier_sense_gate(true);
+        assert_eq!(0x80, config.get::<Mdmcfg1>().value() & 0x80);
+
+        config.set_carrier_sense_gate(false);
+        assert_eq!(0x00, config.get::<Mdmcfg1>().value() & 0x80);
+    }
+
+    #[test]
+    fn can_set_pqt_threshold() {
+        let mut config = wmbus_modecmto::<0>();
+        let preamble_cfg0 = config.get::<PreambleCfg0>();
+
+        config.set_pqt_threshold(3).unwrap();
+
+        let value = config.get::<PreambleCfg0>();
+        assert_eq!(3, value.pqt());
+        assert_eq!(preamble_cfg0.value() & 0xF0, value.value() & 0xF0);
+
+        assert_eq!(
+            Err(ConfigError::InvalidPqtThreshold),
+            config.set_pqt_threshold(16)
+        );
+        assert_eq!(value, config.get::<PreambleCfg0>());
+    }
+
+    #[test]
+    fn can_set_sync_threshold() {
+        let mut config = wmbus_modecmto::<0>();
+        let sync_cfg1 = config.get::<SyncCfg1>();
+
+        config.set_sync_threshold(7).unwrap();
+
+        let value = config.get::<SyncCfg1>();
+        assert_eq!(7, value.sync_thr());
+        assert_eq!(sync_cfg1.value() & 0xE0, value.value() & 0xE0);
+
+        assert_eq!(
+            Err(ConfigError::InvalidSyncThreshold),
+            config.set_sync_threshold(32)
+        );
+        assert_eq!(value, config.get::<SyncCfg1>());
+    }
+
+    #[test]
+    fn can_set_frequency() {
+        let mut config = wmbus_modecmto::<0>();
+        let achieved = config.set_frequency(868_950_000).unwrap();
+
+        // FREQ = 868.95MHz * 4 * 2^16 / 40MHz = 5694750.72
+        assert_eq!(868_950_043, achieved);
+        assert_eq!(0x56, config.get::<Freq2>().value());
+        assert_eq!(0xE5, config.get::<Freq1>().value());
+        assert_eq!(0x1F, config.get::<Freq0>().value());
+        assert_eq!(
+            FsdBandselectValue::Band820,
+            config.get::<FsCfg>().fsd_bandselect()
+        );
+    }
+
+    #[test]
+    fn can_set_frequency_in_410_band() {
+        let mut config = wmbus_modecmto::<0>();
+        let achieved = config.set_frequency(433_920_000).unwrap();
+
+        // FREQ = 433.92MHz * 8 * 2^16 / 40MHz = 5687476.22
+        assert_eq!(433_919_983, achieved);
+        assert_eq!(0x56, config.get::<Freq2>().value());
+        assert_eq!(0xC8, config.get::<Freq1>().value());
+        assert_eq!(0xB4, config.get::<Freq0>().value());
+        assert_eq!(
+            FsdBandselectValue::Band410,
+            config.get::<FsCfg>().fsd_bandselect()
+        );
+    }
+
+    #[test]
+    fn set_frequency_rejects_frequency_outside_bands() {
+        let mut config = wmbus_modecmto::<0>();
+        let freq2 = config.get::<Freq2>();
+        let fs_cfg = config.get::<FsCfg>();
+
+        assert_eq!(
+            Err(ConfigError::InvalidFrequency),
+            config.set_frequency(500_000_000)
+        );
+        assert_eq!(freq2, config.get::<Freq2>());
+        assert_eq!(fs_cfg, config.get::<FsCfg>());
+    }
+
+    #[test]
+    fn can_set_symbol_rate() {
+        let mut config = wmbus_modecmto::<0>();
+        assert_eq!(Ok(100_000), config.set_symbol_rate(100_000));
+        assert_eq!(0xA4, config.get::<SymbolRate2>().value());
+        assert_eq!(0x7A, config.get::<SymbolRate1>().value());
+        assert_eq!(0xE1, config.get::<SymbolRate0>().value());
+
+        // SRATE_M = 1200 * 2^39 / 2^3 / 40MHz - 2^20 = 1013008.3
+        assert_eq!(Ok(1200), config.set_symbol_rate(1200));
+        assert_eq!(0x3F, config.get::<SymbolRate2>().value());
+        assert_eq!(0x75, config.get::<SymbolRate1>().value());
+        assert_eq!(0x10, config.get::<SymbolRate0>().value());
+
+        assert_eq!(Ok(1), config.set_symbol_rate(1));
+        assert_eq!(0x00, config.get::<SymbolRate2>().value() >> 4);
+    }
+
+    #[test]
+    fn set_symbol_rate_rejects_too_high_rate() {
+        let mut config = wmbus_modecmto::<0>();
+        let symbol_rate2 = config.get::<SymbolRate2>();
+
+        assert_eq!(
+            Err(ConfigError::InvalidSymbolRate),
+            config.set_symbol_rate(6_000_000)
+        );
+        assert_eq!(symbol_rate2, config.get::<SymbolRate2>());
+    }
+
+    #[test]
+    fn can_get_symbol_rate_and_deviation() {
+        let mut config = wmbus_modecmto::<0>();
+        let symbol_rate = config.set_symbol_rate(38_400).unwrap();
+        let deviation = config.set_deviation(20_000).unwrap();
+
+        assert!(symbol_rate.abs_diff(38_400) <= 1);
+        assert!(deviation.abs_diff(20_000) <= 38); // Half the resolution of 76 Hz at DEV_E = 3
+        assert_eq!(symbol_rate, config.symbol_rate());
+        assert_eq!(deviation, config.deviation());
+    }
+
+    #[test]
+    fn can_set_uart_bridge() {
+        let mut config = wmbus_modecmto::<0>();
+        assert!(!config.get::<PktCfg0>().uart_mode_en());
+
+        let achieved = config.set_uart_bridge(9600).unwrap();
+        assert!(achieved.abs_diff(9600) <= 1);
+        assert!(config.get::<PktCfg0>().uart_mode_en());
+    }
+
+    #[test]
+    fn can_set_deviation() {
+        let mut config = wmbus_modecmto::<0>();
+        let modcfg_dev_e = config.get::<ModcfgDevE>();
+        config.set(DeviationM::default());
+        config.set(ModcfgDevE::from(modcfg_dev_e.value() & !0x07));
+
+        assert_eq!(Ok(45_013), config.set_deviation(45_000));
+        assert_eq!(0x27, config.get::<DeviationM>().value());
+        assert_eq!(modcfg_dev_e, config.get::<ModcfgDevE>());
+
+        assert_eq!(Ok(2_499), config.set_deviation(2_500));
+        assert_eq!(0x83, config.get::<DeviationM>().value());
+        assert_eq!(0, config.get::<ModcfgDevE>().dev_e());
+    }
+
+    #[test]
+    fn set_deviation_rejects_too_high_deviation() {
+        let mut config = wmbus_modecmto::<0>();
+        let deviation_m = config.get::<DeviationM>();
+        let modcfg_dev_e = config.get::<ModcfgDevE>();
+
+        assert_eq!(
+            Err(ConfigError::InvalidDeviation),
+            config.set_deviation(1_000_000)
+        );
+        assert_eq!(deviation_m, config.get::<DeviationM>());
+        assert_eq!(modcfg_dev_e, config.get::<ModcfgDevE>());
+    }
+
+    #[test]
+    fn can_set_modem_mode() {
+        let mut config = wmbus_modecmto::<0>();
+        let modcfg_dev_e = config.get::<ModcfgDevE>();
+
+        for (mode, code) in [
+            (ModemMode::Normal, 0b00),
+            (ModemMode::DsssRepeat, 0b01),
+            (ModemMode::DsssPn, 0b10),
+            (ModemMode::CarrierSense, 0b11),
+        ] {
+            config.set_modem_mode(mode);
+
+            let value = config.get::<ModcfgDevE>();
+            assert_eq!(code, value.modem_mode());
+            assert_eq!(modcfg_dev_e.value() & 0x3F, value.value() & 0x3F);
+            assert_eq!(mode, config.modem_mode());
+        }
+    }
+
+    #[test]
+    fn can_set_symbol_map() {
+        let mut config = wmbus_modecmto::<0>();
+        let mdmcfg2 = config.get::<Mdmcfg2>();
+
+        for (map, code) in [
+            (SymbolMap::Map0, 0b00),
+            (SymbolMap::Map1, 0b01),
+            (SymbolMap::Map2, 0b10),
+            (SymbolMap::Map3, 0b11),
+        ] {
+            config.set_symbol_map(map);
+
+            let value = config.get::<Mdmcfg2>();
+            assert_eq!(code, value.symbol_map_cfg());
+            assert_eq!(mdmcfg2.value() & 0xCF, value.value() & 0xCF);
+            assert_eq!(map, config.symbol_map());
+        }
+    }
+
+    #[test]
+    fn can_set_wor_mode() {
+        let mut config = wmbus_modecmto::<0>();
+        let wor_res = config.get::<WorCfg1>().wor_res() as u8;
+        let event1 = config.get::<WorCfg1>().event1();
+
+        for (mode, code) in [
+            (WorMode::Feedback, 0b000),
+            (WorMode::Normal, 0b001),
+            (WorMode::Legacy, 0b010),
+            (WorMode::Event1Mask, 0b011),
+            (WorMode::Event0Mask, 0b100),
+        ] {
+            config.set_wor_mode(mode);
+
+            let wor_cfg1 = config.get::<WorCfg1>();
+            assert_eq!(code, wor_cfg1.wor_mode());
+            assert_eq!(wor_res, wor_cfg1.wor_res() as u8);
+            assert_eq!(event1, wor_cfg1.event1());
+            assert_eq!(Some(mode), config.wor_mode());
+        }
+    }
+
+    #[test]
+    fn can_set_frontend_mode() {
+        let mut config = wmbus_modecmto::<0>();
+        assert_eq!(Some(208_333), config.rx_filter_bw());
+
+        for (mode, fe_performance_mode, max_gain) in [
+            (
+                FrontendMode::OptimizedLinearity,
+                FePerformanceModeValue::OptimizedLinearityMode,
+                17,
+            ),
+            (
+                FrontendMode::Normal,
+                FePerformanceModeValue::NormalOperationMode,
+                17,
+            ),
+            (
+                FrontendMode::LowPower,
+                FePerformanceModeValue::LowPowerModeWithReducedGainRange,
+                13,
+            ),
+            (FrontendMode::ZeroIf, FePerformanceModeValue::ZeroIfMode, 7),
+        ] {
+            let mut agc_cfg2 = config.get::<AgcCfg2>();
+            agc_cfg2.set_agc_max_gain(17);
+            config.set(agc_cfg2);
+
+            config.set_frontend_mode(mode);
+
+            let agc_cfg2 = config.get::<AgcCfg2>();
+            assert_eq!(fe_performance_mode, agc_cfg2.fe_performance_mode());
+            assert_eq!(max_gain, agc_cfg2.agc_max_gain());
+            assert_eq!(
+                DvgaGainValue::DbMinus18,
+                config.get::<Mdmcfg1>().dvga_gain()
+            );
+        }
+
+        // 40MHz / (48 * 8 * 2) = 52.083kHz
+        let mut chan_bw = config.get::<ChanBw>();
+        chan_bw.set_adc_cic_decfact(AdcCicDecfactValue::DecimationFactor48);
+        config.set(chan_bw);
+        config.set_frontend_mode(FrontendMode::LowPower);
+        assert_eq!(DvgaGainValue::Db0, config.get::<Mdmcfg1>().dvga_gain());
+    }
+
+    #[test]
+    fn can_set_rx_filter_bw() {
+        let mut config = wmbus_modecmto::<0>();
+
+        // 40MHz / (24 * 4 * 2) = 208.333kHz
+        assert_eq!(Ok(208_333), config.set_rx_filter_bw(200_000));
+        // 40MHz / (48 * 40 * 2) = 10.417kHz
+        assert_eq!(Ok(10_416), config.set_rx_filter_bw(10_400));
+        assert_eq!(Some(10_416), config.rx_filter_bw());
+        let chan_bw = config.get::<ChanBw>();
+        assert_eq!(
+            AdcCicDecfactValue::DecimationFactor48,
+            chan_bw.adc_cic_decfact()
+        );
+        assert_eq!(40, chan_bw.bb_cic_decfact());
+
+        assert_eq!(
+            Err(ConfigError::InvalidRxFilterBandwidth),
+            config.set_rx_filter_bw(0)
+        );
+        assert_eq!(chan_bw, config.get::<ChanBw>());
+
+        // 40MHz / (12 * 1 * 2) = 1.667MHz
+        assert_eq!(Ok(1_666_666), config.set_rx_filter_bw(u32::MAX));
+
+        let mut chan_bw = config.get::<ChanBw>();
+        chan_bw.set_bb_cic_decfact(0);
+        config.set(chan_bw);
+        assert_eq!(None, config.rx_filter_bw());
+    }
+
+    #[test]
+    fn can_configure_ook() {
+        let mut config = wmbus_modecmto::<0>();
+        let mut pa_cfg1 = config.get::<PaCfg1>();
+        pa_cfg1.set_pa_power_ramp(0x3F);
+        config.set(pa_cfg1);
+        let mut sync_cfg0 = config.get::<SyncCfg0>();
+        sync_cfg0.set_rx_config_limitation(false);
+        config.set(sync_cfg0);
+
+        let (symbol_rate, rx_bw) = config.configure_ook(4_800, 50_000).unwrap();
+
+        assert_eq!(4_800, symbol_rate);
+        assert_eq!(50_505, rx_bw);
+        assert_eq!(
+            ModFormatValue::AskOok,
+            config.get::<ModcfgDevE>().mod_format()
+        );
+        // 4 * 0.14 * 50.5kHz = 28.3kHz >= 5 * 4800
+        let ask_cfg = config.get::<AskCfg>();
+        assert_eq!(
+            AgcAskBwValue::AskBwScaleFactorEqualPoint14,
+            ask_cfg.agc_ask_bw()
+        );
+        assert_eq!(0x3F, ask_cfg.ask_depth());
+        // 8 symbols * 101kHz / 4800 = 168 samples
+        assert_eq!(
+            AgcAskDecayValue::Samples1200,
+            config.get::<AgcCfg0>().agc_ask_decay()
+        );
+    }
+
+    #[test]
+    fn cannot_configure_ook_with_zero_rate() {
+        let mut config = wmbus_modecmto::<0>();
+        let modcfg_dev_e = config.get::<ModcfgDevE>();
+
+        assert_eq!(
+            Err(ConfigError::InvalidSymbolRate),
+            config.configure_ook(0, 50_000)
+        );
+        assert_eq!(
+            Err(ConfigError::InvalidRxFilterBandwidth),
+            config.configure_ook(4_800, 0)
+        );
+        assert_eq!(modcfg_dev_e, config.get::<ModcfgDevE>());
+    }
+
+    #[test]
+    fn can_set_ook_depth_db() {
+        let mut config = wmbus_modecmto::<0>();
+        let mut pa_cfg1 = config.get::<PaCfg1>();
+        pa_cfg1.set_pa_power_ramp(0x3F);
+        config.set(pa_cfg1);
+
+        // A_Min = (63 + 1 - 60) / 2 - 18 = -16 dBm
+        config.set_ook_depth_db(30).unwrap();
+        assert_eq!(60, config.get::<AskCfg>().ask_depth());
+
+        assert_eq!(
+            Err(ConfigError::AskDepthBelowMinimumPower),
+            config.set_ook_depth_db(31)
+        );
+        assert_eq!(60, config.get::<AskCfg>().ask_depth());
+    }
+
+    #[test]
+    fn can_set_rx_timeout() {
+        let mut config = wmbus_modecmto::<0>();
+        let mut wor_cfg1 = config.get::<WorCfg1>();
+        wor_cfg1.set_wor_res(WorResValue::HighResolution);
+        config.set(wor_cfg1);
+
+        // 10ms = 320 * 1250/40MHz
+        config.set_rx_timeout(10_000, true).unwrap();
+
+        assert_eq!(0x0A, config.get::<WorEvent0Msb>().value());
+        assert_eq!(0x00, config.get::<WorEvent0Lsb>().value());
+        let rfend_cfg1 = config.get::<RfendCfg1>();
+        assert_eq!(0, rfend_cfg1.rx_time());
+        assert!(rfend_cfg1.rx_time_qual());
+
+        config.set_rx_timeout(10_000, false).unwrap();
+        assert!(!config.get::<RfendCfg1>().rx_time_qual());
+
+        // The longest timeout is 8191 * 1250/40MHz = 255.97ms
+        assert_eq!(
+            Err(ConfigError::RxTimeoutTooLong),
+            config.set_rx_timeout(300_000, false)
+        );
+    }
+
+    #[test]
+    fn can_set_sniff_mode() {
+        let mut config = wmbus_modecmto::<0>();
+
+        // EVENT0 = 1s / 25us = 40000
+        // RX timeout = FLOOR[40000 / 2^(RX_TIME + 3)] * 1250/40MHz
+        let (period, rx_window) = config.set_sniff_mode(1_000_000, 4_000).unwrap();
+
+        assert_eq!(1_000_000, period);
+        assert_eq!(4_875, rx_window);
+        let wor_cfg1 = config.get::<WorCfg1>();
+        assert_eq!(WorResValue::HighResolution, wor_cfg1.wor_res());
+        assert_eq!(Some(WorMode::Normal), config.wor_mode());
+        assert_eq!(0x9C, config.get::<WorEvent0Msb>().value());
+        assert_eq!(0x40, config.get::<WorEvent0Lsb>().value());
+        let rfend_cfg1 = config.get::<RfendCfg1>();
+        assert_eq!(5, rfend_cfg1.rx_time());
+        assert_eq!(RxoffModeValue::Idle, rfend_cfg1.rxoff_mode());
+        let wor_cfg0 = config.get::<WorCfg0>();
+        assert!(!wor_cfg0.rc_pd());
+        assert_eq!(RcModeValue::RcoscCalibrationEnabled, wor_cfg0.rc_mode());
+        assert_eq!(
+            RxDutyCycleModeValue::RxdcmDisabled,
+            wor_cfg0.rx_duty_cycle_mode()
+        );
+    }
+
+    #[test]
+    fn can_set_sniff_mode_with_long_period() {
+        let mut config = wmbus_modecmto::<0>();
+
+        // EVENT0 = 4000s / (25us * 2^15) = 4882.8
+        let (period, _) = config.set_sniff_mode(4_000_000_000, 4_000).unwrap();
+
+        assert_eq!(4_000_153_600, period);
+        assert_eq!(
+            WorResValue::LowResolution,
+            config.get::<WorCfg1>().wor_res()
+        );
+    }
+
+    #[test]
+    fn set_sniff_mode_rejects_too_long_period() {
+        let mut config = wmbus_modecmto::<0>();
+        let wor_cfg1 = config.get::<WorCfg1>();
+
+        // EVENT0 = 4294.97s / (25us * 2^15) = 5242.9, which rounds to a period above u32::MAX us
+        assert_eq!(
+            Err(ConfigError::SniffPeriodTooLong),
+            config.set_sniff_mode(u32::MAX, 4_000)
+        );
+        assert_eq!(wor_cfg1, config.get::<WorCfg1>());
+    }
+
+    #[test]
+    fn can_diff() {
+        let current = wmbus_modecmto::<0>();
+        let mut config = Config(current.0);
+        let mut mdmcfg1 = config.get::<Mdmcfg1>();
+        mdmcfg1.set_manchester_en(!mdmcfg1.manchester_en());
+        config.set(mdmcfg1);
+        config.set(IfMixCfg::from(current.get::<IfMixCfg>().value() ^ 0x04));
+
+        let diff: Vec<_> = config.patch().diff(&current).collect();
+
+        assert_eq!(
+            vec![
+                (Mdmcfg1::ADDRESS, mdmcfg1.value()),
+                (IfMixCfg::ADDRESS, config.get::<IfMixCfg>().value())
+            ],
+            diff
+        );
+        assert_eq!(0, current.patch().diff(&current).count());
+    }
+
+    #[test]
+    fn can_set_device_address() {
+        let mut config = wmbus_modecmto::<0>();
+
+        config.set_device_address(0x42, BroadcastMode::Broadcast00AndFF);
+
+        assert_eq!(0x42, config.get::<DevAddr>().device_addr());
+        assert_eq!(
+            AddrCheckCfgValue::AddressCheck0x00And0xffBroadcast,
+            config.get::<PktCfg1>().addr_check_cfg()
+        );
+    }
+
+    #[test]
+    fn can_enable_154g_mode() {
+        let mut config = wmbus_modecmto::<0>();
+
+        config.enable_154g_mode();
+
+        assert!(config.get::<PktCfg2>().fg_mode_en());
+    }
+
+    #[test]
+    fn can_enable_whitening() {
+        let mut config = wmbus_modecmto::<0>();
+
+        config.enable_whitening(true);
+        assert!(config.get::<PktCfg1>().white_data());
+
+        config.enable_whitening(false);
+        assert!(!config.get::<PktCfg1>().white_data());
+    }
+
+    #[test]
+    fn can_enable_fec() {
+        let mut config = wmbus_modecmto::<0>();
+        let mut modcfg_dev_e = config.get::<ModcfgDevE>();
+        modcfg_dev_e.set_mod_format(ModFormatValue::Fsk2);
+        config.set(modcfg_dev_e);
+        let before = Config(config.0);
+
+        config.enable_fec(true).unwrap();
+
+        let diff: Vec<_> = config.patch().diff(&before).collect();
+        assert_eq!(
+            vec![(PktCfg1::ADDRESS, before.get::<PktCfg1>().value() | 0x80)],
+            diff
+        );
+        assert_eq!(
+            before.get::<PktCfg1>().white_data(),
+            config.get::<PktCfg1>().white_data()
+        );
+
+        config.enable_fec(false).unwrap();
+        assert_eq!(0, config.patch().diff(&before).count());
+    }
+
+    #[test]
+    fn can_set_foc() {
+        let mut config = wmbus_modecmto::<0>();
+        let mut preamble_cfg1 = config.get::<PreambleCfg1>();
+        preamble_cfg1.set_num_preamble(NumPreambleValue::Bytes4);
+        config.set(preamble_cfg1);
+        config.set(FreqoffCfg(0x06));
+
+        config.set_foc(true, FocMode::InFsGain256).unwrap();
+        assert_eq!(0x36, config.get::<FreqoffCfg>().value());
+
+        config.set_foc(false, FocMode::AfterChannelFilter).unwrap();
+        assert_eq!(0x06, config.get::<FreqoffCfg>().value());
+
+        config.set_foc(true, FocMode::AfterChannelFilter).unwrap();
+        assert_eq!(0x26, config.get::<FreqoffCfg>().value());
+    }
+
+    #[test]
+    fn cannot_set_foc_in_fs_with_short_preamble() {
+        let mut config = wmbus_modecmto::<0>();
+        let mut preamble_cfg1 = config.get::<PreambleCfg1>();
+        preamble_cfg1.set_num_preamble(NumPreambleValue::Byte1);
+        config.set(preamble_cfg1);
+        config.set(FreqoffCfg(0x20));
+
+        assert_eq!(
+            Err(ConfigError::InsufficientPreambleForFocInFs),
+            config.set_foc(true, FocMode::InFsGain128)
+        );
+        assert_eq!(0x20, config.get::<FreqoffCfg>().value());
+
+        config.set_foc(false, FocMode::InFsGain128).unwrap();
+        assert_eq!(0x08, config.get::<FreqoffCfg>().value());
+    }
+
+    #[test]
+    fn cannot_enable_fec_for_4ary_modulation() {
+        let mut config = wmbus_modecmto::<0>();
+        let mut modcfg_dev_e = config.get::<ModcfgDevE>();
+        modcfg_dev_e.set_mod_format(ModFormatValue::Gfsk4);
+        config.set(modcfg_dev_e);
+
+        assert_eq!(
+            Err(ConfigError::FecRequires2aryModulation),
+            config.enable_fec(true)
+        );
+        assert!(!config.get::<PktCfg1>().fec_en());
+    }
+
+    #[test]
+    fn cannot_enable_fec_in_infinite_packet_length_mode() {
+        let mut config = wmbus_modecmto::<0>();
+        let mut modcfg_dev_e = config.get::<ModcfgDevE>();
+        modcfg_dev_e.set_mod_format(ModFormatValue::Fsk2);
+        config.set(modcfg_dev_e);
+        let mut pkt_cfg0 = config.get::<PktCfg0>();
+        pkt_cfg0.set_length_config(LengthConfigValue::InfinitePacketLengthMode);
+        config.set(pkt_cfg0);
+
+        assert_eq!(
+            Err(ConfigError::FecInInfinitePacketLengthMode),
+            config.enable_fec(true)
+        );
+    }
+
     #[test]
     fn can_split_pri_ext() {
         let config = wmbus_modecmto::<0>();
@@ -43,15 +43,13 @@ use core::marker::PhantomData;
 
 use crate::{
     cmd::Strobe,
-    driver::lo_divider,
     gpio::{Gpio, GpioOutput},
     regs::{
-        ext::Freq2,
         pri::{
             FifoCfg, LengthConfigValue, Mdmcfg1, PktCfg0, PktCfg1, PktCfg2, PktFormatValue, PktLen,
             RfendCfg0, RfendCfg1, RxoffModeValue, TxoffModeValue,
         },
-        Iocfg, Register,
+        Iocfg,
     },
     ConfigPatch, Driver, Rssi, State, RX_FIFO_SIZE, TX_FIFO_SIZE,
 };
@@ -146,15 +144,10 @@ where
     }
 
     /// Set the frequency in Hz
-    pub async fn set_frequency(&mut self, frequency: u32) -> Result<(), ControllerError> {
-        let lo_div = lo_divider(frequency) as u32;
-        let freq: [u8; 4] = (frequency * lo_div).to_be_bytes();
-        let patch = ConfigPatch {
-            first_address: Freq2::ADDRESS,
-            values: &freq[1..],
-        };
-        self.driver.write_patch(patch).await?;
-        Ok(())
+    ///
+    /// Returns the frequency in Hz that is actually achieved given the register quantization.
+    pub async fn set_frequency(&mut self, frequency: u32) -> Result<u32, ControllerError> {
+        Ok(self.driver.set_frequency(frequency).await?)
     }
 
     /// Write bytes to the chip tx fifo
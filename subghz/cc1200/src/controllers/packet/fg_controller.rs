@@ -0,0 +1,313 @@
+use core::marker::PhantomData;
+
+use embassy_time::Instant;
+use embedded_hal_async::{delay::DelayNs, spi};
+use heapless::Vec;
+
+use crate::{
+    cmd::Strobe,
+    gpio::{Gpio, GpioOutput},
+    regs::{
+        ext::LqiVal,
+        pri::{FifoCfg, Mdmcfg1, PktCfg1, PktCfg2, PktFormatValue},
+        Iocfg,
+    },
+    ConfigPatch, Driver, Rssi, State, RX_FIFO_SIZE, TX_FIFO_SIZE,
+};
+
+use super::ControllerError;
+
+/// The number of status bytes appended to a packet when `PKT_CFG1.APPEND_STATUS` is set
+const STATUS_LENGTH: usize = 2;
+
+/// The 802.15.4g PHY header
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Phr {
+    /// Whether the FCS is a 2 byte CRC16 rather than a 4 byte CRC32
+    pub fcs_16bit: bool,
+    /// Whether the PSDU is whitened
+    pub whitening: bool,
+    /// The PSDU length, including the FCS
+    pub length: u16,
+}
+
+impl Phr {
+    pub const LENGTH: usize = 2;
+    const FCS_TYPE: u16 = 1 << 12;
+    const DATA_WHITENING: u16 = 1 << 11;
+    const FRAME_LENGTH_MASK: u16 = 0x07FF;
+
+    /// Get the length of the FCS
+    pub const fn fcs_length(&self) -> usize {
+        if self.fcs_16bit {
+            2
+        } else {
+            4
+        }
+    }
+
+    pub const fn from_bytes(bytes: [u8; Self::LENGTH]) -> Self {
+        let value = u16::from_be_bytes(bytes);
+        Self {
+            fcs_16bit: value & Self::FCS_TYPE != 0,
+            whitening: value & Self::DATA_WHITENING != 0,
+            length: value & Self::FRAME_LENGTH_MASK,
+        }
+    }
+
+    pub const fn to_bytes(&self) -> [u8; Self::LENGTH] {
+        let mut value = self.length & Self::FRAME_LENGTH_MASK;
+        if self.fcs_16bit {
+            value |= Self::FCS_TYPE;
+        }
+        if self.whitening {
+            value |= Self::DATA_WHITENING;
+        }
+        value.to_be_bytes()
+    }
+}
+
+/// A complete frame received in 802.15.4g packet mode
+#[derive(Debug)]
+pub struct FgFrame<const MAX_FRAME_LENGTH: usize> {
+    /// The timestamp sampled when the sync word was received.
+    pub timestamp: Instant,
+    /// The received PHY header.
+    pub phr: Phr,
+    /// The received bytes, excluding the PHR and the FCS which is checked by the chip.
+    pub payload: Vec<u8, MAX_FRAME_LENGTH>,
+    /// The rssi from the appended status bytes, if status is appended and the rssi is valid.
+    pub rssi: Option<Rssi>,
+    /// The link quality indicator from the appended status bytes, if status is appended.
+    pub lqi: Option<u8>,
+    /// Whether the crc check passed, if status is appended.
+    pub crc_ok: Option<bool>,
+}
+
+/// Transmit and receive frames in 802.15.4g packet mode, where each frame starts with a 2 byte PHR.
+///
+/// The configuration should have 802.15.4g mode enabled with `Config::enable_154g_mode()`.
+pub struct FgController<'a, Spi, Delay, ResetPin, IrqGpio, IrqPin, const MAX_FRAME_LENGTH: usize>
+where
+    Spi: spi::SpiDevice,
+    Delay: DelayNs,
+    ResetPin: embedded_hal::digital::OutputPin,
+    IrqGpio: Gpio,
+    IrqPin: embedded_hal_async::digital::Wait,
+{
+    driver: &'a mut Driver<Spi, Delay, ResetPin>,
+    config: ConfigPatch<'a>,
+    append_status: bool,
+    irq_gpio: PhantomData<IrqGpio>,
+    irq_pin: &'a mut IrqPin,
+}
+
+impl<'a, Spi, Delay, ResetPin, IrqGpio, IrqPin, const MAX_FRAME_LENGTH: usize>
+    FgController<'a, Spi, Delay, ResetPin, IrqGpio, IrqPin, MAX_FRAME_LENGTH>
+where
+    Spi: spi::SpiDevice,
+    Delay: DelayNs,
+    ResetPin: embedded_hal::digital::OutputPin,
+    IrqGpio: Gpio,
+    IrqPin: embedded_hal_async::digital::Wait,
+{
+    /// Create a new 802.15.4g controller
+    ///
+    /// The PHR, the frame and the appended status must fit in the fifos.
+    pub fn new(
+        driver: &'a mut Driver<Spi, Delay, ResetPin>,
+        irq_pin: &'a mut IrqPin,
+        config: ConfigPatch<'a>,
+    ) -> Self {
+        assert!(Phr::LENGTH + MAX_FRAME_LENGTH + STATUS_LENGTH <= RX_FIFO_SIZE);
+        assert!(Phr::LENGTH + MAX_FRAME_LENGTH <= TX_FIFO_SIZE);
+
+        Self {
+            driver,
+            config,
+            append_status: config.get::<PktCfg1>().unwrap_or_default().append_status(),
+            irq_gpio: PhantomData,
+            irq_pin,
+        }
+    }
+
+    /// Initialize the chip by sending a configuration and entering idle state
+    pub async fn init(&mut self) -> Result<(), ControllerError> {
+        self.driver.write_patch(self.config).await?;
+
+        // FIFO must be enabled
+        let mut mdmcfg1 = self.config.get::<Mdmcfg1>().unwrap_or_default();
+        mdmcfg1.set_fifo_en(true);
+        self.driver.write_reg(mdmcfg1).await?;
+
+        // Packet mode must be Normal/FIFO mode with 802.15.4g framing
+        let mut pktcfg2 = self.config.get::<PktCfg2>().unwrap_or_default();
+        pktcfg2.set_pkt_format(PktFormatValue::NormalModeFifoMode);
+        pktcfg2.set_fg_mode_en(true);
+        self.driver.write_reg(pktcfg2).await?;
+
+        self.idle().await?;
+
+        Ok(())
+    }
+
+    /// Transmit a payload and wait for the transmission to complete.
+    ///
+    /// The PHR length is derived from the payload and the FCS type, and the FCS is appended by the chip.
+    pub async fn transmit(
+        &mut self,
+        payload: &[u8],
+        fcs_16bit: bool,
+        whitening: bool,
+    ) -> Result<(), ControllerError> {
+        assert!(payload.len() <= MAX_FRAME_LENGTH);
+
+        let mut phr = Phr {
+            fcs_16bit,
+            whitening,
+            length: 0,
+        };
+        phr.length = (payload.len() + phr.fcs_length()) as u16;
+
+        // Flush TX buffer before we fill it
+        // This can only be safely done if the chip is in IDLE state.
+        self.driver.strobe_until_idle(Strobe::SIDLE).await?;
+        self.driver.strobe(Strobe::SFTX).await?;
+
+        self.driver.write_fifo(&phr.to_bytes()).await?;
+        self.driver.write_fifo(payload).await?;
+
+        // Setup fifo pin
+        // Asserted when sync word has been sent and de-asserted at the end of the packet
+        let mut irq_iocfg = IrqGpio::Iocfg::default();
+        irq_iocfg.set_gpio_cfg(GpioOutput::PKT_SYNC_RXTX);
+        self.driver.write_reg(irq_iocfg).await?;
+
+        self.driver.strobe(Strobe::STX).await?;
+
+        self.irq_pin.wait_for_high().await.unwrap();
+        self.irq_pin.wait_for_low().await.unwrap();
+
+        self.driver.strobe(Strobe::SNOP).await?;
+        if self.driver.last_status().unwrap().state() == State::TX_FIFO_ERROR {
+            self.driver.strobe_until_idle(Strobe::SIDLE).await?;
+            self.driver.strobe(Strobe::SFTX).await?;
+            return Err(ControllerError::TxFifoUnderflow);
+        }
+
+        Ok(())
+    }
+
+    /// Receive the next complete frame
+    pub async fn receive(&mut self) -> Result<FgFrame<MAX_FRAME_LENGTH>, ControllerError> {
+        // Setup fifo pin
+        // Asserted when sync word has been received and de-asserted at the end of the packet
+        let mut irq_iocfg = IrqGpio::Iocfg::default();
+        irq_iocfg.set_gpio_cfg(GpioOutput::PKT_SYNC_RXTX);
+        self.driver.write_reg(irq_iocfg).await?;
+
+        loop {
+            // Flush RX buffer before we start the receiver
+            // This can only be safely done if the chip is in IDLE state.
+            self.driver.strobe_until_idle(Strobe::SIDLE).await?;
+            self.driver.strobe(Strobe::SFRX).await?;
+            self.driver.strobe(Strobe::SRX).await?;
+
+            // Wait for SOF to be detected
+            self.irq_pin.wait_for_high().await.unwrap();
+            let timestamp = Instant::now();
+
+            // Wait for the packet to be fully received
+            self.irq_pin.wait_for_low().await.unwrap();
+
+            if let Some(frame) = self.read_frame(timestamp).await? {
+                return Ok(frame);
+            }
+        }
+    }
+
+    async fn read_frame(
+        &mut self,
+        timestamp: Instant,
+    ) -> Result<Option<FgFrame<MAX_FRAME_LENGTH>>, ControllerError> {
+        let available = self.driver.available_rx().await? as usize;
+        if available < Phr::LENGTH {
+            // The packet was flushed by the chip, e.g. due to crc autoflush
+            return Ok(None);
+        }
+
+        let mut phr = [0; Phr::LENGTH];
+        unsafe { self.driver.read_fifo_raw(&mut phr).await? };
+        let phr = Phr::from_bytes(phr);
+
+        let length = (phr.length as usize).saturating_sub(phr.fcs_length());
+        let status_length = if self.append_status { STATUS_LENGTH } else { 0 };
+        if length > MAX_FRAME_LENGTH || Phr::LENGTH + length + status_length > available {
+            // The frame is not fully received, so discard it
+            return Ok(None);
+        }
+
+        let mut payload = Vec::new();
+        payload.resize_default(length).unwrap();
+        unsafe { self.driver.read_fifo_raw(&mut payload).await? };
+
+        if self.driver.last_status().unwrap().state() == State::RX_FIFO_ERROR {
+            return Err(ControllerError::RxFifoOverflow);
+        }
+
+        let mut frame = FgFrame {
+            timestamp,
+            phr,
+            payload,
+            rssi: None,
+            lqi: None,
+            crc_ok: None,
+        };
+
+        if self.append_status {
+            let mut status = [0; STATUS_LENGTH];
+            unsafe { self.driver.read_fifo_raw(&mut status).await? };
+
+            let lqi_val = LqiVal::from(status[1]);
+            let crc_autoflush = self
+                .config
+                .get::<FifoCfg>()
+                .unwrap_or_default()
+                .crc_autoflush();
+            if crc_autoflush && !lqi_val.pkt_crc_ok() {
+                return Ok(None);
+            }
+
+            frame.rssi = self.driver.map_rssi(status[0]);
+            frame.lqi = Some(lqi_val.lqi());
+            frame.crc_ok = Some(lqi_val.pkt_crc_ok());
+        }
+
+        Ok(Some(frame))
+    }
+
+    /// Transition chip to idle state
+    pub async fn idle(&mut self) -> Result<(), ControllerError> {
+        self.driver.strobe_until_idle(Strobe::SIDLE).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_encode_and_decode_phr() {
+        let phr = Phr {
+            fcs_16bit: true,
+            whitening: true,
+            length: 0x123,
+        };
+
+        assert_eq!([0x19, 0x23], phr.to_bytes());
+        assert_eq!(phr, Phr::from_bytes([0x19, 0x23]));
+        assert_eq!(2, phr.fcs_length());
+    }
+}
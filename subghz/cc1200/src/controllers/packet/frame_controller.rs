@@ -0,0 +1,273 @@
+use core::marker::PhantomData;
+
+use embassy_time::Instant;
+use embedded_hal_async::{delay::DelayNs, spi};
+use heapless::Vec;
+
+use crate::{
+    cmd::Strobe,
+    gpio::{Gpio, GpioOutput},
+    regs::{
+        ext::LqiVal,
+        pri::{
+            AddrCheckCfgValue, CrcCfgValue, FifoCfg, LengthConfigValue, Mdmcfg1, PktCfg0, PktCfg1,
+            PktCfg2, PktFormatValue, PktLen,
+        },
+        Iocfg,
+    },
+    ConfigPatch, Driver, Rssi, State, RX_FIFO_SIZE,
+};
+
+use super::ControllerError;
+
+/// The number of status bytes appended to a packet when `PKT_CFG1.APPEND_STATUS` is set
+const STATUS_LENGTH: usize = 2;
+
+/// A complete frame received in variable packet length mode
+#[derive(Debug)]
+pub struct Frame<const MAX_FRAME_LENGTH: usize> {
+    /// The timestamp sampled when the sync word was received.
+    pub timestamp: Instant,
+    /// The received bytes, excluding the length byte.
+    pub payload: Vec<u8, MAX_FRAME_LENGTH>,
+    /// The rssi from the appended status bytes, if status is appended and the rssi is valid.
+    pub rssi: Option<Rssi>,
+    /// The link quality indicator from the appended status bytes, if status is appended.
+    pub lqi: Option<u8>,
+    /// Whether the crc check passed, if status is appended.
+    pub crc_ok: Option<bool>,
+    /// The address byte that passed the address check, if address check is enabled.
+    /// The address is also the first byte of the payload.
+    pub address: Option<u8>,
+}
+
+pub struct FrameController<'a, Spi, Delay, ResetPin, IrqGpio, IrqPin, const MAX_FRAME_LENGTH: usize>
+where
+    Spi: spi::SpiDevice,
+    Delay: DelayNs,
+    ResetPin: embedded_hal::digital::OutputPin,
+    IrqGpio: Gpio,
+    IrqPin: embedded_hal_async::digital::Wait,
+{
+    driver: &'a mut Driver<Spi, Delay, ResetPin>,
+    config: ConfigPatch<'a>,
+    append_status: bool,
+    crc_autoflush: bool,
+    address_check: bool,
+    dropped: u32,
+    irq_gpio: PhantomData<IrqGpio>,
+    irq_pin: &'a mut IrqPin,
+}
+
+impl<'a, Spi, Delay, ResetPin, IrqGpio, IrqPin, const MAX_FRAME_LENGTH: usize>
+    FrameController<'a, Spi, Delay, ResetPin, IrqGpio, IrqPin, MAX_FRAME_LENGTH>
+where
+    Spi: spi::SpiDevice,
+    Delay: DelayNs,
+    ResetPin: embedded_hal::digital::OutputPin,
+    IrqGpio: Gpio,
+    IrqPin: embedded_hal_async::digital::Wait,
+{
+    /// Create a new frame controller
+    ///
+    /// The length byte, the frame and the appended status must fit in the rx fifo.
+    pub fn new(
+        driver: &'a mut Driver<Spi, Delay, ResetPin>,
+        irq_pin: &'a mut IrqPin,
+        config: ConfigPatch<'a>,
+    ) -> Self {
+        assert!(1 + MAX_FRAME_LENGTH + STATUS_LENGTH <= RX_FIFO_SIZE);
+
+        Self {
+            driver,
+            config,
+            append_status: config.get::<PktCfg1>().unwrap_or_default().append_status(),
+            crc_autoflush: config.get::<FifoCfg>().unwrap_or_default().crc_autoflush(),
+            address_check: config.get::<PktCfg1>().unwrap_or_default().addr_check_cfg()
+                != AddrCheckCfgValue::NoAddressCheck,
+            dropped: 0,
+            irq_gpio: PhantomData,
+            irq_pin,
+        }
+    }
+
+    /// Initialize the chip by sending a configuration and entering idle state
+    pub async fn init(&mut self) -> Result<(), ControllerError> {
+        self.driver.write_patch(self.config).await?;
+
+        // FIFO must be enabled
+        let mut mdmcfg1 = self.config.get::<Mdmcfg1>().unwrap_or_default();
+        mdmcfg1.set_fifo_en(true);
+        self.driver.write_reg(mdmcfg1).await?;
+
+        // Packet mode must be Normal/FIFO mode
+        let mut pktcfg2 = self.config.get::<PktCfg2>().unwrap_or_default();
+        pktcfg2.set_pkt_format(PktFormatValue::NormalModeFifoMode);
+        self.driver.write_reg(pktcfg2).await?;
+
+        // The first byte after the sync word is the frame length
+        let mut pktcfg0 = self.config.get::<PktCfg0>().unwrap_or_default();
+        pktcfg0.set_length_config(LengthConfigValue::VariablePacketLengthMode);
+        self.driver.write_reg(pktcfg0).await?;
+
+        // Frames longer than the maximum are discarded by the chip
+        let pktlen = PktLen(MAX_FRAME_LENGTH as u8);
+        self.driver.write_reg(pktlen).await?;
+
+        self.idle().await?;
+
+        Ok(())
+    }
+
+    /// Get the number of frames that were detected but discarded,
+    /// e.g. because they failed the address check or were not fully received.
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    /// Receive the next complete frame
+    ///
+    /// Frames that fail the crc check are discarded if `FIFO_CFG.CRC_AUTOFLUSH` is set.
+    /// Frames that fail the address check are discarded by the chip if address check is enabled.
+    pub async fn receive(&mut self) -> Result<Frame<MAX_FRAME_LENGTH>, ControllerError> {
+        self.receive_after(Strobe::SRX).await
+    }
+
+    /// Receive the next complete frame, where the receiver is started by `strobe` after the rx fifo is flushed
+    pub(super) async fn receive_after(
+        &mut self,
+        strobe: Strobe,
+    ) -> Result<Frame<MAX_FRAME_LENGTH>, ControllerError> {
+        // Setup fifo pin
+        // Asserted when sync word has been received and de-asserted at the end of the packet
+        let mut irq_iocfg = IrqGpio::Iocfg::default();
+        irq_iocfg.set_gpio_cfg(GpioOutput::PKT_SYNC_RXTX);
+        self.driver.write_reg(irq_iocfg).await?;
+
+        loop {
+            // Flush RX buffer before we start the receiver
+            // This can only be safely done if the chip is in IDLE state.
+            self.driver.strobe_until_idle(Strobe::SIDLE).await?;
+            self.driver.strobe(Strobe::SFRX).await?;
+            self.driver.strobe(strobe).await?;
+
+            // Wait for SOF to be detected
+            self.irq_pin.wait_for_high().await.unwrap();
+            let timestamp = Instant::now();
+
+            // Wait for the packet to be fully received
+            self.irq_pin.wait_for_low().await.unwrap();
+
+            if let Some(frame) = self.read_frame(timestamp).await? {
+                return Ok(frame);
+            }
+
+            self.dropped = self.dropped.wrapping_add(1);
+        }
+    }
+
+    /// Receive the next complete frame that passed the crc check
+    ///
+    /// The irq pin is configured as `PKT_CRC_OK`, so it is only asserted when a frame with a valid crc is received.
+    /// Frames that fail the crc check do not wake up the controller, and the receiver continues to listen.
+    /// The timestamp is sampled when the frame is fully received and not when the sync word is received.
+    /// The crc check must be enabled in `PKT_CFG1.CRC_CFG`.
+    pub async fn receive_crc_ok(&mut self) -> Result<Frame<MAX_FRAME_LENGTH>, ControllerError> {
+        assert_ne!(
+            CrcCfgValue::CrcDisabledForTxAndRx,
+            self.config.get::<PktCfg1>().unwrap_or_default().crc_cfg()
+        );
+
+        // Setup fifo pin
+        // Asserted when a packet with a valid crc has been received and de-asserted when RX is entered
+        let mut irq_iocfg = IrqGpio::Iocfg::default();
+        irq_iocfg.set_gpio_cfg(GpioOutput::PKT_CRC_OK);
+        self.driver.write_reg(irq_iocfg).await?;
+
+        loop {
+            // Flush RX buffer before we start the receiver
+            // This can only be safely done if the chip is in IDLE state.
+            self.driver.strobe_until_idle(Strobe::SIDLE).await?;
+            self.driver.strobe(Strobe::SFRX).await?;
+            self.driver.strobe(Strobe::SRX).await?;
+
+            // Wait for a packet with a valid crc to be received
+            self.irq_pin.wait_for_high().await.unwrap();
+            let timestamp = Instant::now();
+
+            if let Some(frame) = self.read_frame(timestamp).await? {
+                return Ok(frame);
+            }
+
+            self.dropped = self.dropped.wrapping_add(1);
+        }
+    }
+
+    pub(super) fn driver(&mut self) -> &mut Driver<Spi, Delay, ResetPin> {
+        self.driver
+    }
+
+    async fn read_frame(
+        &mut self,
+        timestamp: Instant,
+    ) -> Result<Option<Frame<MAX_FRAME_LENGTH>>, ControllerError> {
+        let available = self.driver.available_rx().await? as usize;
+        if available == 0 {
+            // The packet was flushed by the chip, e.g. due to crc autoflush
+            return Ok(None);
+        }
+
+        let mut length = [0];
+        unsafe { self.driver.read_fifo_raw(&mut length).await? };
+        let length = length[0] as usize;
+
+        let status_length = if self.append_status { STATUS_LENGTH } else { 0 };
+        if length > MAX_FRAME_LENGTH || 1 + length + status_length > available {
+            // The frame is not fully received, so discard it
+            return Ok(None);
+        }
+
+        let mut payload = Vec::new();
+        payload.resize_default(length).unwrap();
+        unsafe { self.driver.read_fifo_raw(&mut payload).await? };
+
+        if self.driver.last_status().unwrap().state() == State::RX_FIFO_ERROR {
+            return Err(ControllerError::RxFifoOverflow);
+        }
+
+        let mut frame = Frame {
+            timestamp,
+            payload,
+            rssi: None,
+            lqi: None,
+            crc_ok: None,
+            address: None,
+        };
+
+        if self.address_check {
+            frame.address = frame.payload.first().copied();
+        }
+
+        if self.append_status {
+            let mut status = [0; STATUS_LENGTH];
+            unsafe { self.driver.read_fifo_raw(&mut status).await? };
+
+            let lqi_val = LqiVal::from(status[1]);
+            if self.crc_autoflush && !lqi_val.pkt_crc_ok() {
+                return Ok(None);
+            }
+
+            frame.rssi = self.driver.map_rssi(status[0]);
+            frame.lqi = Some(lqi_val.lqi());
+            frame.crc_ok = Some(lqi_val.pkt_crc_ok());
+        }
+
+        Ok(Some(frame))
+    }
+
+    /// Transition chip to idle state
+    pub async fn idle(&mut self) -> Result<(), ControllerError> {
+        self.driver.strobe_until_idle(Strobe::SIDLE).await?;
+        Ok(())
+    }
+}
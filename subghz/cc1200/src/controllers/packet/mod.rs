@@ -1,5 +1,11 @@
 mod controller;
 mod error;
+mod fg_controller;
+mod frame_controller;
+mod wor_controller;
 
 pub use controller::{PacketController, RxToken};
 pub use error::ControllerError;
+pub use fg_controller::{FgController, FgFrame, Phr};
+pub use frame_controller::{Frame, FrameController};
+pub use wor_controller::WorController;
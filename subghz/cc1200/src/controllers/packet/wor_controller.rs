@@ -0,0 +1,77 @@
+use embedded_hal_async::{delay::DelayNs, spi};
+
+use crate::{
+    cmd::Strobe,
+    gpio::Gpio,
+    regs::pri::{RcModeValue, WorCfg0},
+    ConfigPatch, Driver,
+};
+
+use super::{ControllerError, Frame, FrameController};
+
+/// Receive frames in eWOR (enhanced Wake-on-Radio) sniff mode.
+///
+/// The chip sleeps between wake-ups and listens for a sync word in each RX window.
+/// The sniff period and RX window are configured with `Config::set_sniff_mode()`.
+/// The eWOR timer is clocked by the RCOSC, so the configuration must have
+/// `WOR_CFG0.RC_PD` cleared and `WOR_CFG0.RC_MODE` set to calibration enabled.
+pub struct WorController<'a, Spi, Delay, ResetPin, IrqGpio, IrqPin, const MAX_FRAME_LENGTH: usize>
+where
+    Spi: spi::SpiDevice,
+    Delay: DelayNs,
+    ResetPin: embedded_hal::digital::OutputPin,
+    IrqGpio: Gpio,
+    IrqPin: embedded_hal_async::digital::Wait,
+{
+    frames: FrameController<'a, Spi, Delay, ResetPin, IrqGpio, IrqPin, MAX_FRAME_LENGTH>,
+}
+
+impl<'a, Spi, Delay, ResetPin, IrqGpio, IrqPin, const MAX_FRAME_LENGTH: usize>
+    WorController<'a, Spi, Delay, ResetPin, IrqGpio, IrqPin, MAX_FRAME_LENGTH>
+where
+    Spi: spi::SpiDevice,
+    Delay: DelayNs,
+    ResetPin: embedded_hal::digital::OutputPin,
+    IrqGpio: Gpio,
+    IrqPin: embedded_hal_async::digital::Wait,
+{
+    /// Create a new eWOR controller
+    pub fn new(
+        driver: &'a mut Driver<Spi, Delay, ResetPin>,
+        irq_pin: &'a mut IrqPin,
+        config: ConfigPatch<'a>,
+    ) -> Self {
+        let wor_cfg0 = config.get::<WorCfg0>().unwrap_or_default();
+        assert!(!wor_cfg0.rc_pd(), "The RCOSC must be running");
+        assert_eq!(RcModeValue::RcoscCalibrationEnabled, wor_cfg0.rc_mode());
+
+        Self {
+            frames: FrameController::new(driver, irq_pin, config),
+        }
+    }
+
+    /// Initialize the chip by sending a configuration, calibrating the RCOSC and entering idle state
+    pub async fn init(&mut self) -> Result<(), ControllerError> {
+        self.frames.init().await?;
+
+        // Calibrate the RCOSC, this requires RC_PD to be cleared before the calibration is started
+        let driver = self.frames.driver();
+        driver.strobe(Strobe::SCAL).await?;
+        driver.strobe_until_idle(Strobe::SNOP).await?;
+
+        Ok(())
+    }
+
+    /// Start sniff mode and wait for the next complete frame.
+    ///
+    /// The chip wakes periodically and the irq pin is asserted when a sync word is detected.
+    /// The chip enters idle state when the frame has been received.
+    pub async fn sniff(&mut self) -> Result<Frame<MAX_FRAME_LENGTH>, ControllerError> {
+        self.frames.receive_after(Strobe::SWOR).await
+    }
+
+    /// Transition chip to idle state
+    pub async fn idle(&mut self) -> Result<(), ControllerError> {
+        self.frames.idle().await
+    }
+}
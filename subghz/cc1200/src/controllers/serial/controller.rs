@@ -9,7 +9,7 @@ use crate::{
     cmd::Strobe,
     gpio::{Gpio, GpioOutput},
     regs::{
-        ext::FreqoffCfg,
+        ext::{FreqoffCfg, ModemStatus1},
         pri::{
             AgcCfg3, AgcSyncBehaviourValue, FifoCfg, LengthConfigValue, Mdmcfg1, PktCfg0, PktCfg2,
             PktFormatValue, RfendCfg1, RxoffModeValue,
@@ -181,9 +181,19 @@ impl<
 
                     let rssi = self.driver.read_rssi().await.unwrap();
                     unsafe { self.driver.read_fifo_raw(&mut chunk_bytes).await.unwrap() };
+                    let state = self.driver.last_status().unwrap().state();
+
+                    // The fifo content is corrupt if the fifo has overflowed or underflowed since the last flush
+                    let fifo_error = match self.driver.read_reg::<ModemStatus1>().await {
+                        Ok(status) => status.rxfifo_overflow() || status.rxfifo_underflow(),
+                        Err(e) => {
+                            yield Err(e.into());
+                            continue;
+                        }
+                    };
 
-                    match self.driver.last_status().unwrap().state() {
-                        State::RX => {
+                    match state {
+                        State::RX if !fifo_error => {
                             yield Ok(RxChunk {
                                 timestamp,
                                 rssi,
@@ -215,16 +225,15 @@ impl<
                                 yield result;
                             }
                         }
-                        State::CALIBRATE => {}
-                        State::SETTLING => {}
-                        State::RX_FIFO_ERROR => {
+                        State::CALIBRATE if !fifo_error => {}
+                        State::SETTLING if !fifo_error => {}
+                        State::RX | State::CALIBRATE | State::SETTLING | State::RX_FIFO_ERROR => {
                             let result: Result<RxChunk<CHUNK_SIZE>, ControllerError> = async {
                                 // Enter idle state
                                 self.driver.strobe_until_idle(Strobe::SIDLE).await?;
 
-                                // Re-start receiver
-                                self.driver.strobe(Strobe::SFRX).await?;
-                                self.driver.strobe(Strobe::SRX).await?;
+                                // Flush the fifo, re-arm the fifo pin and re-start receiver
+                                self.setup_receive().await?;
 
                                 Err(ControllerError::FifoOverflow)
                             }
@@ -5,6 +5,15 @@ use crate::{DriverError, State};
 pub enum ControllerError {
     Recalibrated,
     FifoOverflow,
+    TxFifoUnderflow,
+    /// The transmission did not complete in time
+    TxTimeout,
+    /// The channel was busy, so the transmission was suppressed
+    ChannelBusy,
+    /// The carrier sense did not become valid in time for the clear channel assessment
+    CarrierSenseTimeout,
+    /// The payload could not be read from the reader
+    Read,
     Driver(DriverError),
     UnrecoverableChipState(State),
     Offline,
@@ -1,5 +1,7 @@
 mod controller;
 mod error;
+mod tx_controller;
 
 pub use controller::{RxChunk, SerialController};
 pub use error::ControllerError;
+pub use tx_controller::TxController;
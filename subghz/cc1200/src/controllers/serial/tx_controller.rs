@@ -0,0 +1,388 @@
+use core::marker::PhantomData;
+
+use embassy_time::{with_timeout, Duration, TimeoutError};
+use embedded_hal_async::{delay::DelayNs, spi};
+use embedded_io_async::Read;
+
+use crate::{
+    cmd::Strobe,
+    gpio::{Gpio, GpioOutput},
+    regs::{
+        ext::{MarcStatus0, MarcStatus1, ModemStatus0, Rssi0},
+        pri::{
+            CcaModeValue, FifoCfg, LengthConfigValue, Mdmcfg1, PktCfg0, PktCfg2, PktFormatValue,
+            PktLen,
+        },
+        Iocfg,
+    },
+    ConfigPatch, Driver, DriverError, TX_FIFO_SIZE,
+};
+
+use super::ControllerError;
+
+/// The MARC_STATUS_OUT value reported when a transmission has completed successfully
+const MARC_STATUS_TX_FINISHED: u8 = 0x40;
+
+/// The MARC_STATUS_OUT value reported when TX was suppressed because the channel was busy
+const MARC_STATUS_TXONCCA_FAILED: u8 = 0x0B;
+
+/// The interval between MARC_STATUS1 polls while waiting for the transmission to complete
+const TX_POLL_INTERVAL_US: u32 = 1_000;
+
+/// The number of MARC_STATUS1 polls before the transmission is considered stuck.
+/// This allows a full TX fifo to drain at the lowest symbol rates.
+const TX_POLL_LIMIT: usize = 5_000;
+
+/// The interval between RSSI0 polls while waiting for the carrier sense to become valid
+const CS_POLL_INTERVAL_US: u32 = 100;
+
+/// The number of RSSI0 polls before the carrier sense is considered stuck
+const CS_POLL_LIMIT: usize = 100;
+
+/// The time to wait for the TX fifo to drain below threshold when streaming a payload
+const TX_REFILL_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct TxController<'a, Spi, Delay, ResetPin, IrqGpio, IrqPin>
+where
+    Spi: spi::SpiDevice,
+    Delay: DelayNs,
+    ResetPin: embedded_hal::digital::OutputPin,
+    IrqGpio: Gpio,
+    IrqPin: embedded_hal_async::digital::Wait,
+{
+    driver: &'a mut Driver<Spi, Delay, ResetPin>,
+    config: ConfigPatch<'a>,
+    irq_gpio: PhantomData<IrqGpio>,
+    irq_pin: &'a mut IrqPin,
+}
+
+impl<
+        'a,
+        Spi: spi::SpiDevice,
+        Delay: DelayNs,
+        ResetPin: embedded_hal::digital::OutputPin,
+        IrqGpio: Gpio,
+        IrqPin: embedded_hal_async::digital::Wait,
+    > TxController<'a, Spi, Delay, ResetPin, IrqGpio, IrqPin>
+{
+    /// Create a new transmit controller
+    pub fn new(
+        driver: &'a mut Driver<Spi, Delay, ResetPin>,
+        irq_pin: &'a mut IrqPin,
+        config: ConfigPatch<'a>,
+    ) -> Self {
+        Self {
+            driver,
+            config,
+            irq_gpio: PhantomData,
+            irq_pin,
+        }
+    }
+
+    /// Initialize the chip by sending a configuration and entering idle state
+    pub async fn init(&mut self) -> Result<(), ControllerError> {
+        self.driver.write_patch(self.config).await?;
+
+        // FIFO must be enabled
+        let mut mdmcfg1 = self.config.get::<Mdmcfg1>().unwrap_or_default();
+        mdmcfg1.set_fifo_en(true);
+        self.driver.write_reg(mdmcfg1).await?;
+
+        // Packet mode must be Normal/FIFO mode
+        let mut pktcfg2 = self.config.get::<PktCfg2>().unwrap_or_default();
+        pktcfg2.set_pkt_format(PktFormatValue::NormalModeFifoMode);
+        self.driver.write_reg(pktcfg2).await?;
+
+        self.idle().await?;
+
+        Ok(())
+    }
+
+    /// Transmit a payload and wait for the transmission to complete.
+    /// The TX fifo is refilled while transmitting if the payload does not fit in the fifo.
+    /// `ControllerError::TxTimeout` is returned if the transmission does not complete in time.
+    pub async fn transmit(&mut self, payload: &[u8]) -> Result<(), ControllerError> {
+        let (mut pktcfg0, mut pending) = self.prepare(payload).await?;
+
+        // Start transmitter.
+        self.driver.strobe(Strobe::STX).await?;
+
+        // There is room for at least this many bytes when the fifo is drained below threshold
+        let fifocfg = self.config.get::<FifoCfg>().unwrap_or_default();
+        let refill_length = TX_FIFO_SIZE - fifocfg.bytes_in_txfifo() as usize;
+
+        while !pending.is_empty() {
+            // Wait for fifo buffer to go below threshold.
+            self.irq_pin.wait_for_low().await.unwrap();
+
+            if pktcfg0.length_config() != LengthConfigValue::FixedPacketLengthMode
+                && pending.len() <= TX_FIFO_SIZE
+            {
+                // We are so far in the transmission that we can now transition from
+                // infinite packet length mode to fixed packet length mode.
+                pktcfg0.set_length_config(LengthConfigValue::FixedPacketLengthMode);
+                self.driver.write_reg(pktcfg0).await?;
+            }
+
+            let length = usize::min(pending.len(), refill_length);
+            self.driver.write_fifo(&pending[..length]).await?;
+            pending = &pending[length..];
+
+            self.ensure_no_underflow().await?;
+        }
+
+        self.wait_until_transmitted().await
+    }
+
+    /// Transmit `length` bytes read from `reader` and wait for the transmission to complete.
+    ///
+    /// The transmission is started in infinite packet length mode if the packet is longer than 256 bytes,
+    /// and switched to fixed packet length mode for the tail so that the packet terminates after `length` bytes.
+    /// The TX fifo is refilled from the reader whenever it is drained below threshold.
+    /// `ControllerError::TxFifoUnderflow` is returned if the reader could not keep up with the transmission,
+    /// and `ControllerError::TxTimeout` if the fifo does not drain below threshold in time.
+    pub async fn transmit_stream<R: Read>(
+        &mut self,
+        length: usize,
+        reader: &mut R,
+    ) -> Result<(), ControllerError> {
+        if length == 0 {
+            return Err(DriverError::InvalidPayloadLength.into());
+        }
+
+        // Flush TX buffer before we fill it
+        // This can only be safely done if the chip is in IDLE state.
+        self.driver.strobe(Strobe::SFTX).await?;
+
+        let mut pktcfg0 = self.set_packet_length(length).await?;
+
+        // Fill the fifo
+        let mut buffer = [0; TX_FIFO_SIZE];
+        let chunk = &mut buffer[..usize::min(length, TX_FIFO_SIZE)];
+        reader
+            .read_exact(chunk)
+            .await
+            .map_err(|_| ControllerError::Read)?;
+        self.driver.write_fifo(chunk).await?;
+        let mut pending = length - chunk.len();
+
+        self.setup_fifo_pin().await?;
+
+        // Clear any stale transmission status
+        self.driver.read_reg::<MarcStatus1>().await?;
+
+        // Start transmitter.
+        self.driver.strobe(Strobe::STX).await?;
+
+        // There is room for at least this many bytes when the fifo is drained below threshold
+        let fifocfg = self.config.get::<FifoCfg>().unwrap_or_default();
+        let refill_length = TX_FIFO_SIZE - fifocfg.bytes_in_txfifo() as usize;
+
+        while pending > 0 {
+            // Wait for fifo buffer to go below threshold.
+            match with_timeout(TX_REFILL_TIMEOUT, self.irq_pin.wait_for_low()).await {
+                Ok(result) => result.unwrap(),
+                Err(TimeoutError) => {
+                    // Leave TX so that the chip is not left transmitting
+                    self.driver.strobe_until_idle(Strobe::SIDLE).await?;
+                    self.driver.strobe(Strobe::SFTX).await?;
+
+                    return Err(ControllerError::TxTimeout);
+                }
+            }
+
+            if pktcfg0.length_config() != LengthConfigValue::FixedPacketLengthMode
+                && pending <= TX_FIFO_SIZE
+            {
+                // We are so far in the transmission that we can now transition from
+                // infinite packet length mode to fixed packet length mode.
+                pktcfg0.set_length_config(LengthConfigValue::FixedPacketLengthMode);
+                self.driver.write_reg(pktcfg0).await?;
+            }
+
+            let chunk = &mut buffer[..usize::min(pending, refill_length)];
+            reader
+                .read_exact(chunk)
+                .await
+                .map_err(|_| ControllerError::Read)?;
+            self.driver.write_fifo(chunk).await?;
+            pending -= chunk.len();
+
+            self.ensure_no_underflow().await?;
+        }
+
+        self.wait_until_transmitted().await
+    }
+
+    async fn wait_until_transmitted(&mut self) -> Result<(), ControllerError> {
+        for _ in 0..TX_POLL_LIMIT {
+            let marc_status1 = self.driver.read_reg::<MarcStatus1>().await?;
+            if marc_status1.marc_status_out() == MARC_STATUS_TX_FINISHED {
+                return Ok(());
+            }
+
+            self.ensure_no_underflow().await?;
+            self.driver.delay_us(TX_POLL_INTERVAL_US).await;
+        }
+
+        // Leave TX so that the chip is not left transmitting
+        self.driver.strobe_until_idle(Strobe::SIDLE).await?;
+        self.driver.strobe(Strobe::SFTX).await?;
+
+        Err(ControllerError::TxTimeout)
+    }
+
+    /// Transmit a payload only if the channel is clear, and wait for the transmission to complete.
+    ///
+    /// The receiver is started to assess the channel using `cca_mode`, before the transmitter is started.
+    /// The carrier sense threshold is configured with `Driver::set_carrier_sense_threshold()`.
+    /// `ControllerError::ChannelBusy` is returned if the channel was busy and the transmission was suppressed,
+    /// and `ControllerError::CarrierSenseTimeout` if the carrier sense did not become valid.
+    /// The payload must fit in the TX fifo.
+    pub async fn transmit_cca(
+        &mut self,
+        payload: &[u8],
+        cca_mode: CcaModeValue,
+    ) -> Result<(), ControllerError> {
+        if payload.len() > TX_FIFO_SIZE {
+            return Err(DriverError::InvalidPayloadLength.into());
+        }
+
+        let mut pktcfg2 = self.config.get::<PktCfg2>().unwrap_or_default();
+        pktcfg2.set_pkt_format(PktFormatValue::NormalModeFifoMode);
+        pktcfg2.set_cca_mode(cca_mode);
+        self.driver.write_reg(pktcfg2).await?;
+
+        let result = self.transmit_on_clear_channel(payload).await;
+
+        // Restore the configured cca mode
+        pktcfg2.set_cca_mode(self.config.get::<PktCfg2>().unwrap_or_default().cca_mode());
+        self.driver.write_reg(pktcfg2).await?;
+
+        result
+    }
+
+    async fn transmit_on_clear_channel(&mut self, payload: &[u8]) -> Result<(), ControllerError> {
+        self.prepare(payload).await?;
+
+        // Start the receiver and wait for the carrier sense to be valid
+        self.driver.strobe(Strobe::SRX).await?;
+        self.wait_for_carrier_sense().await?;
+
+        // The chip only enters TX if the channel is clear
+        self.driver.strobe(Strobe::STX).await?;
+
+        // Wait for transmission to complete, or for the clear channel assessment to fail.
+        loop {
+            let marc_status1 = self.driver.read_reg::<MarcStatus1>().await?;
+            match marc_status1.marc_status_out() {
+                MARC_STATUS_TX_FINISHED => return Ok(()),
+                MARC_STATUS_TXONCCA_FAILED
+                    if self
+                        .driver
+                        .read_reg::<MarcStatus0>()
+                        .await?
+                        .txoncca_failed() =>
+                {
+                    // The chip remains in RX, so return to IDLE and discard the payload
+                    self.driver.strobe_until_idle(Strobe::SIDLE).await?;
+                    self.driver.strobe(Strobe::SFTX).await?;
+
+                    return Err(ControllerError::ChannelBusy);
+                }
+                _ => {}
+            }
+
+            self.ensure_no_underflow().await?;
+        }
+    }
+
+    async fn wait_for_carrier_sense(&mut self) -> Result<(), ControllerError> {
+        for _ in 0..CS_POLL_LIMIT {
+            if self.driver.read_reg::<Rssi0>().await?.carrier_sense_valid() {
+                return Ok(());
+            }
+
+            self.driver.delay_us(CS_POLL_INTERVAL_US).await;
+        }
+
+        // Leave RX and discard the payload
+        self.driver.strobe_until_idle(Strobe::SIDLE).await?;
+        self.driver.strobe(Strobe::SFTX).await?;
+
+        Err(ControllerError::CarrierSenseTimeout)
+    }
+
+    /// Flush the TX fifo, configure the frame length and fill the fifo with the start of the payload.
+    /// Returns the packet configuration and the part of the payload that did not fit in the fifo.
+    async fn prepare<'p>(
+        &mut self,
+        payload: &'p [u8],
+    ) -> Result<(PktCfg0, &'p [u8]), ControllerError> {
+        if payload.is_empty() {
+            return Err(DriverError::InvalidPayloadLength.into());
+        }
+
+        // Flush TX buffer before we fill it
+        // This can only be safely done if the chip is in IDLE state.
+        self.driver.strobe(Strobe::SFTX).await?;
+
+        let pktcfg0 = self.set_packet_length(payload.len()).await?;
+
+        // Fill the fifo
+        let length = usize::min(payload.len(), TX_FIFO_SIZE);
+        self.driver.write_fifo(&payload[..length]).await?;
+
+        self.setup_fifo_pin().await?;
+
+        // Clear any stale transmission status
+        self.driver.read_reg::<MarcStatus1>().await?;
+
+        Ok((pktcfg0, &payload[length..]))
+    }
+
+    /// Setup the irq pin to be asserted when the TX FIFO is filled above threshold
+    async fn setup_fifo_pin(&mut self) -> Result<(), ControllerError> {
+        let mut irq_iocfg = IrqGpio::Iocfg::default();
+        irq_iocfg.set_gpio_cfg(GpioOutput::TXFIFO_THR);
+        self.driver.write_reg(irq_iocfg).await?;
+        Ok(())
+    }
+
+    /// Configure fixed or infinite packet length mode and the packet length for a packet of `length` bytes
+    async fn set_packet_length(&mut self, length: usize) -> Result<PktCfg0, ControllerError> {
+        // Set frame length configuration
+        let mut pktcfg0 = self.config.get::<PktCfg0>().unwrap_or_default();
+        if length <= 256 {
+            pktcfg0.set_length_config(LengthConfigValue::FixedPacketLengthMode);
+        } else {
+            pktcfg0.set_length_config(LengthConfigValue::InfinitePacketLengthMode);
+        }
+        self.driver.write_reg(pktcfg0).await?;
+
+        // Set frame length
+        let pktlen = PktLen((length & 0xFF) as u8);
+        self.driver.write_reg(pktlen).await?;
+
+        Ok(pktcfg0)
+    }
+
+    async fn ensure_no_underflow(&mut self) -> Result<(), ControllerError> {
+        let modem_status0 = self.driver.read_reg::<ModemStatus0>().await?;
+        if modem_status0.txfifo_underflow() {
+            // It seems that we came too late with the FIFO refill.
+            self.driver.strobe_until_idle(Strobe::SIDLE).await?;
+            self.driver.strobe(Strobe::SFTX).await?;
+
+            return Err(ControllerError::TxFifoUnderflow);
+        }
+
+        Ok(())
+    }
+
+    /// Transition chip to idle state
+    pub async fn idle(&mut self) -> Result<(), ControllerError> {
+        self.driver.strobe_until_idle(Strobe::SIDLE).await?;
+        Ok(())
+    }
+}
@@ -1,14 +1,23 @@
 use core::convert::Infallible;
 
+#[cfg(feature = "rssi-history")]
+use crate::RssiHistory;
 use crate::{
     cmd::{BurstHeader, Response, SingleCommand, Strobe, StrobeCommand},
+    gpio::{Gpio, Gpio3Output, GpioOutput},
     regs::{
         self,
-        ext::{self, Freqoff0, Freqoff1},
-        Register, RegisterAddress,
+        ext::{self, ExtCtrl, Freqoff0, Freqoff1, FscalCtrl},
+        pri::{
+            AgcCsThr, DevAddr, FsAutocalValue, FsCfg, FsdBandselectValue, Iocfg3, PktCfg1,
+            SettlingCfg,
+        },
+        Iocfg, MarcStateValue, Register, RegisterAddress, REGISTER_NAMES,
     },
+    snapshot::write_register,
     statusbyte::{State, StatusByte},
-    Config, ConfigPatch, DriverError, PartNumber, Rssi, RX_FIFO_SIZE, TX_FIFO_SIZE,
+    BroadcastMode, Config, ConfigPatch, DriverError, PartNumber, RegisterSnapshot, Rssi,
+    RX_FIFO_SIZE, TX_FIFO_SIZE, XOSC_FREQUENCY,
 };
 use embedded_hal::{
     digital::{self, OutputPin},
@@ -22,6 +31,30 @@ use futures::{
 
 const DEFAULT_RSSI_OFFSET: i16 = -99; // The default offset defined in the users guide
 
+/// The AES block and key size in bytes
+pub const AES_BLOCK_SIZE: usize = 16;
+
+/// The number of times `AES.AES_RUN` is polled before the encryption is aborted,
+/// and `AES.AES_ABORT` is polled while the abort sequence completes.
+/// The delay between polls gives a timeout of 10ms regardless of the spi clock.
+const AES_POLL_LIMIT: usize = 100;
+const AES_POLL_INTERVAL_US: u32 = 100;
+const XOSC_POLL_LIMIT: usize = 100;
+const XOSC_POLL_INTERVAL_US: u32 = 100;
+
+/// The number of times the state is polled while waiting for the chip to enter or leave FSTXON
+const FSTXON_POLL_LIMIT: usize = 100;
+const FSTXON_POLL_INTERVAL_US: u32 = 100;
+
+/// High level AES commands in `MARC_SPARE.AES_COMMANDS` operating on the fifo content
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AesFifoCommand {
+    /// Encrypt the TX fifo content
+    TxFifo = 0b1001,
+    /// Decrypt the RX fifo content
+    RxFifo = 0b1010,
+}
+
 pub struct Driver<Spi, Delay, ResetPin = NoPin>
 where
     Delay: delay::DelayNs,
@@ -33,6 +66,8 @@ where
     last_status: Option<StatusByte>,
     rssi_offset: Option<Rssi>,
     freq_off: Option<i16>,
+    #[cfg(feature = "rssi-history")]
+    rssi_history: Option<RssiHistory>,
 }
 
 pub struct NoPin;
@@ -56,6 +91,46 @@ pub struct CalibrationValue<T> {
     pub desired: T,
 }
 
+/// The frequency synthesizer calibration results, which can be restored to skip calibration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FsCalibration {
+    pub fs_vco2: u8,
+    pub fs_vco4: u8,
+    pub fs_chp: u8,
+}
+
+/// Precomputed `FREQ` register values of equally spaced channels for frequency hopping,
+/// so that a hop with [`Driver::hop()`] is a single burst write.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HopTable<const N: usize> {
+    freq: [[u8; 3]; N],
+}
+
+impl<const N: usize> HopTable<N> {
+    /// Compute the `FREQ` values of the channels `base_hz + channel * spacing_hz` for channel 0 to N - 1.
+    ///
+    /// Returns `DriverError::InvalidFrequency` if any channel is outside the band of `base_hz`.
+    pub fn new(base_hz: u32, spacing_hz: u32) -> Result<Self, DriverError> {
+        let mut freq = [[0; 3]; N];
+        for (channel, freq) in freq.iter_mut().enumerate() {
+            let channel = u8::try_from(channel).map_err(|_| DriverError::InvalidFrequency)?;
+            let frequency = channel_frequency(base_hz, spacing_hz, channel)?;
+            let (value, _) = freq_value(frequency);
+            freq.copy_from_slice(&value.to_be_bytes()[1..]);
+        }
+        Ok(Self { freq })
+    }
+
+    /// Get the number of channels in the table.
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+}
+
 impl<T> From<CalibrationValue<T>> for (T, T) {
     fn from(value: CalibrationValue<T>) -> (T, T) {
         (value.measured, value.desired)
@@ -85,6 +160,8 @@ where
             last_status: None,
             rssi_offset: Some(DEFAULT_RSSI_OFFSET),
             freq_off: None,
+            #[cfg(feature = "rssi-history")]
+            rssi_history: None,
         }
     }
 
@@ -96,6 +173,8 @@ where
             last_status: None,
             rssi_offset: Some(DEFAULT_RSSI_OFFSET),
             freq_off: None,
+            #[cfg(feature = "rssi-history")]
+            rssi_history: None,
         }
     }
 
@@ -119,7 +198,7 @@ where
             // The chip reset sequence was sent - wait for chip to become available.
 
             let status = Self::wait_for_xtal(&mut self.spi, &mut self.delay).await?;
-            self.last_status = status;
+            self.set_last_status(status);
 
             if let Some(status) = status
                 && status.chip_rdy()
@@ -132,7 +211,7 @@ where
             const CMD: StrobeCommand = StrobeCommand::new(Strobe::SRES);
             self.spi.write(CMD.request.as_ref()).await?;
             let status = Self::wait_for_xtal(&mut self.spi, &mut self.delay).await?;
-            self.last_status = status;
+            self.set_last_status(status);
 
             if let Some(status) = status
                 && status.chip_rdy()
@@ -144,11 +223,85 @@ where
         }
     }
 
+    /// Reset the chip and bring it to a known IDLE state with empty fifos.
+    ///
+    /// This recovers the chip from e.g. a stuck RX_FIFO_ERR or calibration state.
+    /// [`DriverError::Timeout`] is returned if the crystal oscillator does not stabilize.
+    pub async fn reset_to_idle(&mut self) -> Result<(), DriverError> {
+        self.reset().await?;
+        self.wait_for_xosc_stable().await?;
+
+        self.strobe(Strobe::SFRX).await?;
+        self.strobe(Strobe::SFTX).await?;
+        self.strobe_until_idle(Strobe::SIDLE).await
+    }
+
+    async fn wait_for_xosc_stable(&mut self) -> Result<(), DriverError> {
+        for _ in 0..XOSC_POLL_LIMIT {
+            if self.read_reg::<ext::Xosc1>().await?.xosc_stable() {
+                return Ok(());
+            }
+            self.delay.delay_us(XOSC_POLL_INTERVAL_US).await;
+        }
+
+        Err(DriverError::Timeout)
+    }
+
+    /// Wait using the injected delay, e.g. between status polls in the controllers.
+    #[cfg(feature = "serial-controller")]
+    pub(crate) async fn delay_us(&mut self, us: u32) {
+        self.delay.delay_us(us).await
+    }
+
     /// Get the spi status returned by the last spi operation.
     pub fn last_status(&self) -> Option<StatusByte> {
         self.last_status
     }
 
+    fn set_last_status(&mut self, status: Option<StatusByte>) {
+        #[cfg(feature = "defmt")]
+        if let Some(status) = status {
+            status.log_transition(self.last_status);
+        }
+
+        self.last_status = status;
+    }
+
+    /// Set the carrier sense threshold in dBm used for clear channel assessment.
+    ///
+    /// The threshold is written to `AGC_CS_THR` relative to the rssi offset.
+    /// `DriverError::InvalidCarrierSenseThreshold` is returned if the relative value does not fit in the register.
+    pub async fn set_carrier_sense_threshold(
+        &mut self,
+        threshold: Rssi,
+    ) -> Result<(), DriverError> {
+        let value = threshold
+            .checked_sub(self.rssi_offset.unwrap_or_default())
+            .and_then(|value| i8::try_from(value).ok())
+            .ok_or(DriverError::InvalidCarrierSenseThreshold)?;
+        self.write_reg(AgcCsThr(value as u8)).await
+    }
+
+    /// Set the device address used for packet filtering in RX, and the broadcast addresses that are also accepted.
+    pub async fn set_device_address(
+        &mut self,
+        address: u8,
+        broadcast: BroadcastMode,
+    ) -> Result<(), DriverError> {
+        self.write_reg(DevAddr(address)).await?;
+        self.modify::<PktCfg1>(|pkt_cfg1| pkt_cfg1.set_addr_check_cfg(broadcast.addr_check_cfg()))
+            .await?;
+        Ok(())
+    }
+
+    /// Read the main radio control state.
+    ///
+    /// Use `MarcStateValue::recovery_strobe()` to get the strobe that leaves a fifo error state.
+    pub async fn read_marc_state(&mut self) -> Result<MarcStateValue, DriverError> {
+        let marcstate = self.read_reg::<ext::Marcstate>().await?;
+        Ok(marcstate.marc_state())
+    }
+
     /// Read the chip part number.
     pub async fn read_part_number(&mut self) -> Result<PartNumber, DriverError> {
         let partnumber = self.read_reg::<regs::ext::Partnumber>().await?;
@@ -159,16 +312,46 @@ where
         }
     }
 
+    /// Read the chip part number and ensure that it is the expected part.
+    ///
+    /// This should be called after the chip is reset to catch a miswired spi bus or a wrong part early.
+    pub async fn expect_part_number(&mut self, expected: PartNumber) -> Result<(), DriverError> {
+        if self.read_part_number().await? == expected {
+            Ok(())
+        } else {
+            Err(DriverError::PartNumberMismatch)
+        }
+    }
+
+    /// Read the chip revision.
+    pub async fn read_part_version(&mut self) -> Result<u8, DriverError> {
+        let partversion = self.read_reg::<regs::ext::Partversion>().await?;
+        Ok(partversion.partver())
+    }
+
     /// Read a single register value from chip.
     pub async fn read_reg<R: Register>(&mut self) -> Result<R, DriverError> {
-        let mut cmd = SingleCommand::read(R::ADDRESS);
+        let value = self.read_single(R::ADDRESS).await?;
+        Ok(R::from(value))
+    }
+
+    /// Read an arbitrary extended register value from chip.
+    ///
+    /// The address is the full extended address including the 0x2F prefix, e.g. 0x2F8F for PARTNUMBER.
+    /// `DriverError::InvalidAddress` is returned for any other address.
+    pub async fn read_ext(&mut self, addr: u16) -> Result<u8, DriverError> {
+        self.read_single(extended_address(addr)?).await
+    }
+
+    async fn read_single(&mut self, address: RegisterAddress) -> Result<u8, DriverError> {
+        let mut cmd = SingleCommand::read(address);
 
         self.spi
             .transfer(cmd.response.as_mut(), cmd.request.as_ref())
             .await?;
 
-        self.last_status = Some(cmd.response.status_byte());
-        Ok(R::from(cmd.response.value()))
+        self.set_last_status(Some(cmd.response.status_byte()));
+        Ok(cmd.response.value())
     }
 
     /// Read a sequence of register values from chip.
@@ -186,19 +369,49 @@ where
             ])
             .await?;
 
-        self.last_status = Some(header.response.status_byte());
+        self.set_last_status(Some(header.response.status_byte()));
         Ok(())
     }
 
     /// Write a single register value to chip.
     pub async fn write_reg<R: Register>(&mut self, reg: R) -> Result<(), DriverError> {
-        let mut cmd = SingleCommand::write(R::ADDRESS, reg.value());
+        self.write_single(R::ADDRESS, reg.value()).await
+    }
+
+    /// Read a register, modify it and write it back to chip.
+    ///
+    /// This works for both primary and extended registers, and preserves all the bits that are not modified.
+    /// Returns the register value that was written.
+    pub async fn modify<R: Register>(
+        &mut self,
+        modify: impl FnOnce(&mut R),
+    ) -> Result<R, DriverError> {
+        let mut reg = self.read_reg::<R>().await?;
+        modify(&mut reg);
+        self.write_reg(reg).await?;
+        Ok(reg)
+    }
+
+    /// Write an arbitrary extended register value to chip.
+    ///
+    /// The address is the full extended address including the 0x2F prefix, e.g. 0x2F0C for FREQ2.
+    /// `DriverError::InvalidAddress` is returned for any other address.
+    pub async fn write_ext(&mut self, addr: u16, val: u8) -> Result<(), DriverError> {
+        self.write_single(extended_address(addr)?, val).await
+    }
+
+    async fn write_single(
+        &mut self,
+        address: RegisterAddress,
+        value: u8,
+    ) -> Result<(), DriverError> {
+        let mut cmd = SingleCommand::write(address, value);
 
         self.spi
             .transfer(cmd.response.as_mut(), cmd.request.as_ref())
             .await?;
 
-        self.last_status = Some(cmd.response.status_byte());
+        self.set_last_status(Some(cmd.response.status_byte()));
         Ok(())
     }
 
@@ -217,7 +430,7 @@ where
             ])
             .await?;
 
-        self.last_status = Some(header.response.status_byte());
+        self.set_last_status(Some(header.response.status_byte()));
         Ok(())
     }
 
@@ -244,6 +457,29 @@ where
         Ok(())
     }
 
+    /// Write a configuration patch to chip, only writing the registers that differ from the current chip configuration.
+    ///
+    /// The current configuration is read from the chip in two burst reads,
+    /// and each differing register is written individually.
+    pub async fn apply_patch<'patch>(
+        &mut self,
+        patch: ConfigPatch<'patch>,
+    ) -> Result<(), DriverError> {
+        let current = self.read_config().await?;
+        for (address, value) in patch.diff(&current) {
+            self.write_single(address, value).await?;
+        }
+
+        let (_, ext) = patch.split_pri_ext();
+        if self.freq_off.is_some()
+            && (ext.get::<Freqoff1>().is_some() || ext.get::<Freqoff0>().is_some())
+        {
+            self.write_freq_off().await?;
+        }
+
+        Ok(())
+    }
+
     /// Read entire configuration from chip.
     pub async fn read_config(&mut self) -> Result<Config, DriverError> {
         let mut config = Config([0; 105]);
@@ -254,13 +490,174 @@ where
         Ok(config)
     }
 
-    /// Read the current RSSI level.
+    /// Read all configuration registers for debugging, e.g. to diff against the intended configuration.
+    ///
+    /// The primary and extended registers are read in two separate bursts.
+    pub async fn dump_registers(&mut self) -> Result<RegisterSnapshot, DriverError> {
+        Ok(RegisterSnapshot(self.read_config().await?))
+    }
+
+    /// Write a report of the full register space and the chip status, suitable for pasting into an issue.
+    ///
+    /// The AES key and buffer registers are not read, and are reported as redacted.
+    /// Note that reading some status registers, e.g. MARC_STATUS1, clears them.
+    pub async fn bug_report(&mut self, out: &mut impl core::fmt::Write) -> Result<(), DriverError> {
+        let mut config = Config([0; 105]);
+        let mut buf = [0; 64];
+        let mut start = 0;
+
+        writeln!(out, "cc1200 register dump").map_err(|_| DriverError::Format)?;
+        while start < REGISTER_NAMES.len() {
+            let first = REGISTER_NAMES[start].0;
+            if is_secret_register(first) {
+                write_register(out, first, None).map_err(|_| DriverError::Format)?;
+                start += 1;
+                continue;
+            }
+
+            let mut len = 1;
+            while start + len < REGISTER_NAMES.len()
+                && REGISTER_NAMES[start + len].0 .0 == first.0 + len as u16
+                && !is_secret_register(REGISTER_NAMES[start + len].0)
+            {
+                len += 1;
+            }
+
+            let values = &mut buf[..len];
+            self.read_regs(first, values).await?;
+
+            for (&(address, _), &value) in
+                REGISTER_NAMES[start..start + len].iter().zip(values.iter())
+            {
+                if address <= RegisterAddress::EXT_MAX {
+                    config.0[address.idx()] = value;
+                }
+                write_register(out, address, Some(value)).map_err(|_| DriverError::Format)?;
+            }
+
+            start += len;
+        }
+
+        write!(out, "config =").map_err(|_| DriverError::Format)?;
+        for value in config.0 {
+            write!(out, " {:02X}", value).map_err(|_| DriverError::Format)?;
+        }
+        writeln!(out).map_err(|_| DriverError::Format)?;
+
+        if let Some(status) = self.last_status {
+            writeln!(
+                out,
+                "status = 0x{:02X} (state: {:?}, ready: {})",
+                status.0,
+                status.state(),
+                status.chip_rdy()
+            )
+            .map_err(|_| DriverError::Format)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read the current RSSI level in dBm.
+    ///
+    /// RSSI1 and RSSI0 are read in a single burst, and `None` is returned if `RSSI0.RSSI_VALID` is not set.
+    /// The 12 bit reading with a resolution of 0.0625 dB is rounded to the nearest dB.
+    /// Valid readings are recorded in the RSSI history if it is enabled.
     pub async fn read_rssi(&mut self) -> Result<Option<Rssi>, DriverError> {
-        let rssi = self.read_reg::<ext::Rssi1>().await?.rssi_11_4();
-        Ok(self.map_rssi(rssi))
+        let mut buf = [0; 2];
+        self.read_regs(ext::Rssi1::ADDRESS, &mut buf).await?;
+        let rssi = self.map_rssi_12bit(ext::Rssi1::from(buf[0]), ext::Rssi0::from(buf[1]));
+        #[cfg(feature = "rssi-history")]
+        if let Some(rssi) = rssi
+            && let Some(history) = self.rssi_history.as_mut()
+        {
+            history.record(rssi);
+        }
+        Ok(rssi)
+    }
+
+    /// Start recording readings from `read_rssi()` in a fixed capacity history.
+    ///
+    /// Each reading is timestamped with the tick returned by `clock`.
+    #[cfg(feature = "rssi-history")]
+    pub fn enable_rssi_history(&mut self, clock: fn() -> u32) {
+        self.rssi_history = Some(RssiHistory::new(clock));
+    }
+
+    /// Stop recording RSSI readings and discard the history.
+    #[cfg(feature = "rssi-history")]
+    pub fn disable_rssi_history(&mut self) {
+        self.rssi_history = None;
+    }
+
+    /// Get the recorded RSSI history, if enabled.
+    #[cfg(feature = "rssi-history")]
+    pub fn rssi_history(&self) -> Option<&RssiHistory> {
+        self.rssi_history.as_ref()
+    }
+
+    /// Read the number of bytes currently available in the RX fifo.
+    ///
+    /// This can be polled while a packet is being received,
+    /// e.g. to read the length byte and then wait for the remaining bytes.
+    pub async fn available_rx(&mut self) -> Result<u8, DriverError> {
+        Ok(self.read_reg::<ext::NumRxbytes>().await?.rxbytes())
+    }
+
+    /// Peek the first byte in the RX fifo without consuming it.
+    ///
+    /// Returns the number of available bytes together with the first byte, if any.
+    pub async fn peek_rx(&mut self) -> Result<(u8, Option<u8>), DriverError> {
+        // NUM_RXBYTES, FIFO_NUM_TXBYTES, FIFO_NUM_RXBYTES, RXFIFO_PRE_BUF
+        let mut buf = [0; 4];
+        self.read_regs(ext::NumRxbytes::ADDRESS, &mut buf).await?;
+        let available = ext::NumRxbytes::from(buf[0]).rxbytes();
+        let first = ext::RxfifoPreBuf::from(buf[3]).pre_buf();
+        Ok((available, (available > 0).then_some(first)))
+    }
+
+    /// Measure the noise floor by averaging RSSI readings in RX.
+    ///
+    /// The chip is put in RX, and `samples` RSSI readings are taken `dwell_us` apart.
+    /// Invalid readings are not included in the average, and `DriverError::Timeout` is returned if no reading is valid.
+    /// The chip is returned to IDLE when the measurement completes.
+    /// `DriverError::InvalidSampleCount` is returned if `samples` is zero.
+    pub async fn measure_noise_floor(
+        &mut self,
+        samples: u8,
+        dwell_us: u32,
+    ) -> Result<Rssi, DriverError> {
+        if samples == 0 {
+            return Err(DriverError::InvalidSampleCount);
+        }
+
+        self.strobe_until(Strobe::SRX, |status| status.state() == State::RX)
+            .await?;
+
+        let mut sum = 0i32;
+        let mut count = 0i32;
+        for _ in 0..samples {
+            self.delay.delay_us(dwell_us).await;
+            if let Some(rssi) = self.read_rssi().await? {
+                sum += rssi as i32;
+                count += 1;
+            }
+        }
+
+        self.strobe_until_idle(Strobe::SIDLE).await?;
+
+        if count == 0 {
+            return Err(DriverError::Timeout);
+        }
+
+        // Round to nearest
+        Ok((2 * sum + count).div_euclid(2 * count) as Rssi)
     }
 
     /// Read from the RX fifo by first reading the length and then read what is available.
+    ///
+    /// The read never exceeds `NUM_RXBYTES`, so it cannot underflow the fifo.
+    /// Returns the number of bytes read.
     pub async fn read_fifo(&mut self, buffer: &mut [u8]) -> Result<usize, DriverError> {
         let available = self.read_reg::<ext::NumRxbytes>().await?.rxbytes() as usize;
         let len = core::cmp::min(core::cmp::min(available, buffer.len()), RX_FIFO_SIZE);
@@ -281,7 +678,7 @@ where
             ])
             .await?;
 
-        self.last_status = Some(header.response.status_byte());
+        self.set_last_status(Some(header.response.status_byte()));
         Ok(())
     }
 
@@ -305,7 +702,7 @@ where
         self.spi.transfer(rx, tx).await?;
 
         // The status byte is emitted twice by the chip as we send two opcodes in the same transfer
-        self.last_status = Some(StatusByte(rx[3]));
+        self.set_last_status(Some(StatusByte(rx[3])));
         buffer.copy_from_slice(&rx[4..]);
         Ok(self.map_rssi(rx[2]))
     }
@@ -329,7 +726,7 @@ where
                     .await?;
             }
 
-            self.last_status = Some(StatusByte(rx_buf[0]));
+            self.set_last_status(Some(StatusByte(rx_buf[0])));
         }
         Ok(discarded)
     }
@@ -347,12 +744,111 @@ where
             ])
             .await?;
 
-        self.last_status = Some(header.response.status_byte());
+        self.set_last_status(Some(header.response.status_byte()));
+        Ok(())
+    }
+
+    /// Write to the TX fifo by first reading the fifo occupancy and then write what there is room for.
+    ///
+    /// The write never exceeds the free space in the fifo, so it cannot overflow the fifo.
+    /// Returns the number of bytes written.
+    pub async fn write_fifo_available(&mut self, buffer: &[u8]) -> Result<usize, DriverError> {
+        let used = self.read_reg::<ext::NumTxbytes>().await?.txbytes() as usize;
+        let len = core::cmp::min(buffer.len(), TX_FIFO_SIZE.saturating_sub(used));
+        if len > 0 {
+            self.write_fifo(&buffer[..len]).await?;
+        }
+        Ok(len)
+    }
+
+    /// Transmit a payload only if the frequency synthesizer is in lock.
+    ///
+    /// The out of lock detector is enabled and the chip is brought to FSTXON, where the lock indicator is checked before the TX fifo is filled.
+    /// The lock indicator is checked again after the chip enters TX.
+    /// If the synthesizer is out of lock, the chip is returned to IDLE, the TX fifo is flushed, and `DriverError::OutOfLock` is returned.
+    /// `DriverError::InvalidPayloadLength` is returned if the payload does not fit in the TX fifo,
+    /// and `DriverError::Timeout` if the chip does not enter or leave FSTXON in time.
+    pub async fn transmit_checked(&mut self, payload: &[u8]) -> Result<(), DriverError> {
+        if payload.len() > TX_FIFO_SIZE {
+            return Err(DriverError::InvalidPayloadLength);
+        }
+
+        let mut fs_cfg = self.read_reg::<FsCfg>().await?;
+        if !fs_cfg.fs_lock_en() {
+            fs_cfg.set_fs_lock_en(true);
+            self.write_reg(fs_cfg).await?;
+        }
+
+        self.strobe_until_bounded(Strobe::SFSTXON, |state| state == State::FSTXON)
+            .await?;
+        self.ensure_lock().await?;
+
+        self.write_fifo(payload).await?;
+
+        // Do not repeat STX while waiting as that would retransmit if the payload is already sent
+        self.strobe(Strobe::STX).await?;
+        let state = self
+            .strobe_until_bounded(Strobe::SNOP, |state| state != State::FSTXON)
+            .await?;
+        if state == State::TX {
+            self.ensure_lock().await?;
+        }
+
         Ok(())
     }
 
+    /// Strobe until the state satisfies `pred`, polling a bounded number of times.
+    /// The chip is returned to IDLE and the TX fifo is flushed if it times out.
+    async fn strobe_until_bounded<Pred>(
+        &mut self,
+        strobe: Strobe,
+        pred: Pred,
+    ) -> Result<State, DriverError>
+    where
+        Pred: Fn(State) -> bool,
+    {
+        let mut cmd = StrobeCommand::new(strobe);
+
+        for _ in 0..FSTXON_POLL_LIMIT {
+            self.spi
+                .transfer(cmd.response.as_mut(), cmd.request.as_ref())
+                .await?;
+            let status = cmd.response.status_byte();
+            self.set_last_status(Some(status));
+            if pred(status.state()) {
+                return Ok(status.state());
+            }
+            self.delay.delay_us(FSTXON_POLL_INTERVAL_US).await;
+        }
+
+        self.strobe_until_idle(Strobe::SIDLE).await?;
+        self.strobe(Strobe::SFTX).await?;
+        Err(DriverError::Timeout)
+    }
+
+    async fn ensure_lock(&mut self) -> Result<(), DriverError> {
+        if self.read_reg::<FscalCtrl>().await?.lock() {
+            Ok(())
+        } else {
+            self.strobe_until_idle(Strobe::SIDLE).await?;
+            self.strobe(Strobe::SFTX).await?;
+            Err(DriverError::OutOfLock)
+        }
+    }
+
+    // Map the RSSI1 and RSSI0 register fields to an rssi value.
+    fn map_rssi_12bit(&self, rssi1: ext::Rssi1, rssi0: ext::Rssi0) -> Option<Rssi> {
+        if !rssi0.rssi_valid() || rssi1.rssi_11_4() as i8 == -128 {
+            return None;
+        }
+
+        // Sign extend the 12 bit two's complement value in units of 0.0625 dB
+        let rssi = ((rssi1.rssi_11_4() as i8 as i16) << 4) | rssi0.rssi_3_0() as i16;
+        Some((rssi + 8).div_euclid(16) + self.rssi_offset.unwrap_or_default())
+    }
+
     // Map the RSSI1 register field to an rssi value.
-    fn map_rssi(&self, rssi1_value: u8) -> Option<Rssi> {
+    pub(crate) fn map_rssi(&self, rssi1_value: u8) -> Option<Rssi> {
         let rssi = rssi1_value as i8;
         match rssi {
             -128 => None,
@@ -370,7 +866,7 @@ where
             .transfer(cmd.response.as_mut(), cmd.request.as_ref())
             .await?;
 
-        self.last_status = Some(cmd.response.status_byte());
+        self.set_last_status(Some(cmd.response.status_byte()));
         Ok(())
     }
 
@@ -393,7 +889,7 @@ where
                 .await?;
             let status = cmd.response.status_byte();
             if pred(status) {
-                self.last_status = Some(status);
+                self.set_last_status(Some(status));
                 return Ok(());
             }
         }
@@ -441,6 +937,34 @@ where
         }
     }
 
+    /// Configure the output function and inversion of a GPIO pin.
+    ///
+    /// GPIO1 is shared with the spi SO line and is only driven by the configured function while CSn is de-asserted.
+    /// Leave it as `HIGHZ` if other devices share the spi bus.
+    pub async fn configure_gpio<G: Gpio>(
+        &mut self,
+        output: GpioOutput,
+        invert: bool,
+    ) -> Result<(), DriverError> {
+        self.modify::<G::Iocfg>(|iocfg| {
+            iocfg.set_gpio_cfg(output);
+            iocfg.set_gpio_inv(invert);
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Use an external 40 kHz clock on GPIO3 for the eWOR timer instead of the internal RC oscillator.
+    pub async fn use_external_40k_clock(&mut self) -> Result<(), DriverError> {
+        let mut iocfg3 = self.read_reg::<Iocfg3>().await?;
+        iocfg3.set_gpio3_cfg(Gpio3Output::EXT_40K_CLOCK as u8);
+        self.write_reg(iocfg3).await?;
+
+        let mut ext_ctrl = self.read_reg::<ExtCtrl>().await?;
+        ext_ctrl.set_ext_40k_clock_en(true);
+        self.write_reg(ext_ctrl).await
+    }
+
     // Set the RSSI calibration
     pub async fn set_rssi_cal(
         &mut self,
@@ -483,333 +1007,1926 @@ where
             let measured = x.measured as i32;
             let desired = x.desired as i32;
             let delta = measured - desired;
-            let freq_off = (delta * lo_div * 2i32.pow(18)) / 40_000_000;
+            let freq_off = (delta * lo_div * 2i32.pow(18)) / XOSC_FREQUENCY as i32;
             freq_off as i16
         });
 
         self.write_freq_off().await
     }
 
-    async fn write_freq_off(&mut self) -> Result<(), DriverError> {
-        let values = self.freq_off.unwrap_or_default().to_be_bytes();
-        self.write_regs(Freqoff1::ADDRESS, &values).await
+    /// Set the carrier frequency in Hz.
+    ///
+    /// The band select setting in `FS_CFG.FSD_BANDSELECT` is selected from the frequency before `FREQ` is written.
+    /// Returns the frequency in Hz that is actually achieved given the register quantization,
+    /// or `DriverError::InvalidFrequency` if the frequency is outside all supported bands.
+    pub async fn set_frequency(&mut self, frequency: u32) -> Result<u32, DriverError> {
+        let (bandselect, _) = frequency_band(frequency).ok_or(DriverError::InvalidFrequency)?;
+
+        self.modify::<FsCfg>(|fs_cfg| fs_cfg.set_fsd_bandselect(bandselect))
+            .await?;
+
+        let (freq, achieved) = freq_value(frequency);
+        let freq = freq.to_be_bytes();
+        self.write_regs(ext::Freq2::ADDRESS, &freq[1..]).await?;
+
+        Ok(achieved)
     }
-}
 
-pub(crate) fn lo_divider(frequency: u32) -> u8 {
-    match frequency {
-        820_000_000..=960_000_000 => 4,
-        410_000_000..=480_000_000 => 8,
-        273_300_000..=320_000_000 => 12,
-        205_000_000..=240_000_000 => 16,
-        164_000_000..=192_000_000 => 20,
-        136_700_000..=160_000_000 => 24,
-        _ => panic!("Invalid frequency select"),
+    /// Set the carrier frequency to the channel `base_hz + channel * spacing_hz` for frequency hopping.
+    ///
+    /// Only `FREQ` is written in a single burst, so the band select setting in `FS_CFG.FSD_BANDSELECT`
+    /// must already match the band of `base_hz`, e.g. from [`Driver::set_frequency()`] with the base frequency.
+    /// The chip must be in IDLE, otherwise `DriverError::NotIdle` is returned.
+    /// Returns the frequency in Hz that is actually achieved given the register quantization,
+    /// or `DriverError::InvalidFrequency` if the channel is outside the band of `base_hz`.
+    pub async fn set_channel(
+        &mut self,
+        base_hz: u32,
+        spacing_hz: u32,
+        channel: u8,
+    ) -> Result<u32, DriverError> {
+        let frequency = channel_frequency(base_hz, spacing_hz, channel)?;
+        let (freq, achieved) = freq_value(frequency);
+        self.write_channel_freq(&freq.to_be_bytes()[1..]).await?;
+        Ok(achieved)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use embedded_hal_async_mocks::{delay::MockDelay, spi::MockSpiDevice};
-    use static_cell::make_static;
+    /// Set the carrier frequency to a channel in a precomputed hop table.
+    ///
+    /// This is the same as [`Driver::set_channel()`] without the per hop arithmetic.
+    /// `DriverError::InvalidFrequency` is returned if the channel is not in the table.
+    pub async fn hop<const N: usize>(
+        &mut self,
+        table: &HopTable<N>,
+        channel: u8,
+    ) -> Result<(), DriverError> {
+        let freq = table
+            .freq
+            .get(channel as usize)
+            .ok_or(DriverError::InvalidFrequency)?;
+        self.write_channel_freq(freq).await
+    }
 
-    use crate::regs::{ext::FreqoffCfg, pri::Iocfg2};
+    async fn write_channel_freq(&mut self, freq: &[u8]) -> Result<(), DriverError> {
+        if self.read_marc_state().await? != MarcStateValue::IDLE {
+            return Err(DriverError::NotIdle);
+        }
 
-    use super::*;
+        self.write_regs(ext::Freq2::ADDRESS, freq).await
+    }
 
-    #[tokio::test]
-    async fn read_reg_primary() {
-        // Given
-        let mut spi = MockSpiDevice::new();
-        let delay = MockDelay::new();
+    /// Read the frequency offset estimate in Hz from `FREQOFF_EST`.
+    ///
+    /// The estimate is valid after sync word detection and can be used for automatic frequency control,
+    /// e.g. by feeding it back into `FREQOFF` to follow a drifting transmitter.
+    /// The LO divider is taken from the band selected in `FS_CFG.FSD_BANDSELECT`.
+    pub async fn read_freq_offset_est(&mut self) -> Result<i32, DriverError> {
+        let fs_cfg = self.read_reg::<FsCfg>().await?;
+        let lo_div =
+            band_lo_divider(fs_cfg.fsd_bandselect()).ok_or(DriverError::InvalidFrequency)?;
 
-        spi.expect_transaction_operations(make_static!([Operation::Transfer(
-            make_static!([0x22, 0x33]),
-            &[0x80 | 0x01, 0x00]
-        )]));
+        let mut buf = [0; 2];
+        self.read_regs(ext::FreqoffEst1::ADDRESS, &mut buf).await?;
+        let freqoff_est = i16::from_be_bytes(buf) as i64;
 
-        // When
-        let mut driver: Driver<_, _> = Driver::new(spi, delay);
-        let value = driver.read_reg::<Iocfg2>().await.unwrap();
+        // f_offset = FREQOFF_EST * f_XOSC / LO_div / 2^18
+        Ok((freqoff_est * XOSC_FREQUENCY as i64 / (lo_div as i64 * (1 << 18))) as i32)
+    }
 
-        // Then
-        assert_eq!(0x22, driver.last_status.unwrap().0);
-        assert_eq!(0x33, value.0);
+    /// Enable the random number generator in `RNDGEN`.
+    ///
+    /// The generator is a 7 bit LFSR which is only truly random while the radio is in RX,
+    /// where the receiver noise is XORed into the feedback.
+    /// Outside RX the sequence is deterministic.
+    pub async fn enable_rng(&mut self) -> Result<(), DriverError> {
+        let mut rndgen = ext::Rndgen::default();
+        rndgen.set_rndgen_en(true);
+        self.write_reg(rndgen).await
     }
 
-    #[tokio::test]
-    async fn read_reg_extended() {
-        // Given
-        let mut spi = MockSpiDevice::new();
-        let delay = MockDelay::new();
+    /// Read the next value from the random number generator enabled by [`Driver::enable_rng()`].
+    ///
+    /// Only the 7 least significant bits are random, so the value is in the range 0..=127.
+    pub async fn random_byte(&mut self) -> Result<u8, DriverError> {
+        let rndgen = self.read_reg::<ext::Rndgen>().await?;
+        Ok(rndgen.rndgen_value())
+    }
 
-        spi.expect_transaction_operations(make_static!([Operation::Transfer(
-            make_static!([0x22, 0x00, 0x33]),
-            &[0x80 | 0x2F, 0x01, 0x00]
-        )]));
+    /// Load the 128 bit AES key into `AES_KEY`, most significant byte first.
+    pub async fn aes_load_key(&mut self, key: &[u8; AES_BLOCK_SIZE]) -> Result<(), DriverError> {
+        self.write_regs(ext::AesKey15::ADDRESS, key).await
+    }
 
-        // When
-        let mut driver: Driver<_, _> = Driver::new(spi, delay);
-        let value = driver.read_reg::<FreqoffCfg>().await.unwrap();
+    /// Encrypt a single block in place with the key loaded in `AES_KEY`.
+    ///
+    /// The plaintext is written to `AES_BUFFER` and the ciphertext is read back when the encryption cycle has finished.
+    /// The encryption is aborted and `DriverError::AesTimeout` is returned if it does not finish in time.
+    pub async fn aes_encrypt_block(
+        &mut self,
+        block: &mut [u8; AES_BLOCK_SIZE],
+    ) -> Result<(), DriverError> {
+        self.write_regs(ext::AesBuffer15::ADDRESS, block).await?;
 
-        // Then
-        assert_eq!(0x22, driver.last_status.unwrap().0);
-        assert_eq!(0x33, value.0);
+        let mut aes = ext::Aes::default();
+        aes.set_aes_run(true);
+        self.write_reg(aes).await?;
+        self.wait_for_aes().await?;
+
+        self.read_regs(ext::AesBuffer15::ADDRESS, block).await
     }
 
-    #[tokio::test]
-    async fn read_regs_primary() {
-        // Given
-        let mut spi = MockSpiDevice::new();
-        let delay = MockDelay::new();
+    /// Run a high level AES command on the FIFO content.
+    ///
+    /// The command XOR's the next 16 bytes in the fifo with the encrypted `AES_BUFFER` content,
+    /// so that a packet can be encrypted or decrypted in place, e.g. in counter mode.
+    /// The encryption is aborted and `DriverError::AesTimeout` is returned if it does not finish in time.
+    pub async fn aes_fifo(&mut self, command: AesFifoCommand) -> Result<(), DriverError> {
+        let mut marc_spare = ext::MarcSpare::default();
+        marc_spare.set_aes_commands(command as u8);
+        self.write_reg(marc_spare).await?;
+
+        // The command is executed when SAFC is strobed
+        self.strobe(Strobe::SAFC).await?;
+        self.wait_for_aes().await
+    }
+
+    async fn wait_for_aes(&mut self) -> Result<(), DriverError> {
+        for _ in 0..AES_POLL_LIMIT {
+            if !self.read_reg::<ext::Aes>().await?.aes_run() {
+                return Ok(());
+            }
+            self.delay.delay_us(AES_POLL_INTERVAL_US).await;
+        }
+
+        // The abort bit is cleared by the chip when the abort sequence is completed
+        let mut aes = ext::Aes::default();
+        aes.set_aes_abort(true);
+        self.write_reg(aes).await?;
+        for _ in 0..AES_POLL_LIMIT {
+            if !self.read_reg::<ext::Aes>().await?.aes_abort() {
+                break;
+            }
+            self.delay.delay_us(AES_POLL_INTERVAL_US).await;
+        }
+
+        Err(DriverError::AesTimeout)
+    }
+
+    /// Run a manual frequency synthesizer calibration and read back the calibration results.
+    ///
+    /// The chip must be in IDLE state.
+    pub async fn calibrate(&mut self) -> Result<FsCalibration, DriverError> {
+        self.strobe(Strobe::SCAL).await?;
+        self.strobe_until_idle(Strobe::SNOP).await?;
+
+        Ok(FsCalibration {
+            fs_vco2: self.read_reg::<ext::FsVco2>().await?.value(),
+            fs_vco4: self.read_reg::<ext::FsVco4>().await?.value(),
+            fs_chp: self.read_reg::<ext::FsChp>().await?.value(),
+        })
+    }
+
+    /// Restore calibration results from `calibrate()` and disable automatic calibration.
+    ///
+    /// The results are only valid for the frequency and temperature at which the calibration was run.
+    pub async fn restore_calibration(
+        &mut self,
+        calibration: &FsCalibration,
+    ) -> Result<(), DriverError> {
+        self.write_reg(ext::FsVco2::from(calibration.fs_vco2))
+            .await?;
+        self.write_reg(ext::FsVco4::from(calibration.fs_vco4))
+            .await?;
+        self.write_reg(ext::FsChp::from(calibration.fs_chp)).await?;
+        self.modify::<SettlingCfg>(|settling_cfg| {
+            settling_cfg.set_fs_autocal(FsAutocalValue::Never)
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn write_freq_off(&mut self) -> Result<(), DriverError> {
+        let values = self.freq_off.unwrap_or_default().to_be_bytes();
+        self.write_regs(Freqoff1::ADDRESS, &values).await
+    }
+}
+
+/// The AES_KEY and AES_BUFFER registers hold the secret key and the plaintext
+fn is_secret_register(address: RegisterAddress) -> bool {
+    (ext::AesKey15::ADDRESS.0..=ext::AesBuffer0::ADDRESS.0).contains(&address.0)
+}
+
+pub(crate) fn extended_address(addr: u16) -> Result<RegisterAddress, DriverError> {
+    if addr >> 8 == 0x2F {
+        Ok(RegisterAddress(addr))
+    } else {
+        Err(DriverError::InvalidAddress)
+    }
+}
+
+/// Get the FREQ register value for a carrier frequency in Hz, together with the frequency in Hz that is actually achieved.
+pub(crate) fn freq_value(frequency: u32) -> (u32, u32) {
+    // f_RF = FREQ / 2^16 * f_XOSC / LO_div
+    let lo_div = lo_divider(frequency) as u64;
+    let xosc = XOSC_FREQUENCY as u64;
+    let freq = (((frequency as u64 * lo_div) << 16) + xosc / 2) / xosc;
+    let achieved = ((freq * xosc) + (lo_div << 15)) / (lo_div << 16);
+    (freq as u32, achieved as u32)
+}
+
+pub(crate) fn lo_divider(frequency: u32) -> u8 {
+    match frequency_band(frequency) {
+        Some((_, lo_div)) => lo_div,
+        None => panic!("Invalid frequency select"),
+    }
+}
+
+/// Get the frequency of a channel, if it is within the band of the base frequency.
+fn channel_frequency(base_hz: u32, spacing_hz: u32, channel: u8) -> Result<u32, DriverError> {
+    let frequency = spacing_hz
+        .checked_mul(channel as u32)
+        .and_then(|offset| base_hz.checked_add(offset))
+        .ok_or(DriverError::InvalidFrequency)?;
+
+    match (frequency_band(base_hz), frequency_band(frequency)) {
+        (Some((base_band, _)), Some((band, _))) if base_band == band => Ok(frequency),
+        _ => Err(DriverError::InvalidFrequency),
+    }
+}
+
+/// Get the band select setting and LO divider for a carrier frequency in Hz, if it is within a supported band.
+pub(crate) fn frequency_band(frequency: u32) -> Option<(FsdBandselectValue, u8)> {
+    match frequency {
+        820_000_000..=960_000_000 => Some((FsdBandselectValue::Band820, 4)),
+        410_000_000..=480_000_000 => Some((FsdBandselectValue::Band410, 8)),
+        273_300_000..=320_000_000 => Some((FsdBandselectValue::Band273, 12)),
+        205_000_000..=240_000_000 => Some((FsdBandselectValue::Band205, 16)),
+        164_000_000..=192_000_000 => Some((FsdBandselectValue::Band164, 20)),
+        136_700_000..=160_000_000 => Some((FsdBandselectValue::Band136, 24)),
+        _ => None,
+    }
+}
+
+/// Get the LO divider for a band select setting, if the setting is in use.
+pub(crate) fn band_lo_divider(bandselect: FsdBandselectValue) -> Option<u8> {
+    match bandselect {
+        FsdBandselectValue::Band820 => Some(4),
+        FsdBandselectValue::Band410 => Some(8),
+        FsdBandselectValue::Band273 => Some(12),
+        FsdBandselectValue::Band205 => Some(16),
+        FsdBandselectValue::Band164 => Some(20),
+        FsdBandselectValue::Band136 => Some(24),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal_async_mocks::{delay::MockDelay, spi::MockSpiDevice};
+    use static_cell::make_static;
+
+    use crate::{
+        gpio::Gpio2,
+        regs::{
+            ext::FreqoffCfg,
+            pri::{Iocfg2, Mdmcfg1},
+        },
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn read_reg_primary() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x22, 0x33]),
+            &[0x80 | 0x01, 0x00]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let value = driver.read_reg::<Iocfg2>().await.unwrap();
+
+        // Then
+        assert_eq!(0x22, driver.last_status.unwrap().0);
+        assert_eq!(0x33, value.0);
+    }
+
+    #[tokio::test]
+    async fn read_reg_extended() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x22, 0x00, 0x33]),
+            &[0x80 | 0x2F, 0x01, 0x00]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let value = driver.read_reg::<FreqoffCfg>().await.unwrap();
+
+        // Then
+        assert_eq!(0x22, driver.last_status.unwrap().0);
+        assert_eq!(0x33, value.0);
+    }
+
+    #[tokio::test]
+    async fn read_ext() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x22, 0x00, 0x20]),
+            &[0x80 | 0x2F, 0x8F, 0x00]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let value = driver.read_ext(0x2F8F).await.unwrap();
+
+        // Then
+        assert_eq!(0x22, driver.last_status.unwrap().0);
+        assert_eq!(0x20, value);
+    }
+
+    #[tokio::test]
+    async fn read_ext_rejects_primary_address() {
+        // Given
+        let spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let result = driver.read_ext(0x0C).await;
+
+        // Then
+        assert!(matches!(result, Err(DriverError::InvalidAddress)));
+    }
+
+    #[tokio::test]
+    async fn write_ext() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x22, 0x00, 0x00]),
+            &[0x2F, 0x0C, 0x56]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        driver.write_ext(0x2F0C, 0x56).await.unwrap();
+
+        // Then
+        assert_eq!(0x22, driver.last_status.unwrap().0);
+    }
+
+    #[tokio::test]
+    async fn set_carrier_sense_threshold() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        // -90 dBm - -99 dBm = 9
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00]),
+            &[0x17, 0x09]
+        )]));
+
+        // -110 dBm - -99 dBm = -11
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00]),
+            &[0x17, -11i8 as u8]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        driver.set_carrier_sense_threshold(-90).await.unwrap();
+        driver.set_carrier_sense_threshold(-110).await.unwrap();
+
+        // Then
+        assert_eq!(0x00, driver.last_status.unwrap().0);
+    }
+
+    #[tokio::test]
+    async fn set_carrier_sense_threshold_rejects_out_of_range_threshold() {
+        // Given
+        let spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let result = driver.set_carrier_sense_threshold(30).await;
+
+        // Then
+        assert!(matches!(
+            result,
+            Err(DriverError::InvalidCarrierSenseThreshold)
+        ));
+    }
+
+    #[tokio::test]
+    async fn apply_patch() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        let current = crate::configs::wmbus_modecmto::<0>();
+        let mut config = Config(current.0);
+        let mut mdmcfg1 = config.get::<Mdmcfg1>();
+        mdmcfg1.set_manchester_en(!mdmcfg1.manchester_en());
+        config.set(mdmcfg1);
+
+        let (pri, ext) = current.0.split_at(47);
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x00]), &[0xC0]), // IOCFG3
+            Operation::Read(Box::leak(pri.to_vec().into_boxed_slice()))
+        ]));
+
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x00, 0x00]), &[0xC0 | 0x2F, 0x00]),
+            Operation::Read(Box::leak(ext.to_vec().into_boxed_slice()))
+        ]));
+
+        spi.expect_transaction_operations(Box::leak(Box::new([Operation::Transfer(
+            make_static!([0x00, 0x00]),
+            Box::leak(Box::new([0x11, mdmcfg1.value()])),
+        )])));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        driver.apply_patch(config.patch()).await.unwrap();
+
+        // Then
+        assert_eq!(0x00, driver.last_status.unwrap().0);
+    }
+
+    #[tokio::test]
+    async fn set_device_address() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00]),
+            &[0x1E, 0x42]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x03]), // CRC_CFG = 1 and APPEND_STATUS
+            &[0x80 | 0x27, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00]),
+            &[0x27, 0x13] // ADDR_CHECK_CFG = 0x00 broadcast
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        driver
+            .set_device_address(0x42, BroadcastMode::Broadcast00)
+            .await
+            .unwrap();
+
+        // Then
+        assert_eq!(0x00, driver.last_status.unwrap().0);
+    }
+
+    #[tokio::test]
+    async fn reset_to_idle() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let mut delay = MockDelay::new();
+        delay.expect_delay_ms().return_const(());
+        delay
+            .expect_delay_us()
+            .withf(|us| *us == 100)
+            .times(1)
+            .return_const(());
+
+        spi.expect_transaction_operations(make_static!([Operation::Write(&[0x30])]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00]), // CHIP_RDYn is low
+            &[0x3D]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x00]), // XOSC not yet stable
+            &[0x80 | 0x2F, 0x36, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x01]), // XOSC_STABLE
+            &[0x80 | 0x2F, 0x36, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00]),
+            &[0x3A]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00]),
+            &[0x3B]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00]), // IDLE
+            &[0x36]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        driver.reset_to_idle().await.unwrap();
+
+        // Then
+    }
+
+    #[tokio::test]
+    async fn calibrate() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00]),
+            &[0x33]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x40]), // CALIBRATE
+            &[0x3D]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00]), // IDLE
+            &[0x3D]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x4A]),
+            &[0x80 | 0x2F, 0x25, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x13]),
+            &[0x80 | 0x2F, 0x23, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x28]),
+            &[0x80 | 0x2F, 0x18, 0x00]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let calibration = driver.calibrate().await.unwrap();
+
+        // Then
+        assert_eq!(
+            FsCalibration {
+                fs_vco2: 0x4A,
+                fs_vco4: 0x13,
+                fs_chp: 0x28
+            },
+            calibration
+        );
+    }
+
+    #[tokio::test]
+    async fn restore_calibration() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x00]),
+            &[0x2F, 0x25, 0x4A]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x00]),
+            &[0x2F, 0x23, 0x13]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x00]),
+            &[0x2F, 0x18, 0x28]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x0B]), // FS_AUTOCAL = IDLE to RX or TX
+            &[0x80 | 0x1F, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00]),
+            &[0x1F, 0x03] // FS_AUTOCAL = Never
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        driver
+            .restore_calibration(&FsCalibration {
+                fs_vco2: 0x4A,
+                fs_vco4: 0x13,
+                fs_chp: 0x28,
+            })
+            .await
+            .unwrap();
+
+        // Then
+        assert_eq!(0x00, driver.last_status.unwrap().0);
+    }
+
+    #[tokio::test]
+    async fn read_marc_state() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x60, 0x00, 0x71]), // MARC_2PIN_STATE = RX and RX_FIFO_ERR
+            &[0x80 | 0x2F, 0x73, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x41]), // MARC_2PIN_STATE = IDLE and IDLE
+            &[0x80 | 0x2F, 0x73, 0x00]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let fifo_error = driver.read_marc_state().await.unwrap();
+        let idle = driver.read_marc_state().await.unwrap();
+
+        // Then
+        assert_eq!(MarcStateValue::RX_FIFO_ERR, fifo_error);
+        assert_eq!(Some(Strobe::SFRX), fifo_error.recovery_strobe());
+        assert_eq!(MarcStateValue::IDLE, idle);
+        assert_eq!(None, idle.recovery_strobe());
+    }
+
+    #[tokio::test]
+    async fn read_part_number() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x21]),
+            &[0x80 | 0x2F, 0x8F, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0xFF]), // Miswired spi bus
+            &[0x80 | 0x2F, 0x8F, 0x00]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let part_number = driver.read_part_number().await.unwrap();
+        let invalid = driver.read_part_number().await;
+
+        // Then
+        assert_eq!(PartNumber::Cc1201, part_number);
+        assert!(matches!(invalid, Err(DriverError::InvalidPartNumber)));
+    }
+
+    #[tokio::test]
+    async fn expect_part_number() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x20]),
+            &[0x80 | 0x2F, 0x8F, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x20]),
+            &[0x80 | 0x2F, 0x8F, 0x00]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let matching = driver.expect_part_number(PartNumber::Cc1200).await;
+        let mismatch = driver.expect_part_number(PartNumber::Cc1201).await;
+
+        // Then
+        assert!(matching.is_ok());
+        assert!(matches!(mismatch, Err(DriverError::PartNumberMismatch)));
+    }
+
+    #[tokio::test]
+    async fn read_part_version() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x11]),
+            &[0x80 | 0x2F, 0x90, 0x00]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let version = driver.read_part_version().await.unwrap();
+
+        // Then
+        assert_eq!(0x11, version);
+    }
+
+    #[tokio::test]
+    async fn modify_primary() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x46]),
+            &[0x80 | 0x11, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00]),
+            &[0x11, 0x66]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let mdmcfg1 = driver
+            .modify::<Mdmcfg1>(|mdmcfg1| mdmcfg1.set_manchester_en(true))
+            .await
+            .unwrap();
+
+        // Then
+        assert_eq!(0x66, mdmcfg1.value());
+    }
+
+    #[tokio::test]
+    async fn modify_extended() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0xF0]),
+            &[0x80 | 0x2F, 0x03, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x00]),
+            &[0x2F, 0x03, 0xFA]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        driver
+            .modify::<ext::MarcSpare>(|marc_spare| marc_spare.set_aes_commands(0b1010))
+            .await
+            .unwrap();
+
+        // Then
+        assert_eq!(0x00, driver.last_status.unwrap().0);
+    }
+
+    #[tokio::test]
+    async fn can_read_random_byte() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x00]),
+            &[0x2F, 0x80, 0xFF]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0xDA]),
+            &[0x80 | 0x2F, 0x80, 0x00]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        driver.enable_rng().await.unwrap();
+        let value = driver.random_byte().await.unwrap();
+
+        // Then
+        assert_eq!(0x5A, value);
+    }
+
+    #[tokio::test]
+    async fn read_regs_primary() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x22]), &[0xC0 | 0x01]),
+            Operation::Read(make_static!([0x33, 0x44]))
+        ]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let mut buf = [0; 2];
+        driver.read_regs(Iocfg2::ADDRESS, &mut buf).await.unwrap();
+
+        // Then
+        assert_eq!(0x22, driver.last_status.unwrap().0);
+        assert_eq!([0x33, 0x44].as_ref(), buf);
+    }
+
+    #[tokio::test]
+    async fn read_regs_extended() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x22, 0x00]), &[0xC0 | 0x2F, 0x01]),
+            Operation::Read(make_static!([0x33, 0x44]))
+        ]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let mut buf = [0; 2];
+        driver
+            .read_regs(FreqoffCfg::ADDRESS, &mut buf)
+            .await
+            .unwrap();
+
+        // Then
+        assert_eq!(0x22, driver.last_status.unwrap().0);
+        assert_eq!([0x33, 0x44].as_ref(), buf);
+    }
+
+    #[tokio::test]
+    async fn dump_registers() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x00]), &[0xC0]),
+            Operation::Read(make_static!([0x06; 47]))
+        ]));
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x00, 0x00]), &[0xC0 | 0x2F, 0x00]),
+            Operation::Read(make_static!([0x07; 58]))
+        ]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let snapshot = driver.dump_registers().await.unwrap();
+
+        // Then
+        assert_eq!(0x06, snapshot.get::<Iocfg3>().0);
+        assert_eq!(0x07, snapshot.get::<ext::PaCfg3>().0);
+        assert_eq!(105, snapshot.registers().count());
+
+        let mut expected = Config([0x06; 105]);
+        expected.set(ext::PaCfg3(0x08));
+        let diff: Vec<_> = snapshot.diff(&expected).collect();
+        assert_eq!(58, diff.len());
+        assert_eq!((ext::IfMixCfg::ADDRESS, 0x07, 0x06), diff[0]);
+        assert_eq!((ext::PaCfg3::ADDRESS, 0x07, 0x08), diff[57]);
+
+        let debug = format!("{:?}", snapshot);
+        let mut lines = debug.lines();
+        assert_eq!(Some("RegisterSnapshot"), lines.next());
+        assert_eq!(Some("0x0000 IOCFG3 = 0x06"), lines.next());
+        assert_eq!(Some("0x2F39 PA_CFG3 = 0x07"), lines.last());
+    }
+
+    #[tokio::test]
+    async fn bug_report() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x00]), &[0xC0]),
+            Operation::Read(make_static!([0x06; 47]))
+        ]));
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x00, 0x00]), &[0xC0 | 0x2F, 0x00]),
+            Operation::Read(make_static!([0x07; 58]))
+        ]));
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x00, 0x00]), &[0xC0 | 0x2F, 0x64]),
+            Operation::Read(make_static!([0x08; 63]))
+        ]));
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x10, 0x00]), &[0xC0 | 0x2F, 0xD2]),
+            Operation::Read(make_static!([0x09; 9]))
+        ]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let mut report = String::new();
+        driver.bug_report(&mut report).await.unwrap();
+
+        // Then
+        let mut lines = report.lines();
+        assert_eq!(Some("cc1200 register dump"), lines.next());
+
+        let registers: Vec<(u16, &str, Option<u8>)> = lines
+            .by_ref()
+            .take(REGISTER_NAMES.len())
+            .map(|line| {
+                let (address, rest) = line.split_once(' ').unwrap();
+                let (name, value) = rest.split_once(" = ").unwrap();
+                (
+                    u16::from_str_radix(address.strip_prefix("0x").unwrap(), 16).unwrap(),
+                    name,
+                    value
+                        .strip_prefix("0x")
+                        .map(|value| u8::from_str_radix(value, 16).unwrap()),
+                )
+            })
+            .collect();
+        assert_eq!(REGISTER_NAMES.len(), registers.len());
+        assert_eq!((0x0000, "IOCFG3", Some(0x06)), registers[0]);
+        assert!(registers.contains(&(0x2F8F, "PARTNUMBER", Some(0x08))));
+
+        // The AES key and buffer are redacted
+        let redacted: Vec<_> = registers
+            .iter()
+            .filter(|(_, _, value)| value.is_none())
+            .map(|(_, name, _)| *name)
+            .collect();
+        assert_eq!(32, redacted.len());
+        assert_eq!("AES_KEY15", redacted[0]);
+        assert_eq!("AES_BUFFER0", redacted[31]);
+        assert!(report
+            .lines()
+            .any(|line| line == "0x2FE0 AES_KEY15 = redacted"));
+
+        let config = lines.next().unwrap().strip_prefix("config = ").unwrap();
+        assert_eq!(105, config.split(' ').count());
+        assert!(config.starts_with("06 06"));
+        assert!(config.ends_with("07 07"));
+
+        assert_eq!(Some("status = 0x10 (state: RX, ready: true)"), lines.next());
+        assert_eq!(None, lines.next());
+    }
+
+    #[tokio::test]
+    async fn read_fifo() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x02]),
+            &[0x80 | 0x2F, 0xD7, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x22]), &[0xC0 | 0x3F]),
+            Operation::Read(make_static!([0x33, 0x44]))
+        ]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let mut buf = [0; 4];
+        let read = driver.read_fifo(&mut buf).await.unwrap();
+
+        // Then
+        assert_eq!(2, read);
+        assert_eq!([0x33, 0x44, 0x00, 0x00].as_ref(), buf);
+    }
+
+    #[tokio::test]
+    async fn read_fifo_raw() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x22]), &[0xC0 | 0x3F]),
+            Operation::Read(make_static!([0x33, 0x44]))
+        ]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let mut buf = [0; 2];
+        unsafe { driver.read_fifo_raw(&mut buf).await.unwrap() };
+
+        // Then
+        assert_eq!(0x22, driver.last_status.unwrap().0);
+        assert_eq!([0x33, 0x44].as_ref(), buf);
+    }
+
+    #[tokio::test]
+    async fn read_rssi_and_fifo_raw() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x11, 0x22, 0x33, 0x44]),
+            &[0x80 | 0x2F, 0x71, 0x00, 0xC0 | 0x3F, 0x00, 0x00]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let mut buf = [0; 2];
+        let rssi = unsafe {
+            driver
+                .read_rssi_and_fifo_raw(&mut buf)
+                .await
+                .unwrap()
+                .unwrap()
+        };
+
+        // Then
+        assert_eq!(0x11 - 99, rssi);
+        assert_eq!(0x22, driver.last_status.unwrap().0);
+        assert_eq!([0x33, 0x44].as_ref(), buf);
+    }
+
+    #[tokio::test]
+    async fn drain_fifo_0() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x22, 0x00, 0]),
+            &[0x80 | 0x2F, 0xD7, 0x00]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let discarded = driver.drain_fifo().await.unwrap();
+
+        // Then
+        assert_eq!(0, discarded);
+        assert_eq!(0x22, driver.last_status.unwrap().0);
+    }
+
+    #[tokio::test]
+    async fn drain_fifo_1() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 1]),
+            &[0x80 | 0x2F, 0xD7, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x22, 0x00]),
+            &[0xC0 | 0x3F, 0x00]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let discarded = driver.drain_fifo().await.unwrap();
+
+        // Then
+        assert_eq!(1, discarded);
+        assert_eq!(0x22, driver.last_status.unwrap().0);
+    }
+
+    #[tokio::test]
+    async fn drain_fifo_16() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 16]),
+            &[0x80 | 0x2F, 0xD7, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([
+                0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x00
+            ]),
+            &[
+                0xC0 | 0x3F,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00
+            ]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let discarded = driver.drain_fifo().await.unwrap();
+
+        // Then
+        assert_eq!(16, discarded);
+        assert_eq!(0x22, driver.last_status.unwrap().0);
+    }
+
+    #[tokio::test]
+    async fn drain_fifo_17() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 17]),
+            &[0x80 | 0x2F, 0xD7, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x00
+            ]),
+            &[
+                0xC0 | 0x3F,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x00
+            ]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x22, 0x00]),
+            &[0xC0 | 0x3F, 0x00]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let discarded = driver.drain_fifo().await.unwrap();
+
+        // Then
+        assert_eq!(17, discarded);
+        assert_eq!(0x22, driver.last_status.unwrap().0);
+    }
+
+    #[tokio::test]
+    async fn write_fifo() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
 
         spi.expect_transaction_operations(make_static!([
-            Operation::Transfer(make_static!([0x22]), &[0xC0 | 0x01]),
-            Operation::Read(make_static!([0x33, 0x44]))
+            Operation::Transfer(make_static!([0x22]), &[0x40 | 0x3F]),
+            Operation::Write(make_static!([0x33, 0x44]))
+        ]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        driver.write_fifo(&[0x33, 0x44]).await.unwrap();
+
+        // Then
+        assert_eq!(0x22, driver.last_status.unwrap().0);
+    }
+
+    #[tokio::test]
+    async fn write_fifo_available() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 126]),
+            &[0x80 | 0x2F, 0xD6, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x22]), &[0x40 | 0x3F]),
+            Operation::Write(make_static!([0x33, 0x44]))
+        ]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 128]), // Full
+            &[0x80 | 0x2F, 0xD6, 0x00]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let written = driver
+            .write_fifo_available(&[0x33, 0x44, 0x55])
+            .await
+            .unwrap();
+        let full = driver.write_fifo_available(&[0x55]).await.unwrap();
+
+        // Then
+        assert_eq!(2, written);
+        assert_eq!(0, full);
+    }
+
+    #[tokio::test]
+    async fn transmit_checked_out_of_lock() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x10]), // FS_LOCK_EN is set
+            &[0x80 | 0x20, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x30]), // FSTXON
+            &[0x31]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x30, 0x00, 0x00]), // FS is out of lock
+            &[0x80 | 0x2F, 0x8D, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00]), // IDLE
+            &[0x36]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00]),
+            &[0x3B]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let result = driver.transmit_checked(&[0x01, 0x02, 0x03]).await;
+
+        // Then
+        assert!(matches!(result, Err(DriverError::OutOfLock)));
+    }
+
+    #[tokio::test]
+    async fn transmit_checked_rejects_too_long_payload() {
+        // Given
+        let spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let result = driver.transmit_checked(&[0; TX_FIFO_SIZE + 1]).await;
+
+        // Then
+        assert!(matches!(result, Err(DriverError::InvalidPayloadLength)));
+    }
+
+    #[tokio::test]
+    async fn transmit_checked_times_out_when_fstxon_is_not_entered() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let mut delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x10]), // FS_LOCK_EN is set
+            &[0x80 | 0x20, 0x00]
+        )]));
+
+        for _ in 0..FSTXON_POLL_LIMIT {
+            spi.expect_transaction_operations(Box::leak(Box::new([Operation::Transfer(
+                Box::leak(Box::new([0x40])), // CALIBRATE
+                &[0x31],
+            )])));
+        }
+        delay
+            .expect_delay_us()
+            .times(FSTXON_POLL_LIMIT)
+            .return_const(());
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00]), // IDLE
+            &[0x36]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00]),
+            &[0x3B]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let result = driver.transmit_checked(&[0x01, 0x02, 0x03]).await;
+
+        // Then
+        assert!(matches!(result, Err(DriverError::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn configure_gpio() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x93]), // GPIO2_ATRAN and PKT_CRC_OK
+            &[0x80 | 0x01, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00]),
+            &[0x01, 0xC6] // GPIO2_ATRAN, GPIO2_INV and PKT_SYNC_RXTX
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        driver
+            .configure_gpio::<Gpio2>(GpioOutput::PKT_SYNC_RXTX, true)
+            .await
+            .unwrap();
+
+        // Then
+        assert_eq!(0x00, driver.last_status.unwrap().0);
+    }
+
+    #[tokio::test]
+    async fn use_external_40k_clock() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x46]), // GPIO3_INV and PKT_SYNC_RXTX
+            &[0x80, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00]),
+            &[0x00, 0x70] // GPIO3_INV and HIGHZ
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x01]),
+            &[0x80 | 0x2F, 0x06, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x00]),
+            &[0x2F, 0x06, 0x03] // EXT_40K_CLOCK_EN
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        driver.use_external_40k_clock().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn set_frequency_433() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x12]), // FS_LOCK_EN and 820 MHz band
+            &[0x80 | 0x20, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00]),
+            &[0x20, 0x14] // FS_LOCK_EN and 410 MHz band
+        )]));
+
+        // FREQ = 433.92MHz * 8 * 2^16 / 40MHz = 5687476.22
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x00, 0x00]), &[0x40 | 0x2F, 0x0C]),
+            Operation::Write(&[0x56, 0xC8, 0xB4])
+        ]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let achieved = driver.set_frequency(433_920_000).await.unwrap();
+
+        // Then
+        assert_eq!(433_919_983, achieved);
+    }
+
+    #[tokio::test]
+    async fn set_channel() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x41]), // MARC_2PIN_STATE = IDLE and IDLE
+            &[0x80 | 0x2F, 0x73, 0x00]
+        )]));
+
+        // FREQ = 869MHz * 4 * 2^16 / 40MHz = 5695078.4
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x00, 0x00]), &[0x40 | 0x2F, 0x0C]),
+            Operation::Write(&[0x56, 0xE6, 0x66])
+        ]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let achieved = driver.set_channel(868_000_000, 200_000, 5).await.unwrap();
+
+        // Then
+        assert_eq!(868_999_939, achieved);
+    }
+
+    #[tokio::test]
+    async fn set_channel_requires_idle() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x10, 0x00, 0x6D]), // MARC_2PIN_STATE = RX and RX
+            &[0x80 | 0x2F, 0x73, 0x00]
+        )]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let result = driver.set_channel(868_000_000, 200_000, 5).await;
+
+        // Then
+        assert!(matches!(result, Err(DriverError::NotIdle)));
+    }
+
+    #[tokio::test]
+    async fn hop() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x41]), // MARC_2PIN_STATE = IDLE and IDLE
+            &[0x80 | 0x2F, 0x73, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x00, 0x00]), &[0x40 | 0x2F, 0x0C]),
+            Operation::Write(&[0x56, 0xE6, 0x66])
         ]));
 
+        let table = HopTable::<8>::new(868_000_000, 200_000).unwrap();
+
         // When
         let mut driver: Driver<_, _> = Driver::new(spi, delay);
-        let mut buf = [0; 2];
-        driver.read_regs(Iocfg2::ADDRESS, &mut buf).await.unwrap();
+        driver.hop(&table, 5).await.unwrap();
 
         // Then
-        assert_eq!(0x22, driver.last_status.unwrap().0);
-        assert_eq!([0x33, 0x44].as_ref(), buf);
+        assert_eq!(8, table.len());
     }
 
     #[tokio::test]
-    async fn read_regs_extended() {
+    async fn hop_rejects_channel_outside_table() {
         // Given
-        let mut spi = MockSpiDevice::new();
+        let spi = MockSpiDevice::new();
         let delay = MockDelay::new();
 
-        spi.expect_transaction_operations(make_static!([
-            Operation::Transfer(make_static!([0x22, 0x00]), &[0xC0 | 0x2F, 0x01]),
-            Operation::Read(make_static!([0x33, 0x44]))
-        ]));
+        let table = HopTable::<8>::new(868_000_000, 200_000).unwrap();
 
         // When
         let mut driver: Driver<_, _> = Driver::new(spi, delay);
-        let mut buf = [0; 2];
-        driver
-            .read_regs(FreqoffCfg::ADDRESS, &mut buf)
-            .await
-            .unwrap();
+        let result = driver.hop(&table, 8).await;
 
         // Then
-        assert_eq!(0x22, driver.last_status.unwrap().0);
-        assert_eq!([0x33, 0x44].as_ref(), buf);
+        assert!(matches!(result, Err(DriverError::InvalidFrequency)));
+    }
+
+    #[test]
+    fn hop_table_must_be_within_band() {
+        assert!(matches!(
+            HopTable::<2>::new(959_500_000, 1_000_000),
+            Err(DriverError::InvalidFrequency)
+        ));
     }
 
     #[tokio::test]
-    async fn read_fifo_raw() {
+    async fn set_frequency_868() {
         // Given
         let mut spi = MockSpiDevice::new();
         let delay = MockDelay::new();
 
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x04]), // 410 MHz band
+            &[0x80 | 0x20, 0x00]
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00]),
+            &[0x20, 0x02] // 820 MHz band
+        )]));
+
+        // FREQ = 868MHz * 4 * 2^16 / 40MHz = 5688524.8
         spi.expect_transaction_operations(make_static!([
-            Operation::Transfer(make_static!([0x22]), &[0xC0 | 0x3F]),
-            Operation::Read(make_static!([0x33, 0x44]))
+            Operation::Transfer(make_static!([0x00, 0x00]), &[0x40 | 0x2F, 0x0C]),
+            Operation::Write(&[0x56, 0xCC, 0xCD])
         ]));
 
         // When
         let mut driver: Driver<_, _> = Driver::new(spi, delay);
-        let mut buf = [0; 2];
-        unsafe { driver.read_fifo_raw(&mut buf).await.unwrap() };
+        let achieved = driver.set_frequency(868_000_000).await.unwrap();
 
         // Then
-        assert_eq!(0x22, driver.last_status.unwrap().0);
-        assert_eq!([0x33, 0x44].as_ref(), buf);
+        assert_eq!(868_000_031, achieved);
     }
 
     #[tokio::test]
-    async fn read_rssi_and_fifo_raw() {
+    async fn set_frequency_outside_bands() {
+        // Given
+        let spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let result = driver.set_frequency(500_000_000).await;
+
+        // Then
+        assert!(matches!(result, Err(DriverError::InvalidFrequency)));
+    }
+
+    #[tokio::test]
+    async fn available_rx() {
         // Given
         let mut spi = MockSpiDevice::new();
         let delay = MockDelay::new();
 
         spi.expect_transaction_operations(make_static!([Operation::Transfer(
-            make_static!([0x00, 0x00, 0x11, 0x22, 0x33, 0x44]),
-            &[0x80 | 0x2F, 0x71, 0x00, 0xC0 | 0x3F, 0x00, 0x00]
+            make_static!([0x10, 0x00, 0x05]),
+            &[0x80 | 0x2F, 0xD7, 0x00]
         )]));
 
         // When
         let mut driver: Driver<_, _> = Driver::new(spi, delay);
-        let mut buf = [0; 2];
-        let rssi = unsafe {
-            driver
-                .read_rssi_and_fifo_raw(&mut buf)
-                .await
-                .unwrap()
-                .unwrap()
-        };
+        let available = driver.available_rx().await.unwrap();
 
         // Then
-        assert_eq!(0x11 - 99, rssi);
-        assert_eq!(0x22, driver.last_status.unwrap().0);
-        assert_eq!([0x33, 0x44].as_ref(), buf);
+        assert_eq!(5, available);
     }
 
     #[tokio::test]
-    async fn drain_fifo_0() {
+    async fn read_freq_offset_est() {
         // Given
         let mut spi = MockSpiDevice::new();
         let delay = MockDelay::new();
 
         spi.expect_transaction_operations(make_static!([Operation::Transfer(
-            make_static!([0x22, 0x00, 0]),
-            &[0x80 | 0x2F, 0xD7, 0x00]
+            make_static!([0x00, 0x12]), // 820 MHz band
+            &[0x80 | 0x20, 0x00]
         )]));
 
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x00, 0x00]), &[0xC0 | 0x2F, 0x77]),
+            Operation::Read(make_static!([0xFF, 0x00]))
+        ]));
+
         // When
         let mut driver: Driver<_, _> = Driver::new(spi, delay);
-        let discarded = driver.drain_fifo().await.unwrap();
+        let offset = driver.read_freq_offset_est().await.unwrap();
 
         // Then
-        assert_eq!(0, discarded);
-        assert_eq!(0x22, driver.last_status.unwrap().0);
+        // -256 * 40MHz / 4 / 2^18 = -9765.625 Hz
+        assert_eq!(-9765, offset);
     }
 
     #[tokio::test]
-    async fn drain_fifo_1() {
+    async fn read_rssi() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        // RSSI = -10 + 9/16 = -9.4375 dB
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x10, 0x00]), &[0xC0 | 0x2F, 0x71]),
+            Operation::Read(make_static!([-10i8 as u8, 9 << 3 | 0x01]))
+        ]));
+
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x10, 0x00]), &[0xC0 | 0x2F, 0x71]),
+            Operation::Read(make_static!([-10i8 as u8, 9 << 3])) // Not valid
+        ]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let valid = driver.read_rssi().await.unwrap();
+        let invalid = driver.read_rssi().await.unwrap();
+
+        // Then
+        assert_eq!(Some(-9 - 99), valid);
+        assert_eq!(None, invalid);
+    }
+
+    #[cfg(feature = "rssi-history")]
+    #[tokio::test]
+    async fn rssi_history_wraps_at_capacity() {
+        use crate::{RssiSample, RSSI_HISTORY_CAPACITY};
+        use core::sync::atomic::{AtomicU32, Ordering};
+
+        // Given
+        static TICK: AtomicU32 = AtomicU32::new(0);
+        let mut spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        for i in 0..RSSI_HISTORY_CAPACITY as u8 + 2 {
+            // make_static! cannot be used in a loop
+            let read = Box::leak(Box::new([i, 0x01])); // RSSI_VALID
+            spi.expect_transaction_operations(Box::leak(Box::new([
+                Operation::Transfer(Box::leak(Box::new([0x00, 0x00])), &[0xC0 | 0x2F, 0x71]),
+                Operation::Read(read),
+            ])));
+        }
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        driver.enable_rssi_history(|| TICK.fetch_add(1, Ordering::Relaxed));
+
+        driver.read_rssi().await.unwrap();
+        driver.read_rssi().await.unwrap();
+        let history = driver.rssi_history().unwrap();
+        assert_eq!(
+            [
+                RssiSample { tick: 0, rssi: -99 },
+                RssiSample { tick: 1, rssi: -98 }
+            ]
+            .as_slice(),
+            history.iter().copied().collect::<Vec<_>>()
+        );
+
+        for _ in 0..RSSI_HISTORY_CAPACITY {
+            driver.read_rssi().await.unwrap();
+        }
+
+        // Then
+        let history = driver.rssi_history().unwrap();
+        assert_eq!(RSSI_HISTORY_CAPACITY, history.len());
+        let first = history.iter().next().unwrap();
+        assert_eq!(RssiSample { tick: 2, rssi: -97 }, *first);
+        let last = history.iter().last().unwrap();
+        assert_eq!(
+            RssiSample {
+                tick: RSSI_HISTORY_CAPACITY as u32 + 1,
+                rssi: RSSI_HISTORY_CAPACITY as i16 + 1 - 99
+            },
+            *last
+        );
+    }
+
+    #[tokio::test]
+    async fn peek_rx() {
         // Given
         let mut spi = MockSpiDevice::new();
         let delay = MockDelay::new();
 
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x10, 0x00]), &[0xC0 | 0x2F, 0xD7]),
+            Operation::Read(make_static!([0x03, 0x00, 0x03, 0x20]))
+        ]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let (available, first) = driver.peek_rx().await.unwrap();
+
+        // Then
+        assert_eq!(3, available);
+        assert_eq!(Some(0x20), first);
+    }
+
+    #[tokio::test]
+    async fn measure_noise_floor() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let mut delay = MockDelay::new();
+        delay
+            .expect_delay_us()
+            .withf(|us| *us == 1000)
+            .times(4)
+            .return_const(());
+
         spi.expect_transaction_operations(make_static!([Operation::Transfer(
-            make_static!([0x00, 0x00, 1]),
-            &[0x80 | 0x2F, 0xD7, 0x00]
+            make_static!([0x10]), // RX
+            &[0x34]
         )]));
 
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x10, 0x00]), &[0xC0 | 0x2F, 0x71]),
+            Operation::Read(make_static!([-10i8 as u8, 0x01]))
+        ]));
+
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x10, 0x00]), &[0xC0 | 0x2F, 0x71]),
+            Operation::Read(make_static!([-128i8 as u8, 0x00])) // Invalid
+        ]));
+
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x10, 0x00]), &[0xC0 | 0x2F, 0x71]),
+            Operation::Read(make_static!([-12i8 as u8, 0x01]))
+        ]));
+
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x10, 0x00]), &[0xC0 | 0x2F, 0x71]),
+            Operation::Read(make_static!([-13i8 as u8, 0x01]))
+        ]));
+
         spi.expect_transaction_operations(make_static!([Operation::Transfer(
-            make_static!([0x22, 0x00]),
-            &[0xC0 | 0x3F, 0x00]
+            make_static!([0x00]), // IDLE
+            &[0x36]
         )]));
 
         // When
         let mut driver: Driver<_, _> = Driver::new(spi, delay);
-        let discarded = driver.drain_fifo().await.unwrap();
+        let noise_floor = driver.measure_noise_floor(4, 1000).await.unwrap();
 
         // Then
-        assert_eq!(1, discarded);
-        assert_eq!(0x22, driver.last_status.unwrap().0);
+        // The invalid reading is ignored: (-10 - 12 - 13) / 3 - 99
+        assert_eq!(-111, noise_floor);
     }
 
     #[tokio::test]
-    async fn drain_fifo_16() {
+    async fn measure_noise_floor_rejects_zero_samples() {
+        // Given
+        let spi = MockSpiDevice::new();
+        let delay = MockDelay::new();
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        let result = driver.measure_noise_floor(0, 1000).await;
+
+        // Then
+        assert!(matches!(result, Err(DriverError::InvalidSampleCount)));
+        assert!(driver.last_status.is_none());
+    }
+
+    #[tokio::test]
+    async fn aes_load_key() {
         // Given
         let mut spi = MockSpiDevice::new();
         let delay = MockDelay::new();
 
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x22, 0x00]), &[0x40 | 0x2F, 0xE0]),
+            Operation::Write(&[
+                0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D,
+                0x0E, 0x0F
+            ])
+        ]));
+
+        // When
+        let mut driver: Driver<_, _> = Driver::new(spi, delay);
+        driver
+            .aes_load_key(&[
+                0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D,
+                0x0E, 0x0F,
+            ])
+            .await
+            .unwrap();
+
+        // Then
+        assert_eq!(0x22, driver.last_status.unwrap().0);
+    }
+
+    #[tokio::test]
+    async fn aes_encrypt_block() {
+        // Given
+        let mut spi = MockSpiDevice::new();
+        let mut delay = MockDelay::new();
+
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x00, 0x00]), &[0x40 | 0x2F, 0xF0]),
+            Operation::Write(&[0x11; 16])
+        ]));
+
         spi.expect_transaction_operations(make_static!([Operation::Transfer(
-            make_static!([0x00, 0x00, 16]),
-            &[0x80 | 0x2F, 0xD7, 0x00]
+            make_static!([0x00, 0x00, 0x00]),
+            &[0x2F, 0xA1, 0x01] // AES_RUN
         )]));
 
         spi.expect_transaction_operations(make_static!([Operation::Transfer(
-            make_static!([
-                0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-                0x00, 0x00, 0x00
-            ]),
-            &[
-                0xC0 | 0x3F,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00
-            ]
+            make_static!([0x00, 0x00, 0x01]), // Running
+            &[0x80 | 0x2F, 0xA1, 0x00]
+        )]));
+        delay.expect_delay_us().times(1).return_const(());
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x00]), // Finished
+            &[0x80 | 0x2F, 0xA1, 0x00]
         )]));
 
+        spi.expect_transaction_operations(make_static!([
+            Operation::Transfer(make_static!([0x00, 0x00]), &[0xC0 | 0x2F, 0xF0]),
+            Operation::Read(make_static!([0x22; 16]))
+        ]));
+
         // When
         let mut driver: Driver<_, _> = Driver::new(spi, delay);
-        let discarded = driver.drain_fifo().await.unwrap();
+        let mut block = [0x11; 16];
+        driver.aes_encrypt_block(&mut block).await.unwrap();
 
         // Then
-        assert_eq!(16, discarded);
-        assert_eq!(0x22, driver.last_status.unwrap().0);
+        assert_eq!([0x22; 16], block);
     }
 
     #[tokio::test]
-    async fn drain_fifo_17() {
+    async fn aes_fifo() {
         // Given
         let mut spi = MockSpiDevice::new();
         let delay = MockDelay::new();
 
         spi.expect_transaction_operations(make_static!([Operation::Transfer(
-            make_static!([0x00, 0x00, 17]),
-            &[0x80 | 0x2F, 0xD7, 0x00]
+            make_static!([0x00, 0x00, 0x00]),
+            &[0x2F, 0x03, 0x09] // AES_TXFIFO
         )]));
 
         spi.expect_transaction_operations(make_static!([Operation::Transfer(
-            make_static!([
-                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-                0x00, 0x00, 0x00
-            ]),
-            &[
-                0xC0 | 0x3F,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00,
-                0x00
-            ]
+            make_static!([0x00]),
+            &[0x37]
         )]));
 
         spi.expect_transaction_operations(make_static!([Operation::Transfer(
-            make_static!([0x22, 0x00]),
-            &[0xC0 | 0x3F, 0x00]
+            make_static!([0x00, 0x00, 0x00]), // Finished
+            &[0x80 | 0x2F, 0xA1, 0x00]
         )]));
 
         // When
         let mut driver: Driver<_, _> = Driver::new(spi, delay);
-        let discarded = driver.drain_fifo().await.unwrap();
+        driver.aes_fifo(AesFifoCommand::TxFifo).await.unwrap();
 
         // Then
-        assert_eq!(17, discarded);
-        assert_eq!(0x22, driver.last_status.unwrap().0);
+        assert_eq!(0x00, driver.last_status.unwrap().0);
     }
 
     #[tokio::test]
-    async fn write_fifo() {
+    async fn aes_fifo_times_out_when_abort_does_not_complete() {
         // Given
         let mut spi = MockSpiDevice::new();
-        let delay = MockDelay::new();
+        let mut delay = MockDelay::new();
 
-        spi.expect_transaction_operations(make_static!([
-            Operation::Transfer(make_static!([0x22]), &[0x40 | 0x3F]),
-            Operation::Write(make_static!([0x33, 0x44]))
-        ]));
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x00]),
+            &[0x2F, 0x03, 0x09] // AES_TXFIFO
+        )]));
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00]),
+            &[0x37]
+        )]));
+
+        for _ in 0..AES_POLL_LIMIT {
+            spi.expect_transaction_operations(Box::leak(Box::new([Operation::Transfer(
+                Box::leak(Box::new([0x00, 0x00, 0x01])), // AES_RUN
+                &[0x80 | 0x2F, 0xA1, 0x00],
+            )])));
+        }
+
+        spi.expect_transaction_operations(make_static!([Operation::Transfer(
+            make_static!([0x00, 0x00, 0x00]),
+            &[0x2F, 0xA1, 0x02] // AES_ABORT
+        )]));
+
+        for _ in 0..AES_POLL_LIMIT {
+            spi.expect_transaction_operations(Box::leak(Box::new([Operation::Transfer(
+                Box::leak(Box::new([0x00, 0x00, 0x02])), // AES_ABORT
+                &[0x80 | 0x2F, 0xA1, 0x00],
+            )])));
+        }
+
+        // The delay is awaited between all polls, so the timeout does not depend on the spi clock
+        delay
+            .expect_delay_us()
+            .times(2 * AES_POLL_LIMIT)
+            .return_const(());
 
         // When
         let mut driver: Driver<_, _> = Driver::new(spi, delay);
-        driver.write_fifo(&[0x33, 0x44]).await.unwrap();
+        let result = driver.aes_fifo(AesFifoCommand::TxFifo).await;
 
         // Then
-        assert_eq!(0x22, driver.last_status.unwrap().0);
+        assert!(matches!(result, Err(DriverError::AesTimeout)));
     }
 
     #[tokio::test]
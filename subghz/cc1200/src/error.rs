@@ -3,7 +3,59 @@
 pub enum DriverError {
     Timeout,
     InvalidPartNumber,
+    /// The chip is not the expected part
+    PartNumberMismatch,
     Spi,
+    Format,
+    OutOfLock,
+    /// The frequency is outside all supported bands
+    InvalidFrequency,
+    /// The AES engine did not finish in time, so the encryption was aborted
+    AesTimeout,
+    /// The operation requires the chip to be in IDLE
+    NotIdle,
+    /// The address is not an extended register address
+    InvalidAddress,
+    /// The number of samples to measure must be at least one
+    InvalidSampleCount,
+    /// The payload is empty or too long for the operation
+    InvalidPayloadLength,
+    /// The carrier sense threshold is outside the range of `AGC_CS_THR` at the current rssi offset
+    InvalidCarrierSenseThreshold,
+}
+
+/// An unsupported combination of configuration values
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConfigError {
+    /// FEC is only supported for 2'ary modulation formats
+    FecRequires2aryModulation,
+    /// FEC is not supported in infinite packet length mode
+    FecInInfinitePacketLengthMode,
+    /// The ASK depth would bring the low level below the minimum PA power of -16 dBm
+    AskDepthBelowMinimumPower,
+    /// FOC in the frequency synthesizer needs at least 2 preamble bytes to settle
+    InsufficientPreambleForFocInFs,
+    /// The synthesizer loop bandwidth is not one of 200, 300, 400 or 500 kHz
+    InvalidLoopBandwidth,
+    /// The RSSI valid count is not one of 1, 2, 5 or 9
+    InvalidRssiValidCount,
+    /// The preamble quality threshold is above 15
+    InvalidPqtThreshold,
+    /// The sync word threshold is above 31
+    InvalidSyncThreshold,
+    /// The RX filter bandwidth is zero
+    InvalidRxFilterBandwidth,
+    /// The symbol rate is zero, or too high for `SYMBOL_RATE2.SRATE_E`
+    InvalidSymbolRate,
+    /// The frequency deviation is too high for `MODCFG_DEV_E.DEV_E`
+    InvalidDeviation,
+    /// The frequency is outside all supported bands
+    InvalidFrequency,
+    /// The sniff period is too long to be represented by `WOR_EVENT0` at the lowest eWOR resolution
+    SniffPeriodTooLong,
+    /// The RX timeout does not fit in `WOR_EVENT0` at the current eWOR resolution
+    RxTimeoutTooLong,
 }
 
 impl<SpiError> From<SpiError> for DriverError
@@ -302,6 +302,11 @@ gpio_output!(
     ADC_CLOCK = 46
 );
 
+impl Gpio3Output {
+    /// GPIO3 is the input for an external 40 kHz clock when `EXT_CTRL.EXT_40K_CLOCK_EN` is set
+    pub const EXT_40K_CLOCK: Self = Self::HIGHZ;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -14,11 +14,16 @@ extern crate bitfield;
 #[macro_use]
 extern crate num_derive;
 
+#[cfg(feature = "blocking")]
+mod blocking;
 mod config;
 mod driver;
 mod error;
 pub mod gpio;
 pub mod regs;
+#[cfg(feature = "rssi-history")]
+mod rssi_history;
+mod snapshot;
 mod statusbyte;
 
 mod cmd;
@@ -36,10 +41,22 @@ pub type Rssi = i16;
 pub const RX_FIFO_SIZE: usize = 128;
 pub const TX_FIFO_SIZE: usize = 128;
 
+/// The crystal oscillator frequency in Hz
+pub const XOSC_FREQUENCY: u32 = 40_000_000;
+
 pub use self::{
     cmd::Strobe,
-    config::{Config, ConfigPatch},
-    driver::{CalibrationValue, Driver},
-    error::DriverError,
+    config::{
+        BroadcastMode, Config, ConfigPatch, FocMode, FrontendMode, ModemMode, SymbolMap, WorMode,
+    },
+    driver::{AesFifoCommand, CalibrationValue, Driver, FsCalibration, HopTable, AES_BLOCK_SIZE},
+    error::{ConfigError, DriverError},
+    snapshot::RegisterSnapshot,
     statusbyte::{State, StatusByte},
 };
+
+#[cfg(feature = "blocking")]
+pub use self::blocking::BlockingDriver;
+
+#[cfg(feature = "rssi-history")]
+pub use self::rssi_history::{RssiHistory, RssiSample, RSSI_HISTORY_CAPACITY};
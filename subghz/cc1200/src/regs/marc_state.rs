@@ -1,7 +1,10 @@
 use core::mem::transmute;
 
+use crate::Strobe;
+
 use super::ext::Marcstate;
 
+/// The main radio control state in `MARCSTATE.MARC_STATE`
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[allow(non_camel_case_types)]
 pub enum MarcStateValue {
@@ -22,11 +25,13 @@ pub enum MarcStateValue {
     RX_END = 0b01110,
     RXDCM = 0b01111,
     TXRX_SWITCH = 0b10000,
+    /// RX fifo overflow or underflow, which is only left with an SFRX strobe
     RX_FIFO_ERR = 0b10001,
     FSTXON = 0b10010,
     TX = 0b10011,
     TX_END = 0b10100,
     RXTX_SWITCH = 0b10101,
+    /// TX fifo overflow or underflow, which is only left with an SFTX strobe
     TX_FIFO_ERR = 0b10110,
     IFADCON_TXRX = 0b10111,
     Reserved_11000 = 0b11000,
@@ -39,6 +44,17 @@ pub enum MarcStateValue {
     Reserved_11111 = 0b11111,
 }
 
+impl MarcStateValue {
+    /// Get the strobe required to leave a fifo error state, or None if the state is not an error state.
+    pub const fn recovery_strobe(&self) -> Option<Strobe> {
+        match self {
+            MarcStateValue::RX_FIFO_ERR => Some(Strobe::SFRX),
+            MarcStateValue::TX_FIFO_ERR => Some(Strobe::SFTX),
+            _ => None,
+        }
+    }
+}
+
 impl Marcstate {
     pub fn marc_state(&self) -> MarcStateValue {
         unsafe { transmute(self.marc_state_bits()) }
@@ -5,8 +5,10 @@ use crate::gpio::{Gpio0Output, Gpio1Output, Gpio2Output, Gpio3Output, GpioOutput
 mod generated;
 mod marc_state;
 mod register_address;
+mod register_names;
 pub use generated::*;
 pub use marc_state::MarcStateValue;
+pub(crate) use register_names::REGISTER_NAMES;
 
 use self::pri::{FifoCfg, Iocfg0, Iocfg1, Iocfg2, Iocfg3};
 
@@ -0,0 +1,238 @@
+use super::RegisterAddress;
+
+/// The datasheet names of all known registers, ordered by address.
+pub(crate) const REGISTER_NAMES: [(RegisterAddress, &str); 209] = [
+    (RegisterAddress(0x0000), "IOCFG3"),
+    (RegisterAddress(0x0001), "IOCFG2"),
+    (RegisterAddress(0x0002), "IOCFG1"),
+    (RegisterAddress(0x0003), "IOCFG0"),
+    (RegisterAddress(0x0004), "SYNC3"),
+    (RegisterAddress(0x0005), "SYNC2"),
+    (RegisterAddress(0x0006), "SYNC1"),
+    (RegisterAddress(0x0007), "SYNC0"),
+    (RegisterAddress(0x0008), "SYNC_CFG1"),
+    (RegisterAddress(0x0009), "SYNC_CFG0"),
+    (RegisterAddress(0x000A), "DEVIATION_M"),
+    (RegisterAddress(0x000B), "MODCFG_DEV_E"),
+    (RegisterAddress(0x000C), "DCFILT_CFG"),
+    (RegisterAddress(0x000D), "PREAMBLE_CFG1"),
+    (RegisterAddress(0x000E), "PREAMBLE_CFG0"),
+    (RegisterAddress(0x000F), "IQIC"),
+    (RegisterAddress(0x0010), "CHAN_BW"),
+    (RegisterAddress(0x0011), "MDMCFG1"),
+    (RegisterAddress(0x0012), "MDMCFG0"),
+    (RegisterAddress(0x0013), "SYMBOL_RATE2"),
+    (RegisterAddress(0x0014), "SYMBOL_RATE1"),
+    (RegisterAddress(0x0015), "SYMBOL_RATE0"),
+    (RegisterAddress(0x0016), "AGC_REF"),
+    (RegisterAddress(0x0017), "AGC_CS_THR"),
+    (RegisterAddress(0x0018), "AGC_GAIN_ADJUST"),
+    (RegisterAddress(0x0019), "AGC_CFG3"),
+    (RegisterAddress(0x001A), "AGC_CFG2"),
+    (RegisterAddress(0x001B), "AGC_CFG1"),
+    (RegisterAddress(0x001C), "AGC_CFG0"),
+    (RegisterAddress(0x001D), "FIFO_CFG"),
+    (RegisterAddress(0x001E), "DEV_ADDR"),
+    (RegisterAddress(0x001F), "SETTLING_CFG"),
+    (RegisterAddress(0x0020), "FS_CFG"),
+    (RegisterAddress(0x0021), "WOR_CFG1"),
+    (RegisterAddress(0x0022), "WOR_CFG0"),
+    (RegisterAddress(0x0023), "WOR_EVENT0_MSB"),
+    (RegisterAddress(0x0024), "WOR_EVENT0_LSB"),
+    (RegisterAddress(0x0025), "RXDCM_TIME"),
+    (RegisterAddress(0x0026), "PKT_CFG2"),
+    (RegisterAddress(0x0027), "PKT_CFG1"),
+    (RegisterAddress(0x0028), "PKT_CFG0"),
+    (RegisterAddress(0x0029), "RFEND_CFG1"),
+    (RegisterAddress(0x002A), "RFEND_CFG0"),
+    (RegisterAddress(0x002B), "PA_CFG1"),
+    (RegisterAddress(0x002C), "PA_CFG0"),
+    (RegisterAddress(0x002D), "ASK_CFG"),
+    (RegisterAddress(0x002E), "PKT_LEN"),
+    (RegisterAddress(0x2F00), "IF_MIX_CFG"),
+    (RegisterAddress(0x2F01), "FREQOFF_CFG"),
+    (RegisterAddress(0x2F02), "TOC_CFG"),
+    (RegisterAddress(0x2F03), "MARC_SPARE"),
+    (RegisterAddress(0x2F04), "ECG_CFG"),
+    (RegisterAddress(0x2F05), "MDMCFG2"),
+    (RegisterAddress(0x2F06), "EXT_CTRL"),
+    (RegisterAddress(0x2F07), "RCCAL_FINE"),
+    (RegisterAddress(0x2F08), "RCCAL_COARSE"),
+    (RegisterAddress(0x2F09), "RCCAL_OFFSET"),
+    (RegisterAddress(0x2F0A), "FREQOFF1"),
+    (RegisterAddress(0x2F0B), "FREQOFF0"),
+    (RegisterAddress(0x2F0C), "FREQ2"),
+    (RegisterAddress(0x2F0D), "FREQ1"),
+    (RegisterAddress(0x2F0E), "FREQ0"),
+    (RegisterAddress(0x2F0F), "IF_ADC2"),
+    (RegisterAddress(0x2F10), "IF_ADC1"),
+    (RegisterAddress(0x2F11), "IF_ADC0"),
+    (RegisterAddress(0x2F12), "FS_DIG1"),
+    (RegisterAddress(0x2F13), "FS_DIG0"),
+    (RegisterAddress(0x2F14), "FS_CAL3"),
+    (RegisterAddress(0x2F15), "FS_CAL2"),
+    (RegisterAddress(0x2F16), "FS_CAL1"),
+    (RegisterAddress(0x2F17), "FS_CAL0"),
+    (RegisterAddress(0x2F18), "FS_CHP"),
+    (RegisterAddress(0x2F19), "FS_DIVTWO"),
+    (RegisterAddress(0x2F1A), "FS_DSM1"),
+    (RegisterAddress(0x2F1B), "FS_DSM0"),
+    (RegisterAddress(0x2F1C), "FS_DVC1"),
+    (RegisterAddress(0x2F1D), "FS_DVC0"),
+    (RegisterAddress(0x2F1E), "FS_LBI"),
+    (RegisterAddress(0x2F1F), "FS_PFD"),
+    (RegisterAddress(0x2F20), "FS_PRE"),
+    (RegisterAddress(0x2F21), "FS_REG_DIV_CML"),
+    (RegisterAddress(0x2F22), "FS_SPARE"),
+    (RegisterAddress(0x2F23), "FS_VCO4"),
+    (RegisterAddress(0x2F24), "FS_VCO3"),
+    (RegisterAddress(0x2F25), "FS_VCO2"),
+    (RegisterAddress(0x2F26), "FS_VCO1"),
+    (RegisterAddress(0x2F27), "FS_VCO0"),
+    (RegisterAddress(0x2F28), "GBIAS6"),
+    (RegisterAddress(0x2F29), "GBIAS5"),
+    (RegisterAddress(0x2F2A), "GBIAS4"),
+    (RegisterAddress(0x2F2B), "GBIAS3"),
+    (RegisterAddress(0x2F2C), "GBIAS2"),
+    (RegisterAddress(0x2F2D), "GBIAS1"),
+    (RegisterAddress(0x2F2E), "GBIAS0"),
+    (RegisterAddress(0x2F2F), "IFAMP"),
+    (RegisterAddress(0x2F30), "LNA"),
+    (RegisterAddress(0x2F31), "RXMIX"),
+    (RegisterAddress(0x2F32), "XOSC5"),
+    (RegisterAddress(0x2F33), "XOSC4"),
+    (RegisterAddress(0x2F34), "XOSC3"),
+    (RegisterAddress(0x2F35), "XOSC2"),
+    (RegisterAddress(0x2F36), "XOSC1"),
+    (RegisterAddress(0x2F37), "XOSC0"),
+    (RegisterAddress(0x2F38), "ANALOG_SPARE"),
+    (RegisterAddress(0x2F39), "PA_CFG3"),
+    (RegisterAddress(0x2F64), "WOR_TIME1"),
+    (RegisterAddress(0x2F65), "WOR_TIME0"),
+    (RegisterAddress(0x2F66), "WOR_CAPTURE1"),
+    (RegisterAddress(0x2F67), "WOR_CAPTURE0"),
+    (RegisterAddress(0x2F68), "BIST"),
+    (RegisterAddress(0x2F69), "DCFILTOFFSET_I1"),
+    (RegisterAddress(0x2F6A), "DCFILTOFFSET_I0"),
+    (RegisterAddress(0x2F6B), "DCFILTOFFSET_Q1"),
+    (RegisterAddress(0x2F6C), "DCFILTOFFSET_Q0"),
+    (RegisterAddress(0x2F6D), "IQIE_I1"),
+    (RegisterAddress(0x2F6E), "IQIE_I0"),
+    (RegisterAddress(0x2F6F), "IQIE_Q1"),
+    (RegisterAddress(0x2F70), "IQIE_Q0"),
+    (RegisterAddress(0x2F71), "RSSI1"),
+    (RegisterAddress(0x2F72), "RSSI0"),
+    (RegisterAddress(0x2F73), "MARCSTATE"),
+    (RegisterAddress(0x2F74), "LQI_VAL"),
+    (RegisterAddress(0x2F75), "PQT_SYNC_ERR"),
+    (RegisterAddress(0x2F76), "DEM_STATUS"),
+    (RegisterAddress(0x2F77), "FREQOFF_EST1"),
+    (RegisterAddress(0x2F78), "FREQOFF_EST0"),
+    (RegisterAddress(0x2F79), "AGC_GAIN3"),
+    (RegisterAddress(0x2F7A), "AGC_GAIN2"),
+    (RegisterAddress(0x2F7B), "AGC_GAIN1"),
+    (RegisterAddress(0x2F7C), "AGC_GAIN0"),
+    (RegisterAddress(0x2F7D), "CFM_RX_DATA_OUT"),
+    (RegisterAddress(0x2F7E), "CFM_TX_DATA_IN"),
+    (RegisterAddress(0x2F7F), "ASK_SOFT_RX_DATA"),
+    (RegisterAddress(0x2F80), "RNDGEN"),
+    (RegisterAddress(0x2F81), "MAGN2"),
+    (RegisterAddress(0x2F82), "MAGN1"),
+    (RegisterAddress(0x2F83), "MAGN0"),
+    (RegisterAddress(0x2F84), "ANG1"),
+    (RegisterAddress(0x2F85), "ANG0"),
+    (RegisterAddress(0x2F86), "CHFILT_I2"),
+    (RegisterAddress(0x2F87), "CHFILT_I1"),
+    (RegisterAddress(0x2F88), "CHFILT_I0"),
+    (RegisterAddress(0x2F89), "CHFILT_Q2"),
+    (RegisterAddress(0x2F8A), "CHFILT_Q1"),
+    (RegisterAddress(0x2F8B), "CHFILT_Q0"),
+    (RegisterAddress(0x2F8C), "GPIO_STATUS"),
+    (RegisterAddress(0x2F8D), "FSCAL_CTRL"),
+    (RegisterAddress(0x2F8E), "PHASE_ADJUST"),
+    (RegisterAddress(0x2F8F), "PARTNUMBER"),
+    (RegisterAddress(0x2F90), "PARTVERSION"),
+    (RegisterAddress(0x2F91), "SERIAL_STATUS"),
+    (RegisterAddress(0x2F92), "MODEM_STATUS1"),
+    (RegisterAddress(0x2F93), "MODEM_STATUS0"),
+    (RegisterAddress(0x2F94), "MARC_STATUS1"),
+    (RegisterAddress(0x2F95), "MARC_STATUS0"),
+    (RegisterAddress(0x2F96), "PA_IFAMP_TEST"),
+    (RegisterAddress(0x2F97), "FSRF_TEST"),
+    (RegisterAddress(0x2F98), "PRE_TEST"),
+    (RegisterAddress(0x2F99), "PRE_OVR"),
+    (RegisterAddress(0x2F9A), "ADC_TEST"),
+    (RegisterAddress(0x2F9B), "DVC_TEST"),
+    (RegisterAddress(0x2F9C), "ATEST"),
+    (RegisterAddress(0x2F9D), "ATEST_LVDS"),
+    (RegisterAddress(0x2F9E), "ATEST_MODE"),
+    (RegisterAddress(0x2F9F), "XOSC_TEST1"),
+    (RegisterAddress(0x2FA0), "XOSC_TEST0"),
+    (RegisterAddress(0x2FA1), "AES"),
+    (RegisterAddress(0x2FA2), "MDM_TEST"),
+    (RegisterAddress(0x2FD2), "RXFIRST"),
+    (RegisterAddress(0x2FD3), "TXFIRST"),
+    (RegisterAddress(0x2FD4), "RXLAST"),
+    (RegisterAddress(0x2FD5), "TXLAST"),
+    (RegisterAddress(0x2FD6), "NUM_TXBYTES"),
+    (RegisterAddress(0x2FD7), "NUM_RXBYTES"),
+    (RegisterAddress(0x2FD8), "FIFO_NUM_TXBYTES"),
+    (RegisterAddress(0x2FD9), "FIFO_NUM_RXBYTES"),
+    (RegisterAddress(0x2FDA), "RXFIFO_PRE_BUF"),
+    (RegisterAddress(0x2FE0), "AES_KEY15"),
+    (RegisterAddress(0x2FE1), "AES_KEY14"),
+    (RegisterAddress(0x2FE2), "AES_KEY13"),
+    (RegisterAddress(0x2FE3), "AES_KEY12"),
+    (RegisterAddress(0x2FE4), "AES_KEY11"),
+    (RegisterAddress(0x2FE5), "AES_KEY10"),
+    (RegisterAddress(0x2FE6), "AES_KEY9"),
+    (RegisterAddress(0x2FE7), "AES_KEY8"),
+    (RegisterAddress(0x2FE8), "AES_KEY7"),
+    (RegisterAddress(0x2FE9), "AES_KEY6"),
+    (RegisterAddress(0x2FEA), "AES_KEY5"),
+    (RegisterAddress(0x2FEB), "AES_KEY4"),
+    (RegisterAddress(0x2FEC), "AES_KEY3"),
+    (RegisterAddress(0x2FED), "AES_KEY2"),
+    (RegisterAddress(0x2FEE), "AES_KEY1"),
+    (RegisterAddress(0x2FEF), "AES_KEY0"),
+    (RegisterAddress(0x2FF0), "AES_BUFFER15"),
+    (RegisterAddress(0x2FF1), "AES_BUFFER14"),
+    (RegisterAddress(0x2FF2), "AES_BUFFER13"),
+    (RegisterAddress(0x2FF3), "AES_BUFFER12"),
+    (RegisterAddress(0x2FF4), "AES_BUFFER11"),
+    (RegisterAddress(0x2FF5), "AES_BUFFER10"),
+    (RegisterAddress(0x2FF6), "AES_BUFFER9"),
+    (RegisterAddress(0x2FF7), "AES_BUFFER8"),
+    (RegisterAddress(0x2FF8), "AES_BUFFER7"),
+    (RegisterAddress(0x2FF9), "AES_BUFFER6"),
+    (RegisterAddress(0x2FFA), "AES_BUFFER5"),
+    (RegisterAddress(0x2FFB), "AES_BUFFER4"),
+    (RegisterAddress(0x2FFC), "AES_BUFFER3"),
+    (RegisterAddress(0x2FFD), "AES_BUFFER2"),
+    (RegisterAddress(0x2FFE), "AES_BUFFER1"),
+    (RegisterAddress(0x2FFF), "AES_BUFFER0"),
+];
+
+impl RegisterAddress {
+    /// Get the datasheet name of the register at this address.
+    pub fn name(&self) -> Option<&'static str> {
+        REGISTER_NAMES
+            .binary_search_by_key(&self.0, |(address, _)| address.0)
+            .ok()
+            .map(|index| REGISTER_NAMES[index].1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regs::{ext::Partnumber, pri::Iocfg3, Register};
+
+    use super::*;
+
+    #[test]
+    fn name() {
+        assert_eq!(Some("IOCFG3"), Iocfg3::ADDRESS.name());
+        assert_eq!(Some("PARTNUMBER"), Partnumber::ADDRESS.name());
+        assert_eq!(None, RegisterAddress(0x2F3A).name());
+    }
+}
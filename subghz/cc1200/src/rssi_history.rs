@@ -0,0 +1,59 @@
+use crate::Rssi;
+
+/// The number of samples kept in the RSSI history.
+pub const RSSI_HISTORY_CAPACITY: usize = 64;
+
+/// A timestamped RSSI reading.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RssiSample {
+    pub tick: u32,
+    pub rssi: Rssi,
+}
+
+/// Fixed capacity ring of RSSI readings, where the oldest sample is overwritten when full.
+pub struct RssiHistory {
+    clock: fn() -> u32,
+    samples: [RssiSample; RSSI_HISTORY_CAPACITY],
+    head: usize,
+    len: usize,
+}
+
+impl RssiHistory {
+    pub(crate) fn new(clock: fn() -> u32) -> Self {
+        Self {
+            clock,
+            samples: [RssiSample { tick: 0, rssi: 0 }; RSSI_HISTORY_CAPACITY],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub(crate) fn record(&mut self, rssi: Rssi) {
+        let tick = (self.clock)();
+        self.samples[self.head] = RssiSample { tick, rssi };
+        self.head = (self.head + 1) % RSSI_HISTORY_CAPACITY;
+        self.len = (self.len + 1).min(RSSI_HISTORY_CAPACITY);
+    }
+
+    /// The number of samples in the history.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove all samples from the history.
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Iterate the samples from the oldest to the newest.
+    pub fn iter(&self) -> impl Iterator<Item = &RssiSample> {
+        let start = (self.head + RSSI_HISTORY_CAPACITY - self.len) % RSSI_HISTORY_CAPACITY;
+        (0..self.len).map(move |i| &self.samples[(start + i) % RSSI_HISTORY_CAPACITY])
+    }
+}
@@ -0,0 +1,79 @@
+use core::fmt;
+
+use crate::{
+    regs::{Register, RegisterAddress},
+    Config,
+};
+
+const PRI_MIN: RegisterAddress = RegisterAddress::PRI_MIN;
+const PRI_MAX: RegisterAddress = RegisterAddress::PRI_MAX;
+const EXT_MIN: RegisterAddress = RegisterAddress::EXT_MIN;
+const EXT_MAX: RegisterAddress = RegisterAddress::EXT_MAX;
+
+/// A snapshot of the configuration registers read from the chip,
+/// i.e. the primary registers 0x00-0x2E and the extended registers 0x2F00-0x2F39.
+///
+/// The `Debug` and `defmt::Format` implementations list each register by its datasheet name.
+pub struct RegisterSnapshot(pub Config);
+
+impl RegisterSnapshot {
+    /// Get a register value from the snapshot.
+    pub fn get<R: Register>(&self) -> R {
+        self.0.get()
+    }
+
+    /// Get the addresses and values of all registers in the snapshot, ordered by address.
+    pub fn registers(&self) -> impl Iterator<Item = (RegisterAddress, u8)> + '_ {
+        (PRI_MIN.0..=PRI_MAX.0)
+            .chain(EXT_MIN.0..=EXT_MAX.0)
+            .map(|address| {
+                let address = RegisterAddress(address);
+                (address, self.0 .0[address.idx()])
+            })
+    }
+
+    /// Get the address, the snapshot value, and the expected value of the registers that differ from `expected`,
+    /// e.g. one of the configurations in [`crate::configs`] or a SmartRF export.
+    pub fn diff<'a>(
+        &'a self,
+        expected: &'a Config,
+    ) -> impl Iterator<Item = (RegisterAddress, u8, u8)> + 'a {
+        self.registers()
+            .map(|(address, value)| (address, value, expected.0[address.idx()]))
+            .filter(|(_, value, expected)| value != expected)
+    }
+}
+
+impl fmt::Debug for RegisterSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "RegisterSnapshot")?;
+        for (address, value) in self.registers() {
+            write_register(f, address, Some(value))?;
+        }
+        Ok(())
+    }
+}
+
+/// Write a register dump line with the datasheet name of the register, or `redacted` if there is no value.
+pub(crate) fn write_register(
+    out: &mut impl fmt::Write,
+    address: RegisterAddress,
+    value: Option<u8>,
+) -> fmt::Result {
+    let name = address.name().unwrap_or("?");
+    match value {
+        Some(value) => writeln!(out, "0x{:04X} {} = 0x{:02X}", address.0, name, value),
+        None => writeln!(out, "0x{:04X} {} = redacted", address.0, name),
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RegisterSnapshot {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "RegisterSnapshot");
+        for (address, value) in self.registers() {
+            let name = address.name().unwrap_or("?");
+            defmt::write!(fmt, "\n0x{:04X} {=str} = 0x{:02X}", address.0, name, value);
+        }
+    }
+}
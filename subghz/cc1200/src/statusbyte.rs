@@ -45,6 +45,25 @@ impl StatusByte {
     pub fn chip_rdy(self) -> bool {
         !self.chip_rdyn()
     }
+
+    /// Log the state if it differs from the state in the `previous` status byte.
+    ///
+    /// FIFO errors are logged as warnings as the chip stays in the error state until the FIFO is flushed.
+    #[cfg(feature = "defmt")]
+    pub(crate) fn log_transition(self, previous: Option<StatusByte>) {
+        let state = self.state();
+        let previous = previous.map(|status| status.state());
+        if previous == Some(state) {
+            return;
+        }
+
+        match state {
+            State::RX_FIFO_ERROR | State::TX_FIFO_ERROR => {
+                defmt::warn!("cc1200 state {} -> {}", previous, state)
+            }
+            _ => defmt::debug!("cc1200 state {} -> {}", previous, state),
+        }
+    }
 }
 
 #[cfg(test)]